@@ -0,0 +1,135 @@
+//! Throughput of the `evm_logs` insert paths against the Postgres at `DATABASE_URL`.
+//!
+//! Run with `cargo bench --bench insert_logs`, optionally passing the number of logs
+//! per run: `cargo bench --bench insert_logs -- 50000`. Rows are written under a
+//! chain id no real chain uses and deleted after every run.
+
+use std::time::{Duration, Instant};
+
+use alloy::{
+    primitives::{Address, B256, Bytes},
+    rpc::types::Log,
+};
+use blockchain_indexer::{
+    infrastructure::database::pgsql::new_database_connection,
+    services::{
+        dtos::index_logs::IngestRange,
+        repository::{
+            EVMLogsRepository, IngestionRepository,
+            evm_logs::evm_log_repository::{EVMLogsRepositoryImpl, MAX_INSERT_BATCH_SIZE},
+            ingestion::ingestion_repository::IngestionRepositoryImpl,
+        },
+    },
+};
+use sqlx::PgPool;
+
+const BENCH_CHAIN_ID: u64 = 4_000_000_001;
+
+const DEFAULT_LOG_COUNT: usize = 20_000;
+
+/// Logs spread over blocks of 50, each with four topics and 64 bytes of data.
+fn synthetic_logs(count: usize) -> Vec<Log> {
+    (0..count)
+        .map(|i| {
+            let block_number = (i / 50) as u64;
+            let topics = (0..4)
+                .map(|topic| B256::left_padding_from(&((i * 4 + topic) as u64).to_be_bytes()))
+                .collect();
+            let inner = alloy::primitives::Log::new(
+                Address::repeat_byte(0x11),
+                topics,
+                Bytes::from(vec![0xab; 64]),
+            )
+            .expect("at most four topics");
+
+            Log {
+                inner,
+                block_number: Some(block_number),
+                block_hash: Some(B256::left_padding_from(&block_number.to_be_bytes())),
+                block_timestamp: Some(1_700_000_000 + block_number),
+                transaction_hash: Some(B256::left_padding_from(&(i as u64).to_be_bytes())),
+                transaction_index: Some(0),
+                log_index: Some(i as u64),
+                removed: false,
+            }
+        })
+        .collect()
+}
+
+async fn clear(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM evm_logs WHERE chain_id = $1")
+        .bind(BENCH_CHAIN_ID as i64)
+        .execute(pool)
+        .await?;
+
+    Ok(())
+}
+
+fn report(name: &str, count: usize, elapsed: Duration) {
+    println!(
+        "{name:<32} {count:>8} logs in {:>8.2?} {:>10.0} logs/s",
+        elapsed,
+        count as f64 / elapsed.as_secs_f64()
+    );
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenvy::dotenv().ok();
+    let Ok(database_url) = std::env::var("DATABASE_URL") else {
+        println!("DATABASE_URL is not set, skipping the insert benchmark");
+        return Ok(());
+    };
+
+    let count = std::env::args()
+        .skip(1)
+        .find_map(|arg| arg.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_LOG_COUNT);
+    let pool = new_database_connection(&database_url, 4).await?;
+    let logs = synthetic_logs(count);
+    clear(&pool).await?;
+
+    // The row-by-row path is slow enough to only time a slice of the logs
+    let log_repo = EVMLogsRepositoryImpl::new(pool.clone());
+    let sample = count.min(2_000);
+    let started = Instant::now();
+    for log in &logs[..sample] {
+        log_repo.create(BENCH_CHAIN_ID, log.clone(), false).await?;
+    }
+    report("create, one log per statement", sample, started.elapsed());
+    clear(&pool).await?;
+
+    for batch_size in [100, 1_000, MAX_INSERT_BATCH_SIZE] {
+        let ingestion_repo = IngestionRepositoryImpl::new(pool.clone(), batch_size);
+        let range = IngestRange {
+            chain_id: BENCH_CHAIN_ID,
+            blocks: Vec::new(),
+            logs: logs.clone(),
+            confirmed_block: u64::MAX,
+            transactions: Vec::new(),
+            cursors: Vec::new(),
+            ranges: Vec::new(),
+        };
+
+        let started = Instant::now();
+        let inserted = ingestion_repo.ingest_range(range.clone()).await?;
+        report(
+            &format!("ingest_range, batches of {batch_size}"),
+            inserted as usize,
+            started.elapsed(),
+        );
+
+        // Replaying the range only hits conflicts
+        let started = Instant::now();
+        ingestion_repo.ingest_range(range).await?;
+        report(
+            &format!("  replayed, batches of {batch_size}"),
+            count,
+            started.elapsed(),
+        );
+
+        clear(&pool).await?;
+    }
+
+    Ok(())
+}
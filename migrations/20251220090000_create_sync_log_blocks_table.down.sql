@@ -0,0 +1 @@
+DROP TABLE IF EXISTS evm_sync_log_blocks;
@@ -0,0 +1,11 @@
+-- Recent block hashes observed by each sync cursor, used to detect reorgs
+CREATE TABLE IF NOT EXISTS evm_sync_log_blocks
+(
+    address BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash BYTEA NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (address, block_number)
+);
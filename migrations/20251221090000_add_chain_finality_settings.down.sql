@@ -0,0 +1,4 @@
+ALTER TABLE evm_logs DROP COLUMN IF EXISTS unconfirmed;
+ALTER TABLE evm_chains
+    DROP COLUMN IF EXISTS finality_tag,
+    DROP COLUMN IF EXISTS confirmation_depth;
@@ -0,0 +1,8 @@
+-- Blocks the listener stays behind the head, or a `safe`/`finalized` tag to follow instead
+ALTER TABLE evm_chains
+    ADD COLUMN IF NOT EXISTS confirmation_depth INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS finality_tag TEXT NULL CHECK (finality_tag IN ('safe', 'finalized'));
+
+-- Logs ingested past the confirmed bound by contracts running in unconfirmed mode
+ALTER TABLE evm_logs
+    ADD COLUMN IF NOT EXISTS unconfirmed BOOL NOT NULL DEFAULT FALSE;
@@ -0,0 +1,24 @@
+DROP INDEX IF EXISTS evm_logs_unique_on_chain_id_transaction_hash_log_index;
+
+CREATE UNIQUE INDEX IF NOT EXISTS
+  evm_logs_unique_on_transaction_hash_log_index
+ON evm_logs (
+  transaction_hash,
+  log_index
+);
+
+ALTER TABLE evm_logs DROP COLUMN IF EXISTS chain_id;
+
+ALTER TABLE evm_sync_log_blocks
+    DROP CONSTRAINT IF EXISTS evm_sync_log_blocks_pkey,
+    DROP COLUMN IF EXISTS chain_id,
+    ADD PRIMARY KEY (address, block_number);
+
+ALTER TABLE evm_sync_logs
+    DROP CONSTRAINT IF EXISTS evm_sync_logs_pkey,
+    ADD PRIMARY KEY (address);
+
+ALTER TABLE evm_chains
+    DROP COLUMN IF EXISTS ws_url,
+    DROP COLUMN IF EXISTS rpc_urls,
+    DROP COLUMN IF EXISTS enabled;
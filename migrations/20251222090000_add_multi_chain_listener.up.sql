@@ -0,0 +1,54 @@
+-- Chains the listener runs, each with its own RPC endpoints
+ALTER TABLE evm_chains
+    ADD COLUMN IF NOT EXISTS enabled BOOL NOT NULL DEFAULT TRUE,
+    ADD COLUMN IF NOT EXISTS rpc_urls TEXT[] NOT NULL DEFAULT '{}',
+    ADD COLUMN IF NOT EXISTS ws_url TEXT NULL;
+
+-- Contracts share addresses across chains, so cursors and logs are keyed by chain too
+ALTER TABLE evm_sync_logs
+    DROP CONSTRAINT IF EXISTS evm_sync_logs_pkey,
+    ADD PRIMARY KEY (chain_id, address);
+
+ALTER TABLE evm_sync_log_blocks
+    ADD COLUMN IF NOT EXISTS chain_id BIGINT NULL;
+
+UPDATE evm_sync_log_blocks
+SET chain_id = evm_sync_logs.chain_id
+FROM evm_sync_logs
+WHERE evm_sync_logs.address = evm_sync_log_blocks.address;
+
+DELETE FROM evm_sync_log_blocks WHERE chain_id IS NULL;
+
+ALTER TABLE evm_sync_log_blocks
+    ALTER COLUMN chain_id SET NOT NULL,
+    DROP CONSTRAINT IF EXISTS evm_sync_log_blocks_pkey,
+    ADD PRIMARY KEY (chain_id, address, block_number);
+
+ALTER TABLE evm_logs
+    ADD COLUMN IF NOT EXISTS chain_id BIGINT NULL;
+
+UPDATE evm_logs
+SET chain_id = evm_sync_logs.chain_id
+FROM evm_sync_logs
+WHERE evm_sync_logs.address = evm_logs.address;
+
+-- Logs without a cursor belong to the single chain indexed before chains were keyed,
+-- those left when there were several cannot be attributed to one
+UPDATE evm_logs
+SET chain_id = (SELECT id FROM evm_chains)
+WHERE chain_id IS NULL AND (SELECT count(*) FROM evm_chains) = 1;
+
+DELETE FROM evm_logs WHERE chain_id IS NULL;
+
+ALTER TABLE evm_logs
+    ALTER COLUMN chain_id SET NOT NULL;
+
+DROP INDEX IF EXISTS evm_logs_unique_on_transaction_hash_log_index;
+
+CREATE UNIQUE INDEX
+  evm_logs_unique_on_chain_id_transaction_hash_log_index
+ON evm_logs (
+  chain_id,
+  transaction_hash,
+  log_index
+);
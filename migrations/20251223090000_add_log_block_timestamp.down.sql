@@ -0,0 +1 @@
+ALTER TABLE evm_logs DROP COLUMN IF EXISTS block_timestamp;
@@ -0,0 +1,3 @@
+-- Unix time of the block each log was emitted in
+ALTER TABLE evm_logs
+    ADD COLUMN IF NOT EXISTS block_timestamp BIGINT NULL;
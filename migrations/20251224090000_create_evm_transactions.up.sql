@@ -0,0 +1,19 @@
+-- Transactions and receipts of indexed logs, joined through evm_logs.transaction_hash
+CREATE TABLE IF NOT EXISTS evm_transactions
+(
+    chain_id BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash BYTEA NOT NULL,
+    transaction_index BIGINT NOT NULL,
+    from_address BYTEA NOT NULL,
+    to_address BYTEA NULL,
+    value NUMERIC NOT NULL,
+    gas_used NUMERIC NOT NULL,
+    effective_gas_price NUMERIC NOT NULL,
+    status BOOL NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, transaction_hash)
+);
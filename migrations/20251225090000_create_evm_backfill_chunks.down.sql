@@ -0,0 +1 @@
+DROP TABLE IF EXISTS evm_backfill_chunks;
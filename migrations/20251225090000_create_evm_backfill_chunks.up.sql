@@ -0,0 +1,13 @@
+-- Block ranges of a historical backfill, completed ones skipped when it resumes
+CREATE TABLE IF NOT EXISTS evm_backfill_chunks
+(
+    chain_id BIGINT NOT NULL,
+    address BYTEA NOT NULL,
+    from_block BIGINT NOT NULL,
+    to_block BIGINT NOT NULL,
+    completed_at TIMESTAMP WITHOUT TIME ZONE NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, address, from_block)
+);
@@ -0,0 +1,3 @@
+ALTER TABLE evm_sync_logs
+    DROP COLUMN IF EXISTS contract_name,
+    DROP COLUMN IF EXISTS parent_address;
@@ -0,0 +1,4 @@
+-- Contracts discovered through a factory event keep the handler they are processed with
+ALTER TABLE evm_sync_logs
+    ADD COLUMN IF NOT EXISTS contract_name TEXT NULL,
+    ADD COLUMN IF NOT EXISTS parent_address BYTEA NULL;
@@ -0,0 +1,13 @@
+ALTER TABLE evm_sync_logs
+    ADD COLUMN IF NOT EXISTS contract_name TEXT NULL,
+    ADD COLUMN IF NOT EXISTS parent_address BYTEA NULL;
+
+UPDATE evm_sync_logs
+SET contract_name = tracked_contracts.contract_name,
+    parent_address = tracked_contracts.parent_address
+FROM tracked_contracts
+WHERE tracked_contracts.chain_id = evm_sync_logs.chain_id
+    AND tracked_contracts.address = evm_sync_logs.address
+    AND tracked_contracts.parent_address IS NOT NULL;
+
+DROP TABLE IF EXISTS tracked_contracts;
@@ -0,0 +1,35 @@
+-- Contracts the listener indexes and the processor handles, e.g.
+--   INSERT INTO tracked_contracts (chain_id, address, contract_name, start_block)
+--   VALUES (84532, '\x4752ba5dbc23f44d87826276bf6fd6b1c372ad24', 'uniswap_v3_factory', 1000);
+-- Both processes pick up inserted, updated and disabled rows while running
+CREATE TABLE IF NOT EXISTS tracked_contracts
+(
+    chain_id BIGINT NOT NULL REFERENCES evm_chains(id),
+    address BYTEA NOT NULL,
+    contract_name TEXT NOT NULL,
+    -- Deployment block discovery runs when unset
+    start_block BIGINT NULL,
+    enabled BOOL NOT NULL DEFAULT TRUE,
+    -- Factory the contract was discovered through
+    parent_address BYTEA NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, address)
+);
+
+CREATE TRIGGER update_tracked_contracts_updated_at
+BEFORE UPDATE ON tracked_contracts
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+INSERT INTO tracked_contracts (chain_id, address, contract_name, parent_address)
+SELECT chain_id, address, contract_name, parent_address
+FROM evm_sync_logs
+WHERE contract_name IS NOT NULL
+ON CONFLICT (chain_id, address) DO NOTHING;
+
+ALTER TABLE evm_sync_logs
+    DROP COLUMN IF EXISTS contract_name,
+    DROP COLUMN IF EXISTS parent_address;
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS evm_ingested_ranges;
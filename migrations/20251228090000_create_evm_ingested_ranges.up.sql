@@ -0,0 +1,15 @@
+-- Block ranges whose logs were ingested per contract, checked for gaps against the
+-- cursor. Contracts ingested before ranges were recorded show their history as a gap
+CREATE TABLE IF NOT EXISTS evm_ingested_ranges
+(
+    id BIGSERIAL PRIMARY KEY,
+    chain_id BIGINT NOT NULL,
+    address BYTEA NOT NULL,
+    from_block BIGINT NOT NULL,
+    to_block BIGINT NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS evm_ingested_ranges_on_chain_id_address_from_block
+ON evm_ingested_ranges (chain_id, address, from_block);
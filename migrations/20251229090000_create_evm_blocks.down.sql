@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS evm_sync_log_blocks
+(
+    chain_id BIGINT NOT NULL,
+    address BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    block_hash BYTEA NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, address, block_number)
+);
+
+DROP TABLE IF EXISTS evm_blocks;
@@ -0,0 +1,20 @@
+-- Every block of the synced ranges, checked for reorgs and read for log timestamps
+-- and the chain's block time
+CREATE TABLE IF NOT EXISTS evm_blocks
+(
+    chain_id BIGINT NOT NULL REFERENCES evm_chains (id),
+    number BIGINT NOT NULL,
+    hash BYTEA NOT NULL,
+    parent_hash BYTEA NOT NULL,
+    timestamp BIGINT NOT NULL,
+    gas_used BIGINT NOT NULL,
+    base_fee_per_gas BIGINT NULL,
+    miner BYTEA NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, number)
+);
+
+-- Superseded by evm_blocks, which holds every block instead of those with logs
+DROP TABLE IF EXISTS evm_sync_log_blocks;
@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS uniswap_v3_fee_tiers;
+DROP TABLE IF EXISTS uniswap_v3_factory_owners;
+DROP TABLE IF EXISTS uniswap_v3_pools;
@@ -0,0 +1,51 @@
+-- State of the Uniswap V3 factories, written by the processor from their events.
+-- Each row records the log it came from, so a reprocessed log is skipped
+CREATE TABLE IF NOT EXISTS uniswap_v3_pools
+(
+    chain_id BIGINT NOT NULL,
+    factory_address BYTEA NOT NULL,
+    pool_address BYTEA NOT NULL,
+    token0 BYTEA NOT NULL,
+    token1 BYTEA NOT NULL,
+    fee INTEGER NOT NULL,
+    tick_spacing INTEGER NOT NULL,
+    block_number BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    log_index BIGINT NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, factory_address, pool_address)
+);
+
+-- Every ownership transfer, the latest one holding the current owner
+CREATE TABLE IF NOT EXISTS uniswap_v3_factory_owners
+(
+    chain_id BIGINT NOT NULL,
+    factory_address BYTEA NOT NULL,
+    previous_owner BYTEA NOT NULL,
+    new_owner BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    log_index BIGINT NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, factory_address, block_number, log_index)
+);
+
+-- Fee tiers can be enabled but never changed or disabled
+CREATE TABLE IF NOT EXISTS uniswap_v3_fee_tiers
+(
+    chain_id BIGINT NOT NULL,
+    factory_address BYTEA NOT NULL,
+    fee INTEGER NOT NULL,
+    tick_spacing INTEGER NOT NULL,
+    block_number BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    log_index BIGINT NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, factory_address, fee)
+);
@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS evm_logs_on_block_number_log_index;
+DROP TABLE IF EXISTS uniswap_v3_swaps;
+DROP TRIGGER IF EXISTS update_uniswap_v3_pool_states_updated_at ON uniswap_v3_pool_states;
+DROP TABLE IF EXISTS uniswap_v3_pool_states;
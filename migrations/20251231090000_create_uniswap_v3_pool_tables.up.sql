@@ -0,0 +1,57 @@
+-- Live state of each Uniswap V3 pool, moved forward by its events in log order.
+-- Events older than the last one applied, such as those of a later backfill, only
+-- add their swap rows
+CREATE TABLE IF NOT EXISTS uniswap_v3_pool_states
+(
+    chain_id BIGINT NOT NULL,
+    pool_address BYTEA NOT NULL,
+    sqrt_price_x96 NUMERIC NOT NULL,
+    tick INTEGER NOT NULL,
+    liquidity NUMERIC NOT NULL DEFAULT 0,
+    -- Tokens swapped in either direction
+    volume_token0 NUMERIC NOT NULL DEFAULT 0,
+    volume_token1 NUMERIC NOT NULL DEFAULT 0,
+    -- Swap fees charged on the tokens paid in, plus the fees of flash loans
+    fees_token0 NUMERIC NOT NULL DEFAULT 0,
+    fees_token1 NUMERIC NOT NULL DEFAULT 0,
+    -- Log the state was last moved by
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, pool_address)
+);
+
+CREATE TRIGGER update_uniswap_v3_pool_states_updated_at
+BEFORE UPDATE ON uniswap_v3_pool_states
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS uniswap_v3_swaps
+(
+    chain_id BIGINT NOT NULL,
+    pool_address BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    block_timestamp BIGINT NULL,
+    sender BYTEA NOT NULL,
+    recipient BYTEA NOT NULL,
+    -- Positive amounts were paid into the pool
+    amount0 NUMERIC NOT NULL,
+    amount1 NUMERIC NOT NULL,
+    -- Pool state after the swap
+    sqrt_price_x96 NUMERIC NOT NULL,
+    liquidity NUMERIC NOT NULL,
+    tick INTEGER NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, pool_address, block_number, log_index)
+);
+
+-- The processor reads logs in chain order
+CREATE INDEX IF NOT EXISTS evm_logs_on_block_number_log_index
+ON evm_logs (block_number, log_index);
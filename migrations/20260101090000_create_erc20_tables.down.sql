@@ -0,0 +1,7 @@
+DROP TRIGGER IF EXISTS update_erc20_allowances_updated_at ON erc20_allowances;
+DROP TABLE IF EXISTS erc20_allowances;
+DROP TABLE IF EXISTS erc20_transfers;
+DROP TRIGGER IF EXISTS update_erc20_balances_updated_at ON erc20_balances;
+DROP TABLE IF EXISTS erc20_balances;
+DROP TRIGGER IF EXISTS update_erc20_tokens_updated_at ON erc20_tokens;
+DROP TABLE IF EXISTS erc20_tokens;
@@ -0,0 +1,82 @@
+-- ERC-20 tokens written by the processor from their Transfer and Approval events.
+-- Balances and supply add up the transfers indexed, so they are exact for tokens
+-- indexed from their deployment block
+CREATE TABLE IF NOT EXISTS erc20_tokens
+(
+    chain_id BIGINT NOT NULL,
+    token_address BYTEA NOT NULL,
+    -- Minted from the zero address minus burned to it
+    total_supply NUMERIC NOT NULL DEFAULT 0,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, token_address)
+);
+
+CREATE TRIGGER update_erc20_tokens_updated_at
+BEFORE UPDATE ON erc20_tokens
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS erc20_balances
+(
+    chain_id BIGINT NOT NULL,
+    token_address BYTEA NOT NULL,
+    holder BYTEA NOT NULL,
+    balance NUMERIC NOT NULL DEFAULT 0,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, token_address, holder)
+);
+
+CREATE TRIGGER update_erc20_balances_updated_at
+BEFORE UPDATE ON erc20_balances
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+CREATE INDEX IF NOT EXISTS erc20_balances_on_chain_id_holder
+ON erc20_balances (chain_id, holder);
+
+-- Every transfer, applied to the balances once when first stored
+CREATE TABLE IF NOT EXISTS erc20_transfers
+(
+    chain_id BIGINT NOT NULL,
+    token_address BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    block_timestamp BIGINT NULL,
+    from_address BYTEA NOT NULL,
+    to_address BYTEA NOT NULL,
+    value NUMERIC NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, token_address, block_number, log_index)
+);
+
+-- Latest allowance of each owner and spender
+CREATE TABLE IF NOT EXISTS erc20_allowances
+(
+    chain_id BIGINT NOT NULL,
+    token_address BYTEA NOT NULL,
+    owner BYTEA NOT NULL,
+    spender BYTEA NOT NULL,
+    value NUMERIC NOT NULL,
+    -- Approval the allowance was last set by
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, token_address, owner, spender)
+);
+
+CREATE TRIGGER update_erc20_allowances_updated_at
+BEFORE UPDATE ON erc20_allowances
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
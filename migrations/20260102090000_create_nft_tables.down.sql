@@ -0,0 +1,7 @@
+DROP TRIGGER IF EXISTS update_erc1155_uris_updated_at ON erc1155_uris;
+DROP TABLE IF EXISTS erc1155_uris;
+DROP TRIGGER IF EXISTS update_erc1155_balances_updated_at ON erc1155_balances;
+DROP TABLE IF EXISTS erc1155_balances;
+DROP TRIGGER IF EXISTS update_erc721_owners_updated_at ON erc721_owners;
+DROP TABLE IF EXISTS erc721_owners;
+DROP TABLE IF EXISTS nft_transfers;
@@ -0,0 +1,95 @@
+-- ERC-721 and ERC-1155 collections written by the processor from their transfers
+CREATE TABLE IF NOT EXISTS nft_transfers
+(
+    chain_id BIGINT NOT NULL,
+    token_address BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    -- Position of the id in an ERC-1155 batch transfer, 0 otherwise
+    batch_index INTEGER NOT NULL DEFAULT 0,
+    transaction_hash BYTEA NOT NULL,
+    block_timestamp BIGINT NULL,
+    -- ERC-1155 only
+    operator BYTEA NULL,
+    from_address BYTEA NOT NULL,
+    to_address BYTEA NOT NULL,
+    token_id NUMERIC NOT NULL,
+    -- Always 1 for ERC-721
+    amount NUMERIC NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, token_address, block_number, log_index, batch_index)
+);
+
+CREATE INDEX IF NOT EXISTS nft_transfers_on_chain_id_token_address_token_id
+ON nft_transfers (chain_id, token_address, token_id);
+
+-- Current owner of each ERC-721 token, the zero address once burned
+CREATE TABLE IF NOT EXISTS erc721_owners
+(
+    chain_id BIGINT NOT NULL,
+    token_address BYTEA NOT NULL,
+    token_id NUMERIC NOT NULL,
+    owner BYTEA NOT NULL,
+    -- Transfer the owner was last set by
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, token_address, token_id)
+);
+
+CREATE TRIGGER update_erc721_owners_updated_at
+BEFORE UPDATE ON erc721_owners
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+CREATE INDEX IF NOT EXISTS erc721_owners_on_chain_id_owner
+ON erc721_owners (chain_id, owner);
+
+-- Balance of each ERC-1155 holder per token id, adding up the transfers indexed
+CREATE TABLE IF NOT EXISTS erc1155_balances
+(
+    chain_id BIGINT NOT NULL,
+    token_address BYTEA NOT NULL,
+    holder BYTEA NOT NULL,
+    token_id NUMERIC NOT NULL,
+    balance NUMERIC NOT NULL DEFAULT 0,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, token_address, holder, token_id)
+);
+
+CREATE TRIGGER update_erc1155_balances_updated_at
+BEFORE UPDATE ON erc1155_balances
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+CREATE INDEX IF NOT EXISTS erc1155_balances_on_chain_id_holder
+ON erc1155_balances (chain_id, holder);
+
+-- Latest metadata URI of each ERC-1155 token id
+CREATE TABLE IF NOT EXISTS erc1155_uris
+(
+    chain_id BIGINT NOT NULL,
+    token_address BYTEA NOT NULL,
+    token_id NUMERIC NOT NULL,
+    uri TEXT NOT NULL,
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, token_address, token_id)
+);
+
+CREATE TRIGGER update_erc1155_uris_updated_at
+BEFORE UPDATE ON erc1155_uris
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,8 @@
+DROP TABLE IF EXISTS uniswap_v2_liquidity_events;
+DROP TABLE IF EXISTS uniswap_v2_swaps;
+DROP TRIGGER IF EXISTS update_uniswap_v2_pair_states_updated_at ON uniswap_v2_pair_states;
+DROP TABLE IF EXISTS uniswap_v2_pair_states;
+DROP TABLE IF EXISTS uniswap_v2_pairs;
+
+ALTER TABLE tracked_contracts
+    DROP COLUMN IF EXISTS deployment;
@@ -0,0 +1,85 @@
+-- Forks of a protocol may rename its events, their ABIs then live in a subdirectory
+-- of the artifacts named after the deployment, e.g. `sushiswap/uniswap_v2_pair.json`.
+-- Contracts a factory discovers inherit its deployment
+ALTER TABLE tracked_contracts
+    ADD COLUMN IF NOT EXISTS deployment TEXT NULL;
+
+-- Pairs deployed by the Uniswap V2 factories and their forks
+CREATE TABLE IF NOT EXISTS uniswap_v2_pairs
+(
+    chain_id BIGINT NOT NULL,
+    factory_address BYTEA NOT NULL,
+    pair_address BYTEA NOT NULL,
+    token0 BYTEA NOT NULL,
+    token1 BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    log_index BIGINT NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, factory_address, pair_address)
+);
+
+-- Reserves of each pair, set by its Sync events in log order
+CREATE TABLE IF NOT EXISTS uniswap_v2_pair_states
+(
+    chain_id BIGINT NOT NULL,
+    pair_address BYTEA NOT NULL,
+    reserve0 NUMERIC NOT NULL,
+    reserve1 NUMERIC NOT NULL,
+    -- Sync the reserves were last set by
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, pair_address)
+);
+
+CREATE TRIGGER update_uniswap_v2_pair_states_updated_at
+BEFORE UPDATE ON uniswap_v2_pair_states
+FOR EACH ROW
+EXECUTE FUNCTION update_updated_at_column();
+
+CREATE TABLE IF NOT EXISTS uniswap_v2_swaps
+(
+    chain_id BIGINT NOT NULL,
+    pair_address BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    block_timestamp BIGINT NULL,
+    sender BYTEA NOT NULL,
+    recipient BYTEA NOT NULL,
+    amount0_in NUMERIC NOT NULL,
+    amount1_in NUMERIC NOT NULL,
+    amount0_out NUMERIC NOT NULL,
+    amount1_out NUMERIC NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, pair_address, block_number, log_index)
+);
+
+-- Liquidity added by Mint and removed by Burn events
+CREATE TABLE IF NOT EXISTS uniswap_v2_liquidity_events
+(
+    chain_id BIGINT NOT NULL,
+    pair_address BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    block_timestamp BIGINT NULL,
+    kind TEXT NOT NULL CHECK (kind IN ('mint', 'burn')),
+    sender BYTEA NOT NULL,
+    -- Burns only
+    recipient BYTEA NULL,
+    amount0 NUMERIC NOT NULL,
+    amount1 NUMERIC NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, pair_address, block_number, log_index)
+);
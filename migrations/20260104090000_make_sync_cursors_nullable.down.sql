@@ -0,0 +1,5 @@
+UPDATE evm_sync_logs SET last_synced_block_number = 0 WHERE last_synced_block_number IS NULL;
+
+ALTER TABLE evm_sync_logs
+    ALTER COLUMN last_synced_block_number SET DEFAULT 0,
+    ALTER COLUMN last_synced_block_number SET NOT NULL;
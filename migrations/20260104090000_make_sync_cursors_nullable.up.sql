@@ -0,0 +1,7 @@
+-- A NULL cursor has indexed nothing yet, so indexing can start at block 0
+ALTER TABLE evm_sync_logs
+    ALTER COLUMN last_synced_block_number DROP DEFAULT,
+    ALTER COLUMN last_synced_block_number DROP NOT NULL;
+
+-- Cursors created for block 0 or 1 were stored at 0 and skipped block 0
+UPDATE evm_sync_logs SET last_synced_block_number = NULL WHERE last_synced_block_number = 0;
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS uniswap_v3_flashes;
+DROP TABLE IF EXISTS uniswap_v3_liquidity_changes;
@@ -0,0 +1,45 @@
+-- Every event now adds to the volumes, fees and liquidity of uniswap_v3_pool_states
+-- once, whatever order it arrives in. Its block_number and log_index are those of the
+-- event that last set the price, which only moves forward
+
+-- Mints and burns of Uniswap V3 pools, keyed by log so that a replayed event changes
+-- the liquidity of its pool once. `amount` is negative for burns
+CREATE TABLE IF NOT EXISTS uniswap_v3_liquidity_changes
+(
+    chain_id BIGINT NOT NULL,
+    pool_address BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    owner BYTEA NOT NULL,
+    tick_lower INTEGER NOT NULL,
+    tick_upper INTEGER NOT NULL,
+    amount NUMERIC NOT NULL,
+    amount0 NUMERIC NOT NULL,
+    amount1 NUMERIC NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, pool_address, block_number, log_index)
+);
+
+-- Flash loans of Uniswap V3 pools, keyed by log so that a replayed event adds its fees once
+CREATE TABLE IF NOT EXISTS uniswap_v3_flashes
+(
+    chain_id BIGINT NOT NULL,
+    pool_address BYTEA NOT NULL,
+    block_number BIGINT NOT NULL,
+    log_index BIGINT NOT NULL,
+    transaction_hash BYTEA NOT NULL,
+    sender BYTEA NOT NULL,
+    recipient BYTEA NOT NULL,
+    amount0 NUMERIC NOT NULL,
+    amount1 NUMERIC NOT NULL,
+    -- Fees paid back on top of the amounts
+    paid0 NUMERIC NOT NULL,
+    paid1 NUMERIC NOT NULL,
+
+    created_at TIMESTAMP WITHOUT TIME ZONE NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (chain_id, pool_address, block_number, log_index)
+);
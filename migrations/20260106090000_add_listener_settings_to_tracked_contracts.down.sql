@@ -0,0 +1,3 @@
+ALTER TABLE tracked_contracts
+    DROP COLUMN IF EXISTS unconfirmed,
+    DROP COLUMN IF EXISTS events;
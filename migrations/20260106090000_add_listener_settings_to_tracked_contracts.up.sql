@@ -0,0 +1,8 @@
+-- Listener settings of each contract, picked up with the rest of its row:
+-- `unconfirmed` indexes it up to the chain head ahead of confirmations, and contracts
+-- discovered through it inherit the setting, while `events` limits its logs to the
+-- named events of its handler's ABI, every event when NULL, e.g.
+--   UPDATE tracked_contracts SET events = '{Swap,Mint,Burn}' WHERE address = '\x...';
+ALTER TABLE tracked_contracts
+    ADD COLUMN IF NOT EXISTS unconfirmed BOOL NOT NULL DEFAULT FALSE,
+    ADD COLUMN IF NOT EXISTS events TEXT[] NULL;
@@ -0,0 +1,2 @@
+ALTER TABLE evm_sync_logs
+    DROP COLUMN IF EXISTS start_block;
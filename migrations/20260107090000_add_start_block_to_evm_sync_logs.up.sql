@@ -0,0 +1,5 @@
+-- First block each cursor indexes, the configured start block of its contract or the
+-- block it was found deployed at, which coverage reports are measured from. Cursors
+-- created before it keep NULL.
+ALTER TABLE evm_sync_logs
+    ADD COLUMN IF NOT EXISTS start_block BIGINT NULL;
@@ -0,0 +1,145 @@
+use alloy::hex;
+use blockchain_indexer::{
+    config::load_config,
+    infrastructure::{
+        abi::abi_loader::AbiLoader,
+        blockchain::{
+            chain_provider::{ChainEndpoints, new_chain_provider},
+            request_budget::RequestBudgets,
+        },
+        database::pgsql::new_database_connection,
+    },
+    services::{
+        dtos::{backfill::BackfillJob, index_logs::TrackedContract},
+        repository::{
+            EVMChainRepository, TrackedContractsRepository,
+            evm_backfill_chunks::evm_backfill_chunk_repository::EVMBackfillChunksRepositoryImpl,
+            evm_blocks::evm_block_repository::EVMBlocksRepositoryImpl,
+            evm_chains::evm_chain_repository::EVMChainRepositoryImpl,
+            evm_logs::evm_log_repository::EVMLogsRepositoryImpl,
+            evm_sync_logs::evm_sync_logs::EVMSyncLogsRepositoryImpl,
+            evm_transactions::evm_transaction_repository::EVMTransactionsRepositoryImpl,
+            ingestion::ingestion_repository::IngestionRepositoryImpl,
+            tracked_contracts::tracked_contract_repository::TrackedContractsRepositoryImpl,
+        },
+        usecase::{
+            BackfillUC, backfill::backfill_uc::BackfillUCImpl,
+            index_logs::index_log_uc::IndexLogUCImpl,
+        },
+    },
+};
+use clap::Parser;
+
+/// Indexes the history of contracts in concurrent chunks while the listener keeps
+/// following the head. Interrupted backfills resume from their pending chunks.
+#[derive(Debug, Parser)]
+struct Args {
+    #[arg(long)]
+    chain_id: u64,
+
+    /// Contract to backfill, repeatable, indexed with the settings it is tracked with
+    /// in `tracked_contracts`
+    #[arg(long = "address", required = true)]
+    addresses: Vec<String>,
+
+    /// First block to backfill. Without `--to-block` it only replaces deployment block
+    /// discovery for contracts without a cursor, and the range runs up to the
+    /// confirmed head the cursor is moved to
+    #[arg(long)]
+    from_block: Option<u64>,
+
+    /// Last block to backfill, leaving the cursor untouched
+    #[arg(long, requires = "from_block")]
+    to_block: Option<u64>,
+
+    /// Blocks per chunk
+    #[arg(long, default_value_t = 10_000)]
+    chunk_size: u64,
+
+    /// Chunks indexed concurrently
+    #[arg(long, default_value_t = 4)]
+    workers: usize,
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let config = load_config()?;
+
+    let db_pool = new_database_connection(
+        &config.database.database_url,
+        config.database.max_connections,
+    )
+    .await?;
+
+    let evm_chain_repo = EVMChainRepositoryImpl::new(db_pool.clone());
+    let evm_chain = evm_chain_repo.fetch_by_id(args.chain_id).await?;
+    let endpoints = ChainEndpoints::resolve(&evm_chain, &config.listener)?;
+    let budgets = RequestBudgets::from_config(&config.listener)?;
+    let provider =
+        new_chain_provider(args.chain_id, &endpoints, &config.listener, &budgets).await?;
+
+    let evm_transaction_repo = config
+        .listener
+        .enrich_transactions
+        .then(|| EVMTransactionsRepositoryImpl::new(db_pool.clone()));
+    let index_log_uc = IndexLogUCImpl::new(
+        provider,
+        EVMLogsRepositoryImpl::new(db_pool.clone()),
+        EVMSyncLogsRepositoryImpl::new(db_pool.clone()),
+        evm_transaction_repo,
+        IngestionRepositoryImpl::new(db_pool.clone(), config.listener.insert_batch_size),
+        EVMBlocksRepositoryImpl::new(db_pool.clone()),
+        10,
+        config.listener.reorg_depth,
+    );
+    let backfill_uc = BackfillUCImpl::new(
+        index_log_uc,
+        EVMBackfillChunksRepositoryImpl::new(db_pool.clone()),
+    );
+
+    let tracked_contracts = TrackedContractsRepositoryImpl::new(db_pool)
+        .find_enabled(Some(args.chain_id))
+        .await?;
+    let abi_loader = AbiLoader::new(config.processor.artifacts_base_path.clone());
+
+    let mut failed = false;
+    for address in &args.addresses {
+        let address = address.trim().trim_start_matches("0x").to_lowercase();
+        let tracked = tracked_contracts
+            .iter()
+            .find(|contract| hex::encode(contract.address) == address)
+            .ok_or_else(|| {
+                format!(
+                    "Contract {address} is not tracked on chain {}",
+                    args.chain_id
+                )
+            })?;
+
+        // Indexed with the events the listener filters it by, history being confirmed
+        let contract = TrackedContract::from_tracked(tracked, &abi_loader)?;
+        let job = BackfillJob {
+            contract: TrackedContract {
+                unconfirmed: false,
+                start_block: args.from_block.or(contract.start_block),
+                ..contract
+            },
+            range: args.from_block.zip(args.to_block),
+            chunk_size: args.chunk_size,
+            workers: args.workers,
+        };
+
+        let report = backfill_uc.execute(&evm_chain, &job).await?;
+        println!(
+            "Backfill of {address} finished: {} chunks completed, {} failed, {} logs",
+            report.completed_chunks, report.failed_chunks, report.logs
+        );
+        failed |= report.failed_chunks > 0;
+    }
+
+    if failed {
+        return Err("Some chunks failed, run the backfill again to retry them".into());
+    }
+
+    Ok(())
+}
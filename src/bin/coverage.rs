@@ -0,0 +1,156 @@
+use alloy::hex;
+use blockchain_indexer::{
+    config::load_config,
+    infrastructure::{
+        blockchain::{
+            chain_provider::{ChainEndpoints, new_chain_provider},
+            request_budget::RequestBudgets,
+        },
+        database::pgsql::new_database_connection,
+    },
+    services::{
+        dtos::{coverage::CoverageReport, index_logs::TrackedContract},
+        repository::{
+            EVMChainRepository, TrackedContractsRepository,
+            evm_blocks::evm_block_repository::EVMBlocksRepositoryImpl,
+            evm_chains::evm_chain_repository::EVMChainRepositoryImpl,
+            evm_logs::evm_log_repository::EVMLogsRepositoryImpl,
+            evm_sync_logs::evm_sync_logs::EVMSyncLogsRepositoryImpl,
+            evm_transactions::evm_transaction_repository::EVMTransactionsRepositoryImpl,
+            ingestion::ingestion_repository::IngestionRepositoryImpl,
+            tracked_contracts::tracked_contract_repository::TrackedContractsRepositoryImpl,
+        },
+        usecase::{
+            CoverageUC, coverage::coverage_uc::CoverageUCImpl,
+            index_logs::index_log_uc::IndexLogUCImpl,
+        },
+    },
+};
+use clap::Parser;
+
+/// Reports which blocks between each contract's start block and its cursor were
+/// ingested, and optionally re-fetches the missing ones.
+#[derive(Debug, Parser)]
+struct Args {
+    #[arg(long)]
+    chain_id: u64,
+
+    /// Contract to check, repeatable. Every enabled tracked contract of the chain
+    /// when omitted
+    #[arg(long = "address")]
+    addresses: Vec<String>,
+
+    /// Re-fetch the gaps and merge overlapping ranges
+    #[arg(long)]
+    repair: bool,
+}
+
+fn format_ranges(ranges: &[(u64, u64)]) -> String {
+    if ranges.is_empty() {
+        return "none".to_string();
+    }
+
+    ranges
+        .iter()
+        .map(|(from_block, to_block)| format!("{from_block}-{to_block}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn print_report(address: &str, report: &CoverageReport) {
+    println!(
+        "{address}: blocks {}-{}, {}/{} covered, gaps: {}, overlaps: {}",
+        report.start_block,
+        report.cursor,
+        report.covered_blocks,
+        report.expected_blocks(),
+        format_ranges(&report.gaps),
+        format_ranges(&report.overlaps),
+    );
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+    let config = load_config()?;
+
+    let db_pool = new_database_connection(
+        &config.database.database_url,
+        config.database.max_connections,
+    )
+    .await?;
+
+    let evm_chain_repo = EVMChainRepositoryImpl::new(db_pool.clone());
+    let evm_chain = evm_chain_repo.fetch_by_id(args.chain_id).await?;
+
+    let tracked_contracts = TrackedContractsRepositoryImpl::new(db_pool.clone())
+        .find_enabled(Some(args.chain_id))
+        .await?;
+    let contracts: Vec<TrackedContract> = match args.addresses.is_empty() {
+        true => tracked_contracts
+            .iter()
+            .map(|contract| TrackedContract {
+                address: hex::encode(contract.address),
+                unconfirmed: false,
+                start_block: contract.start_block.map(|block| block as u64),
+                event_signatures: Vec::new(),
+            })
+            .collect(),
+        false => args
+            .addresses
+            .iter()
+            .map(|address| {
+                let address = address.trim().trim_start_matches("0x").to_lowercase();
+                let start_block = tracked_contracts
+                    .iter()
+                    .find(|contract| hex::encode(contract.address) == address)
+                    .and_then(|contract| contract.start_block);
+
+                TrackedContract {
+                    address,
+                    unconfirmed: false,
+                    start_block: start_block.map(|block| block as u64),
+                    event_signatures: Vec::new(),
+                }
+            })
+            .collect(),
+    };
+
+    let endpoints = ChainEndpoints::resolve(&evm_chain, &config.listener)?;
+    let budgets = RequestBudgets::from_config(&config.listener)?;
+    let provider =
+        new_chain_provider(args.chain_id, &endpoints, &config.listener, &budgets).await?;
+
+    let evm_transaction_repo = config
+        .listener
+        .enrich_transactions
+        .then(|| EVMTransactionsRepositoryImpl::new(db_pool.clone()));
+    let index_log_uc = IndexLogUCImpl::new(
+        provider,
+        EVMLogsRepositoryImpl::new(db_pool.clone()),
+        EVMSyncLogsRepositoryImpl::new(db_pool.clone()),
+        evm_transaction_repo,
+        IngestionRepositoryImpl::new(db_pool.clone(), config.listener.insert_batch_size),
+        EVMBlocksRepositoryImpl::new(db_pool.clone()),
+        10,
+        config.listener.reorg_depth,
+    );
+    let coverage_uc = CoverageUCImpl::new(index_log_uc, EVMSyncLogsRepositoryImpl::new(db_pool));
+
+    let mut incomplete = false;
+    for contract in &contracts {
+        let report = match args.repair {
+            true => coverage_uc.repair(&evm_chain, contract).await?,
+            false => coverage_uc.report(&evm_chain, contract).await?,
+        };
+
+        print_report(&contract.address, &report);
+        incomplete |= !report.gaps.is_empty();
+    }
+
+    if incomplete {
+        return Err("Some contracts have coverage gaps".into());
+    }
+
+    Ok(())
+}
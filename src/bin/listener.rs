@@ -1,20 +1,51 @@
-use std::{sync::Arc, time::Duration};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
 use blockchain_indexer::{
-    config::load_config,
-    infrastructure::{blockchain::provider::EVMProvider, database::pgsql::new_database_connection},
+    config::{ListenerConfig, LogFilterMode, load_config},
+    infrastructure::{
+        abi::abi_loader::AbiLoader,
+        blockchain::{
+            chain_provider::{ChainEndpoints, new_chain_provider},
+            request_budget::RequestBudgets,
+        },
+        database::pgsql::new_database_connection,
+    },
     services::{
-        delivery::event::evm_log_listener::EVMLogListener,
+        delivery::event::{
+            evm_chain_log_listener::EVMChainLogListener, evm_log_listener::EVMLogListener,
+            evm_log_subscriber::EVMLogSubscriber,
+        },
+        dtos::index_logs::TrackedContract,
+        entities::evm_chains::EvmChains,
         repository::{
-            EVMChainRepository, evm_chains::evm_chain_repository::EVMChainRepositoryImpl,
+            EVMBlocksRepository, EVMChainRepository, TrackedContractsRepository,
+            evm_blocks::evm_block_repository::EVMBlocksRepositoryImpl,
+            evm_chains::evm_chain_repository::EVMChainRepositoryImpl,
             evm_logs::evm_log_repository::EVMLogsRepositoryImpl,
             evm_sync_logs::evm_sync_logs::EVMSyncLogsRepositoryImpl,
+            evm_transactions::evm_transaction_repository::EVMTransactionsRepositoryImpl,
+            ingestion::ingestion_repository::IngestionRepositoryImpl,
+            tracked_contracts::tracked_contract_repository::TrackedContractsRepositoryImpl,
         },
-        usecase::index_logs::index_log_uc::IndexLogUCImpl,
+        usecase::{IndexLogUC, errors::AppError, index_logs::index_log_uc::IndexLogUCImpl},
     },
 };
-use tokio::task::JoinSet;
-use tower::{Service, ServiceBuilder, ServiceExt};
+use sqlx::PgPool;
+use tokio::{
+    sync::watch,
+    task::{AbortHandle, JoinSet},
+    time::{MissedTickBehavior, interval, sleep},
+};
+use tower::{Service, ServiceExt};
+
+/// Pause before a chain whose listener failed is started again.
+const CHAIN_RESTART_DELAY: Duration = Duration::from_secs(10);
+
+/// Newest stored blocks the block time of a chain is measured over.
+const BLOCK_TIME_SAMPLE: i64 = 1_000;
+
+/// Shortest pause between polls, for chains producing blocks faster than a second.
+const MIN_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -26,52 +57,307 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     )
     .await?;
 
-    let provider = EVMProvider::new(&config.listener.rpc_url).await?;
+    let abi_loader = Arc::new(AbiLoader::new(config.processor.artifacts_base_path.clone()));
+    // Every chain task shares the budget of the endpoints it reaches
+    let budgets = Arc::new(RequestBudgets::from_config(&config.listener)?);
+    let listener_config = Arc::new(config.listener);
+
     let evm_chain_repo = EVMChainRepositoryImpl::new(db_pool.clone());
+    let chains = evm_chain_repo.find_enabled().await?;
+    if chains.is_empty() {
+        return Err("No enabled chains in evm_chains".into());
+    }
+
+    // Each chain runs in its own task, so a failing chain never stops the others
+    let mut chain_tasks = JoinSet::new();
+    let mut chain_ids = HashMap::new();
+    for chain in chains {
+        let task = chain_tasks.spawn(supervise_chain(
+            chain.id as u64,
+            Duration::ZERO,
+            Arc::clone(&listener_config),
+            Arc::clone(&abi_loader),
+            Arc::clone(&budgets),
+            db_pool.clone(),
+        ));
+        chain_ids.insert(task.id(), chain.id as u64);
+    }
+
+    while let Some(result) = chain_tasks.join_next_with_id().await {
+        let task_id = match result {
+            Ok((task_id, ())) => task_id,
+            Err(err) => {
+                eprintln!("Chain listener panicked: {err}");
+                err.id()
+            }
+        };
+
+        let Some(chain_id) = chain_ids.remove(&task_id) else {
+            continue;
+        };
+
+        let task = chain_tasks.spawn(supervise_chain(
+            chain_id,
+            CHAIN_RESTART_DELAY,
+            Arc::clone(&listener_config),
+            Arc::clone(&abi_loader),
+            Arc::clone(&budgets),
+            db_pool.clone(),
+        ));
+        chain_ids.insert(task.id(), chain_id);
+    }
+
+    Ok(())
+}
+
+/// Runs the listeners of a chain, starting them again whenever they fail or the
+/// chain has no contract tracked anymore.
+async fn supervise_chain(
+    chain_id: u64,
+    delay: Duration,
+    config: Arc<ListenerConfig>,
+    abi_loader: Arc<AbiLoader>,
+    budgets: Arc<RequestBudgets>,
+    db_pool: PgPool,
+) {
+    sleep(delay).await;
+
+    loop {
+        if let Err(err) = run_chain(chain_id, &config, &abi_loader, &budgets, db_pool.clone()).await
+        {
+            eprintln!("Listener for chain {chain_id} failed: {err}");
+            sleep(CHAIN_RESTART_DELAY).await;
+        }
+    }
+}
+
+/// Listens on every contract tracked on the chain, with its own provider and use
+/// case. Contracts tracked, disabled or changed since are handed to the running
+/// listeners, until no contract is tracked on the chain anymore.
+async fn run_chain(
+    chain_id: u64,
+    config: &ListenerConfig,
+    abi_loader: &AbiLoader,
+    budgets: &RequestBudgets,
+    db_pool: PgPool,
+) -> Result<(), AppError> {
+    let evm_chain_repo = EVMChainRepositoryImpl::new(db_pool.clone());
+    let tracked_contract_repo = TrackedContractsRepositoryImpl::new(db_pool.clone());
+    let evm_chain = evm_chain_repo.fetch_by_id(chain_id).await?;
+    let endpoints = ChainEndpoints::resolve(&evm_chain, config)?;
+
+    let tracked_contracts = tracked_contract_repo.find_enabled(Some(chain_id)).await?;
+    let reload_interval = Duration::from_secs(config.contract_reload_secs);
+    if tracked_contracts.is_empty() {
+        println!("No contracts tracked on chain {chain_id}");
+        wait_for_contracts(chain_id, reload_interval, &tracked_contract_repo).await;
+        return Ok(());
+    }
+
+    let contracts = tracked_contracts
+        .iter()
+        .map(|contract| TrackedContract::from_tracked(contract, abi_loader))
+        .collect::<Result<Vec<TrackedContract>, AppError>>()?;
+
+    let provider = new_chain_provider(chain_id, &endpoints, config, budgets).await?;
     let evm_log_repo = EVMLogsRepositoryImpl::new(db_pool.clone());
-    let evm_sync_log_repo = EVMSyncLogsRepositoryImpl::new(db_pool.clone());
+    let evm_block_repo = EVMBlocksRepositoryImpl::new(db_pool.clone());
+    let evm_transaction_repo = config
+        .enrich_transactions
+        .then(|| EVMTransactionsRepositoryImpl::new(db_pool.clone()));
     let index_log_uc = Arc::new(IndexLogUCImpl::new(
         provider,
         evm_log_repo,
-        evm_sync_log_repo,
+        EVMSyncLogsRepositoryImpl::new(db_pool.clone()),
+        evm_transaction_repo,
+        IngestionRepositoryImpl::new(db_pool.clone(), config.insert_batch_size),
+        evm_block_repo.clone(),
         10,
+        config.reorg_depth,
     ));
 
-    let evm_chain = evm_chain_repo.fetch_by_id(config.listener.chain_id).await?;
-    let addresses: Vec<String> = config
-        .listener
-        .contract_addresses
-        .split(",")
-        .map(|s| s.to_string())
-        .collect();
+    println!(
+        "Listening on {} contracts of chain {chain_id}",
+        contracts.len()
+    );
 
+    // Measured from the stored blocks once there are some, the configured block time
+    // only covers a chain indexed for the first time
+    let block_time = evm_block_repo
+        .average_block_time(chain_id, BLOCK_TIME_SAMPLE)
+        .await?
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .unwrap_or(Duration::from_secs(evm_chain.block_time as u64));
+    let (contract_updates, contracts) = watch::channel(contracts);
     let mut futures = JoinSet::new();
-    for address in addresses {
-        let mut service = ServiceBuilder::new()
-            .rate_limit(1, Duration::from_secs(evm_chain.block_time as u64))
-            .service(EVMLogListener {
-                usecase: Arc::clone(&index_log_uc),
-                chain_id: config.listener.chain_id,
-                address: address.to_string(),
-            });
-
-        let future = async move {
-            loop {
-                if service.ready().await.is_ok() {
-                    match service.call(()).await {
-                        Ok(()) => {}
-                        Err(err) => {
-                            eprintln!("Failed to indexed: {:?}", err);
-                        }
-                    }
-                }
+    match config.log_filter_mode {
+        // A subscription already covers every contract of the chain
+        _ if endpoints.ws_url.is_some() => {
+            let subscriber = EVMLogSubscriber::new(
+                Arc::clone(&index_log_uc),
+                evm_chain.clone(),
+                contracts,
+                Duration::from_secs(config.subscription_retry_secs),
+            );
+
+            futures.spawn(async move { subscriber.run().await });
+        }
+        LogFilterMode::PerContract => {
+            futures.spawn(run_contract_listeners(
+                Arc::clone(&index_log_uc),
+                evm_chain.clone(),
+                contracts,
+                block_time,
+            ));
+        }
+        LogFilterMode::Combined => {
+            let service =
+                EVMChainLogListener::new(Arc::clone(&index_log_uc), evm_chain.clone(), contracts);
+
+            futures.spawn(run_listener(service, block_time));
+        }
+    }
+
+    futures.spawn(reload_contracts(
+        chain_id,
+        abi_loader.clone(),
+        reload_interval,
+        tracked_contract_repo,
+        contract_updates,
+    ));
+
+    // Listeners run until stopped, dropping the set aborts them once no contract is
+    // tracked anymore
+    futures.join_next().await;
+
+    Ok(())
+}
+
+/// Runs a listener per contract. Listeners start for contracts tracked since and stop
+/// for contracts disabled or changed, while those of the other contracts keep running.
+async fn run_contract_listeners<U>(
+    usecase: Arc<U>,
+    chain: EvmChains,
+    mut contracts: watch::Receiver<Vec<TrackedContract>>,
+    block_time: Duration,
+) where
+    U: IndexLogUC + Send + Sync + 'static,
+{
+    let mut listeners: HashMap<String, (TrackedContract, AbortHandle)> = HashMap::new();
+    let mut tasks = JoinSet::new();
+
+    loop {
+        let current = contracts.borrow_and_update().clone();
+        listeners.retain(|_, (contract, task)| {
+            let kept = current.contains(contract);
+            if !kept {
+                task.abort();
+            }
+            kept
+        });
+        while tasks.try_join_next().is_some() {}
+
+        for contract in current {
+            if listeners.contains_key(&contract.address) {
+                continue;
+            }
+
+            let service =
+                EVMLogListener::new(Arc::clone(&usecase), chain.clone(), contract.clone());
+            let task = tasks.spawn(run_listener(service, block_time));
+            listeners.insert(contract.address.clone(), (contract, task));
+        }
+
+        if contracts.changed().await.is_err() {
+            return;
+        }
+    }
+}
+
+/// Returns once contracts are tracked on the chain.
+async fn wait_for_contracts(
+    chain_id: u64,
+    interval: Duration,
+    tracked_contract_repo: &TrackedContractsRepositoryImpl,
+) {
+    loop {
+        sleep(interval).await;
+
+        match tracked_contract_repo.find_enabled(Some(chain_id)).await {
+            Ok(contracts) if !contracts.is_empty() => return,
+            Ok(_) => {}
+            Err(err) => eprintln!("Failed to reload contracts of chain {chain_id}: {err}"),
+        }
+    }
+}
+
+/// Reloads the contracts tracked on the chain every `interval`, handing those tracked,
+/// disabled or changed since to the listeners. Returns once no contract is tracked on
+/// the chain anymore.
+async fn reload_contracts(
+    chain_id: u64,
+    abi_loader: AbiLoader,
+    interval: Duration,
+    tracked_contract_repo: TrackedContractsRepositoryImpl,
+    contracts: watch::Sender<Vec<TrackedContract>>,
+) {
+    loop {
+        sleep(interval).await;
+
+        let tracked_contracts = match tracked_contract_repo.find_enabled(Some(chain_id)).await {
+            Ok(tracked_contracts) => tracked_contracts,
+            Err(err) => {
+                eprintln!("Failed to reload contracts of chain {chain_id}: {err}");
+                continue;
+            }
+        };
+        if tracked_contracts.is_empty() {
+            println!("No contracts tracked on chain {chain_id} anymore, stopping listeners");
+            return;
+        }
+
+        let reloaded = match tracked_contracts
+            .iter()
+            .map(|contract| TrackedContract::from_tracked(contract, &abi_loader))
+            .collect::<Result<Vec<TrackedContract>, AppError>>()
+        {
+            Ok(reloaded) => reloaded,
+            Err(err) => {
+                eprintln!("Failed to reload contracts of chain {chain_id}: {err}");
+                continue;
             }
         };
 
-        futures.spawn(future);
+        contracts.send_if_modified(|current| {
+            if *current == reloaded {
+                return false;
+            }
+
+            println!("Tracked contracts of chain {chain_id} changed, updating listeners");
+            *current = reloaded;
+            true
+        });
     }
+}
 
-    futures.join_all().await;
+/// Calls the listener service forever, once per block. The requests it sends are
+/// paced by the budget of the RPC endpoints.
+async fn run_listener<S>(mut service: S, block_time: Duration)
+where
+    S: Service<(), Response = (), Error = AppError>,
+{
+    let mut ticks = interval(block_time.max(MIN_POLL_INTERVAL));
+    ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
-    Ok(())
+    loop {
+        ticks.tick().await;
+        if service.ready().await.is_ok() {
+            match service.call(()).await {
+                Ok(()) => {}
+                Err(err) => {
+                    eprintln!("Failed to indexed: {:?}", err);
+                }
+            }
+        }
+    }
 }
@@ -1,4 +1,4 @@
-use std::{collections::HashMap, time::Duration};
+use std::time::Duration;
 
 use blockchain_indexer::{
     config::load_config,
@@ -7,7 +7,14 @@ use blockchain_indexer::{
         database::pgsql::new_database_connection,
     },
     services::{
-        repository::{EVMLogsRepository, evm_logs::evm_log_repository::EVMLogsRepositoryImpl},
+        repository::{
+            EVMLogsRepository, erc20::erc20_repository::Erc20RepositoryImpl,
+            evm_logs::evm_log_repository::EVMLogsRepositoryImpl,
+            nft::nft_repository::NftRepositoryImpl,
+            tracked_contracts::tracked_contract_repository::TrackedContractsRepositoryImpl,
+            uniswap_v2::uniswap_v2_repository::UniswapV2RepositoryImpl,
+            uniswap_v3::uniswap_v3_repository::UniswapV3RepositoryImpl,
+        },
         usecase::index_engine::index_engine_uc::IndexEngineUCImpl,
     },
 };
@@ -19,17 +26,6 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let sleep_duration = Duration::from_secs(config.processor.poll_interval.parse::<u64>()?);
     let batch_size = config.processor.batch_size.parse::<u64>()?;
     let abi_loader = AbiLoader::new(config.processor.artifacts_base_path);
-    let mut contract_name_by_address: HashMap<String, String> = HashMap::new();
-    let contract = config.processor.contracts;
-    contract.split(",").for_each(|contract| {
-        let contract_details: Vec<&str> = contract
-            .split(":")
-            .map(|address_str| address_str.trim())
-            .collect();
-
-        let (contract_name, contract_address) = (contract_details[0], contract_details[1]);
-        contract_name_by_address.insert(contract_address.to_lowercase(), contract_name.to_string());
-    });
 
     let db_pool = new_database_connection(
         &config.database.database_url,
@@ -38,9 +34,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     .await?;
 
     let evm_logs_repo = EVMLogsRepositoryImpl::new(db_pool.clone());
-    let contract_registry = ContractRegistry::new(contract_name_by_address, abi_loader);
-    let index_engine_uc =
-        IndexEngineUCImpl::new(evm_logs_repo.clone(), contract_registry, batch_size);
+    let tracked_contract_repo = TrackedContractsRepositoryImpl::new(db_pool.clone());
+    let contract_registry = ContractRegistry::new(abi_loader);
+    let index_engine_uc = IndexEngineUCImpl::new(
+        evm_logs_repo.clone(),
+        tracked_contract_repo,
+        UniswapV3RepositoryImpl::new(db_pool.clone()),
+        UniswapV2RepositoryImpl::new(db_pool.clone()),
+        Erc20RepositoryImpl::new(db_pool.clone()),
+        NftRepositoryImpl::new(db_pool.clone()),
+        contract_registry,
+        batch_size,
+    );
 
     'l: loop {
         let unprocessed_count = match evm_logs_repo.count().await {
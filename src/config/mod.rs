@@ -55,15 +55,101 @@ pub struct LogConfig {
 }
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ListenerConfig {
-    pub chain_id: u64,
-    pub contract_addresses: String,
-    pub rpc_url: String,
+    /// Chain the `rpc_url` and `ws_url` belong to. Every enabled chain in
+    /// `evm_chains` is listened on with its enabled `tracked_contracts`
+    #[serde(default)]
+    pub chain_id: Option<u64>,
+
+    /// RPC endpoint of `chain_id` when the chain has no `rpc_urls` of its own
+    #[serde(default)]
+    pub rpc_url: Option<String>,
+
+    /// WebSocket endpoint of `chain_id` streaming new logs through `eth_subscribe`
+    /// when the chain has no `ws_url` of its own, polling when neither is set
+    #[serde(default)]
+    pub ws_url: Option<String>,
+
+    /// Seconds spent polling after a subscription drops before subscribing again
+    #[serde(default = "default_subscription_retry_secs")]
+    pub subscription_retry_secs: u64,
+
+    /// Whether each contract polls with its own log filter or all of them share one
+    #[serde(default)]
+    pub log_filter_mode: LogFilterMode,
+
+    /// Upper bound for the block span of a single `eth_getLogs` call
+    #[serde(default = "default_max_log_window")]
+    pub max_log_window: u64,
+
+    /// Blocks an RPC endpoint may lag behind the highest head seen on its chain
+    /// before requests stop being routed to it
+    #[serde(default = "default_max_head_lag")]
+    pub max_head_lag: u64,
+
+    /// Retries of a transient or rate limited RPC request
+    #[serde(default = "default_rpc_max_retries")]
+    pub rpc_max_retries: u32,
+
+    /// Backoff before the first RPC retry, doubling up to `rpc_retry_max_delay_ms`
+    #[serde(default = "default_rpc_retry_base_delay_ms")]
+    pub rpc_retry_base_delay_ms: u64,
+
+    #[serde(default = "default_rpc_retry_max_delay_ms")]
+    pub rpc_retry_max_delay_ms: u64,
+
+    /// Consecutive RPC failures that stop requests to an endpoint for
+    /// `rpc_circuit_open_secs`
+    #[serde(default = "default_rpc_circuit_failure_threshold")]
+    pub rpc_circuit_failure_threshold: u32,
+
+    #[serde(default = "default_rpc_circuit_open_secs")]
+    pub rpc_circuit_open_secs: u64,
+
+    /// Request units each RPC endpoint may be sent per second, unlimited when 0. The
+    /// budget is kept in memory, each process spending its share of it
+    #[serde(default)]
+    pub rpc_budget_per_sec: u32,
+
+    /// Processes sending requests to the same endpoints at once, such as a listener
+    /// and a backfill, each being given an equal share of `rpc_budget_per_sec`
+    #[serde(default = "default_rpc_budget_processes")]
+    pub rpc_budget_processes: u32,
+
+    /// Comma separated `method:units` pairs, such as `eth_getLogs:75`, for RPC methods
+    /// costing more than one unit of the budget
+    #[serde(default)]
+    pub rpc_method_weights: String,
+
+    /// Number of recent blocks checked for reorgs behind each cursor
+    #[serde(default = "default_reorg_depth")]
+    pub reorg_depth: u64,
+
+    /// Whether the transaction and receipt of every indexed log are stored in
+    /// `evm_transactions`
+    #[serde(default)]
+    pub enrich_transactions: bool,
+
+    /// Logs written per `INSERT` statement, capped by the Postgres bind parameter limit
+    #[serde(default = "default_insert_batch_size")]
+    pub insert_batch_size: usize,
+
+    /// Seconds between checks for contracts tracked since the listeners of a chain
+    /// started, such as children discovered through a factory
+    #[serde(default = "default_contract_reload_secs")]
+    pub contract_reload_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogFilterMode {
+    #[default]
+    PerContract,
+    Combined,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProcessorConfig {
     pub artifacts_base_path: String,
-    pub contracts: String,
     #[serde(default = "default_poll_interval")]
     pub poll_interval: String,
     #[serde(default = "default_batch_size")]
@@ -72,6 +158,10 @@ pub struct ProcessorConfig {
 
 /* ---------------- defaults ---------------- */
 
+fn default_subscription_retry_secs() -> u64 {
+    30
+}
+
 fn default_max_connections() -> u32 {
     10
 }
@@ -88,6 +178,50 @@ fn default_log_output() -> String {
     "stdout".to_string()
 }
 
+fn default_max_log_window() -> u64 {
+    10_000
+}
+
+fn default_max_head_lag() -> u64 {
+    10
+}
+
+fn default_rpc_max_retries() -> u32 {
+    3
+}
+
+fn default_rpc_retry_base_delay_ms() -> u64 {
+    250
+}
+
+fn default_rpc_retry_max_delay_ms() -> u64 {
+    10_000
+}
+
+fn default_rpc_circuit_failure_threshold() -> u32 {
+    5
+}
+
+fn default_rpc_circuit_open_secs() -> u64 {
+    30
+}
+
+fn default_rpc_budget_processes() -> u32 {
+    1
+}
+
+fn default_reorg_depth() -> u64 {
+    64
+}
+
+fn default_insert_batch_size() -> usize {
+    1_000
+}
+
+fn default_contract_reload_secs() -> u64 {
+    30
+}
+
 fn default_poll_interval() -> String {
     "10".to_string()
 }
@@ -1,6 +1,6 @@
-use std::fs;
+use std::{collections::HashMap, fs};
 
-use alloy::json_abi::JsonAbi;
+use alloy::{json_abi::JsonAbi, primitives::B256};
 
 use crate::services::usecase::errors::AppError;
 
@@ -13,7 +13,7 @@ impl AbiLoader {
     pub fn load(&self, contract: &str) -> Result<JsonAbi, AppError> {
         let path = format!("{}/{}.json", self.artifacts_base_path, contract);
 
-        let bytes = fs::read(&path).map_err(|_| AppError::MissingContractAbiFile(path.into()))?;
+        let bytes = fs::read(&path).map_err(|_| AppError::MissingContractAbiFile(path))?;
 
         let abi = serde_json::from_slice(&bytes)
             .map_err(|_| AppError::InvalidAbiFile(contract.into()))?;
@@ -21,6 +21,50 @@ impl AbiLoader {
         Ok(abi)
     }
 
+    /// Name to load the ABI of `contract` by, under the subdirectory of a `deployment`
+    /// whose ABIs differ from the default ones, such as a fork renaming events.
+    pub fn abi_name(contract: &str, deployment: Option<&str>) -> String {
+        match deployment {
+            Some(deployment) => format!("{deployment}/{contract}"),
+            None => contract.into(),
+        }
+    }
+
+    /// Names `contract` gives the events a handler looks up by their canonical name, read
+    /// from `<contract>.events.json` next to its ABI, e.g. `{ "Sync": "ReservesUpdated" }`.
+    /// Events are looked up by their canonical name when there is no such file.
+    pub fn event_names(&self, contract: &str) -> Result<HashMap<String, String>, AppError> {
+        let path = format!("{}/{}.events.json", self.artifacts_base_path, contract);
+
+        let Ok(bytes) = fs::read(&path) else {
+            return Ok(HashMap::new());
+        };
+
+        serde_json::from_slice(&bytes).map_err(|_| AppError::InvalidAbiFile(path))
+    }
+
+    /// Resolves event names declared in the contract's ABI to their topic0 hashes.
+    pub fn event_signatures(
+        &self,
+        contract: &str,
+        events: &[String],
+    ) -> Result<Vec<B256>, AppError> {
+        let abi = self.load(contract)?;
+
+        events
+            .iter()
+            .map(|name| {
+                abi.events
+                    .get(name)
+                    .map(|overloads| overloads.iter().map(|event| event.selector()))
+                    .ok_or_else(|| AppError::MissingEvent(contract.into(), name.clone()))
+            })
+            .try_fold(Vec::new(), |mut signatures, selectors| {
+                signatures.extend(selectors?);
+                Ok(signatures)
+            })
+    }
+
     pub fn new(artifacts_base_path: String) -> Self {
         Self {
             artifacts_base_path,
@@ -73,6 +117,79 @@ mod tests {
         assert_eq!(func.outputs[0].name, "balance");
     }
 
+    const EVENTS_ABI: &str = r#"
+       [
+           {
+               "type": "event",
+               "name": "Transfer",
+               "anonymous": false,
+               "inputs": [
+                   { "name": "from", "type": "address", "indexed": true },
+                   { "name": "to", "type": "address", "indexed": true },
+                   { "name": "value", "type": "uint256", "indexed": false }
+               ]
+           }
+       ]
+       "#;
+
+    #[test]
+    fn event_signatures_resolve_to_topic0() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("erc20.json"), EVENTS_ABI).unwrap();
+        let loader = AbiLoader::new(dir.path().to_string_lossy().to_string());
+
+        let signatures = loader
+            .event_signatures("erc20", &["Transfer".to_string()])
+            .unwrap();
+
+        assert_eq!(
+            signatures,
+            vec![alloy::primitives::keccak256(
+                "Transfer(address,address,uint256)"
+            )]
+        );
+
+        match loader.event_signatures("erc20", &["Approval".to_string()]) {
+            Err(AppError::MissingEvent(contract, event)) => {
+                assert_eq!(contract, "erc20");
+                assert_eq!(event, "Approval");
+            }
+            _ => panic!("Expected MissingEvent error"),
+        }
+    }
+
+    #[test]
+    fn deployment_abi_is_loaded_from_its_subdirectory() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("sushiswap")).unwrap();
+        fs::write(dir.path().join("sushiswap").join("erc20.json"), EVENTS_ABI).unwrap();
+        let loader = AbiLoader::new(dir.path().to_string_lossy().to_string());
+
+        let abi = loader
+            .load(&AbiLoader::abi_name("erc20", Some("sushiswap")))
+            .unwrap();
+
+        assert!(abi.events.contains_key("Transfer"));
+        assert!(loader.load(&AbiLoader::abi_name("erc20", None)).is_err());
+    }
+
+    #[test]
+    fn event_names_are_read_next_to_the_abi() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("forkswap")).unwrap();
+        fs::write(
+            dir.path().join("forkswap").join("pair.events.json"),
+            r#"{ "Sync": "ReservesUpdated" }"#,
+        )
+        .unwrap();
+        let loader = AbiLoader::new(dir.path().to_string_lossy().to_string());
+
+        let names = loader.event_names("forkswap/pair").unwrap();
+
+        assert_eq!(names["Sync"], "ReservesUpdated");
+        assert!(loader.event_names("pair").unwrap().is_empty());
+    }
+
     #[test]
     fn missing_abi_file_returns_error() {
         let dir = tempdir().unwrap();
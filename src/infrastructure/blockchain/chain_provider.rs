@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use crate::{
+    config::ListenerConfig,
+    infrastructure::blockchain::{
+        failover_provider::FailoverProvider,
+        provider::EVMProvider,
+        request_budget::RequestBudgets,
+        retry_provider::{RetryPolicy, RetryProvider},
+    },
+    services::{entities::evm_chains::EvmChains, usecase::errors::AppError},
+};
+
+/// Every RPC endpoint of a chain retried behind its own circuit breaker, with
+/// requests routed to the healthiest.
+pub type ChainProvider = FailoverProvider<RetryProvider<EVMProvider>>;
+
+/// Endpoints a chain is reached through.
+#[derive(Debug, Clone)]
+pub struct ChainEndpoints {
+    pub rpc_urls: Vec<String>,
+
+    /// Streams new logs when set
+    pub ws_url: Option<String>,
+}
+
+impl ChainEndpoints {
+    /// Endpoints stored with the chain, falling back to the environment for the chain
+    /// `config.chain_id` names.
+    pub fn resolve(chain: &EvmChains, config: &ListenerConfig) -> Result<Self, AppError> {
+        let is_configured_chain = config.chain_id == Some(chain.id as u64);
+
+        let mut rpc_urls = chain.rpc_urls.clone();
+        if rpc_urls.is_empty() && is_configured_chain {
+            rpc_urls.extend(config.rpc_url.clone());
+        }
+        if rpc_urls.is_empty() {
+            return Err(AppError::ConfigError(format!(
+                "No RPC URL for chain {}",
+                chain.id
+            )));
+        }
+
+        let ws_url = chain
+            .ws_url
+            .clone()
+            .or_else(|| config.ws_url.clone().filter(|_| is_configured_chain))
+            .filter(|ws_url| !ws_url.is_empty());
+
+        Ok(Self { rpc_urls, ws_url })
+    }
+}
+
+/// Builds the provider of a chain, each endpoint spending the budget `budgets` keeps
+/// for its URL.
+pub async fn new_chain_provider(
+    chain_id: u64,
+    endpoints: &ChainEndpoints,
+    config: &ListenerConfig,
+    budgets: &RequestBudgets,
+) -> Result<ChainProvider, AppError> {
+    let retry_policy = RetryPolicy {
+        max_retries: config.rpc_max_retries,
+        base_delay: Duration::from_millis(config.rpc_retry_base_delay_ms),
+        max_delay: Duration::from_millis(config.rpc_retry_max_delay_ms),
+        failure_threshold: config.rpc_circuit_failure_threshold,
+        open_duration: Duration::from_secs(config.rpc_circuit_open_secs),
+    };
+
+    // Subscriptions go through the first endpoint, the only one given the WebSocket URL
+    let mut providers = Vec::with_capacity(endpoints.rpc_urls.len());
+    for (index, rpc_url) in endpoints.rpc_urls.iter().enumerate() {
+        let ws_url = endpoints.ws_url.as_deref().filter(|_| index == 0);
+        let budget = budgets.endpoint(rpc_url);
+        let provider = EVMProvider::new(rpc_url, ws_url, config.max_log_window, budget)
+            .await
+            .map_err(|e| AppError::ProviderError(e.to_string()))?;
+        let name = format!("{chain_id}#{index}");
+        let provider = RetryProvider::new(name.clone(), provider, retry_policy.clone());
+        providers.push((name, provider));
+    }
+
+    Ok(FailoverProvider::new(providers, config.max_head_lag))
+}
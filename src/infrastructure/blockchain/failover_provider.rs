@@ -0,0 +1,548 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use alloy::{
+    primitives::{Address, B256, Bytes},
+    rpc::types::{BlockNumberOrTag, Filter, Header, Log, Transaction, TransactionReceipt},
+};
+use async_trait::async_trait;
+use futures::future::{BoxFuture, join_all};
+
+use crate::{
+    infrastructure::blockchain::{
+        provider::{BlockchainProvider, LogStream},
+        retry_provider::CircuitState,
+    },
+    services::usecase::errors::AppError,
+};
+
+/// Weight of the latest request in the latency and error rate averages.
+const SMOOTHING: f64 = 0.2;
+
+/// Score added by an endpoint failing every request, in milliseconds of latency.
+const ERROR_PENALTY_MS: f64 = 5_000.0;
+
+/// Score added per block an endpoint lags behind the highest observed head.
+const HEAD_LAG_PENALTY_MS: f64 = 250.0;
+
+/// Request statistics of a single RPC endpoint.
+#[derive(Debug, Default)]
+struct EndpointHealth {
+    /// Moving average of successful request latencies
+    latency_ms: f64,
+
+    /// Moving average of failed requests, from 0 to 1
+    error_rate: f64,
+
+    /// Latest block number the endpoint reported
+    head: Option<u64>,
+}
+
+impl EndpointHealth {
+    fn record_success(&mut self, latency: Duration) {
+        let latency_ms = latency.as_secs_f64() * 1_000.0;
+        self.latency_ms = self.latency_ms * (1.0 - SMOOTHING) + latency_ms * SMOOTHING;
+        self.error_rate *= 1.0 - SMOOTHING;
+    }
+
+    fn record_failure(&mut self) {
+        self.error_rate = self.error_rate * (1.0 - SMOOTHING) + SMOOTHING;
+    }
+
+    fn lag(&self, best_head: u64) -> u64 {
+        self.head.map_or(0, |head| best_head.saturating_sub(head))
+    }
+
+    /// Lower is healthier: latency plus penalties for errors and a stale head.
+    fn score(&self, best_head: u64) -> f64 {
+        self.latency_ms
+            + self.error_rate * ERROR_PENALTY_MS
+            + self.lag(best_head) as f64 * HEAD_LAG_PENALTY_MS
+    }
+}
+
+struct Endpoint<P> {
+    name: String,
+    provider: P,
+    health: Mutex<EndpointHealth>,
+}
+
+/// Routes every request to the healthiest of several RPC endpoints, trying the next
+/// one when it fails.
+///
+/// Endpoints lagging more than `max_head_lag` blocks behind the highest head any of
+/// them reported are only used once every other endpoint has failed.
+pub struct FailoverProvider<P> {
+    endpoints: Vec<Endpoint<P>>,
+    max_head_lag: u64,
+}
+
+impl<P: BlockchainProvider> FailoverProvider<P> {
+    /// Wraps `(name, provider)` pairs, the name only being used in logs.
+    pub fn new(endpoints: Vec<(String, P)>, max_head_lag: u64) -> Self {
+        Self {
+            endpoints: endpoints
+                .into_iter()
+                .map(|(name, provider)| Endpoint {
+                    name,
+                    provider,
+                    health: Mutex::new(EndpointHealth::default()),
+                })
+                .collect(),
+            max_head_lag,
+        }
+    }
+
+    fn best_head(&self) -> u64 {
+        self.endpoints
+            .iter()
+            .filter_map(|endpoint| endpoint.health.lock().unwrap().head)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Endpoint indices from healthiest to least healthy, lagging endpoints and
+    /// endpoints with an open circuit last.
+    fn ranked(&self) -> Vec<usize> {
+        let best_head = self.best_head();
+        let mut ranked: Vec<(bool, f64, usize)> = self
+            .endpoints
+            .iter()
+            .enumerate()
+            .map(|(index, endpoint)| {
+                let health = endpoint.health.lock().unwrap();
+                (
+                    health.lag(best_head) > self.max_head_lag
+                        || endpoint.provider.circuit_state() == CircuitState::Open,
+                    health.score(best_head),
+                    index,
+                )
+            })
+            .collect();
+        ranked.sort_by(|a, b| a.0.cmp(&b.0).then(a.1.total_cmp(&b.1)));
+
+        ranked.into_iter().map(|(_, _, index)| index).collect()
+    }
+
+    async fn route<'a, T, F>(&'a self, request: F) -> Result<T, AppError>
+    where
+        F: Fn(&'a P) -> BoxFuture<'a, Result<T, AppError>>,
+    {
+        self.route_among(self.ranked(), request).await
+    }
+
+    /// Sends the request to the `ranked` endpoints in turn until one answers.
+    async fn route_among<'a, T, F>(&'a self, ranked: Vec<usize>, request: F) -> Result<T, AppError>
+    where
+        F: Fn(&'a P) -> BoxFuture<'a, Result<T, AppError>>,
+    {
+        let mut last_error = None;
+        for index in ranked {
+            let endpoint = &self.endpoints[index];
+            let started = Instant::now();
+            match request(&endpoint.provider).await {
+                Ok(value) => {
+                    let latency = started.elapsed();
+                    endpoint.health.lock().unwrap().record_success(latency);
+                    return Ok(value);
+                }
+                Err(err) => {
+                    endpoint.health.lock().unwrap().record_failure();
+                    eprintln!("RPC endpoint {} failed: {err}", endpoint.name);
+                    last_error = Some(err);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(|| AppError::ProviderError("No RPC endpoints".into())))
+    }
+}
+
+#[async_trait]
+impl<P: BlockchainProvider> BlockchainProvider for FailoverProvider<P> {
+    /// Polls every reachable endpoint, which keeps their heads fresh, and returns the
+    /// highest.
+    async fn get_block_number(&self) -> Result<u64, AppError> {
+        // Endpoints with an open circuit are only polled once none is left
+        let mut polled: Vec<&Endpoint<P>> = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.provider.circuit_state() != CircuitState::Open)
+            .collect();
+        if polled.is_empty() {
+            polled = self.endpoints.iter().collect();
+        }
+
+        let results = join_all(polled.into_iter().map(|endpoint| async move {
+            let started = Instant::now();
+            let result = endpoint.provider.get_block_number().await;
+            (endpoint, started.elapsed(), result)
+        }))
+        .await;
+
+        let mut answered = false;
+        let mut last_error = None;
+        for (endpoint, latency, result) in results {
+            let mut health = endpoint.health.lock().unwrap();
+            match result {
+                Ok(head) => {
+                    health.record_success(latency);
+                    health.head = Some(head);
+                    answered = true;
+                }
+                Err(err) => {
+                    health.record_failure();
+                    eprintln!("RPC endpoint {} failed: {err}", endpoint.name);
+                    last_error = Some(err);
+                }
+            }
+        }
+
+        match answered {
+            true => Ok(self.best_head()),
+            false => {
+                Err(last_error
+                    .unwrap_or_else(|| AppError::ProviderError("No RPC endpoints".into())))
+            }
+        }
+    }
+
+    async fn get_block_number_by_tag(&self, tag: BlockNumberOrTag) -> Result<u64, AppError> {
+        self.route(|provider| provider.get_block_number_by_tag(tag))
+            .await
+    }
+
+    /// Sent only to endpoints whose last reported head reached the end of the range,
+    /// as the others answer without the logs of the blocks they have not seen yet.
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, AppError> {
+        let Some(to_block) = filter.get_to_block() else {
+            return self.route(|provider| provider.get_logs(filter)).await;
+        };
+
+        let synced: Vec<usize> = self
+            .ranked()
+            .into_iter()
+            .filter(|&index| {
+                let health = self.endpoints[index].health.lock().unwrap();
+                health.head.is_some_and(|head| head >= to_block)
+            })
+            .collect();
+        if synced.is_empty() {
+            return Err(AppError::TransientRpcError(format!(
+                "No RPC endpoint has reached block {to_block}"
+            )));
+        }
+
+        self.route_among(synced, |provider| provider.get_logs(filter))
+            .await
+    }
+
+    async fn get_block_header(&self, block_number: u64) -> Result<Option<Header>, AppError> {
+        // A block at or below the best head is only missing from a lagging endpoint
+        let best_head = self.best_head();
+        self.route(|provider| {
+            Box::pin(async move {
+                match provider.get_block_header(block_number).await? {
+                    None if block_number <= best_head => Err(AppError::RpcError(format!(
+                        "Block {block_number} not found"
+                    ))),
+                    header => Ok(header),
+                }
+            })
+        })
+        .await
+    }
+
+    async fn get_code(&self, address: Address, block_number: u64) -> Result<Bytes, AppError> {
+        self.route(|provider| provider.get_code(address, block_number))
+            .await
+    }
+
+    async fn get_block_receipts(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<Vec<TransactionReceipt>>, AppError> {
+        self.route(|provider| provider.get_block_receipts(block_number))
+            .await
+    }
+
+    async fn get_transaction_receipt(
+        &self,
+        hash: B256,
+    ) -> Result<Option<TransactionReceipt>, AppError> {
+        self.route(|provider| provider.get_transaction_receipt(hash))
+            .await
+    }
+
+    async fn get_block_transactions(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<Vec<Transaction>>, AppError> {
+        self.route(|provider| provider.get_block_transactions(block_number))
+            .await
+    }
+
+    /// Subscribes through the endpoints given a WebSocket URL only, so that the others
+    /// are not penalised for lacking one.
+    async fn subscribe_logs(&self, filter: &Filter) -> Result<LogStream, AppError> {
+        let ranked: Vec<usize> = self
+            .ranked()
+            .into_iter()
+            .filter(|&index| self.endpoints[index].provider.supports_subscriptions())
+            .collect();
+        if ranked.is_empty() {
+            return Err(AppError::RpcError(
+                "No WebSocket endpoint configured".into(),
+            ));
+        }
+
+        self.route_among(ranked, |provider| provider.subscribe_logs(filter))
+            .await
+    }
+
+    fn log_window(&self) -> u64 {
+        self.ranked()
+            .first()
+            .map_or(1, |&index| self.endpoints[index].provider.log_window())
+    }
+
+    /// Closed while any endpoint takes requests.
+    fn supports_subscriptions(&self) -> bool {
+        self.endpoints
+            .iter()
+            .any(|endpoint| endpoint.provider.supports_subscriptions())
+    }
+
+    fn circuit_state(&self) -> CircuitState {
+        let states: Vec<CircuitState> = self
+            .endpoints
+            .iter()
+            .map(|endpoint| endpoint.provider.circuit_state())
+            .collect();
+
+        [CircuitState::Closed, CircuitState::HalfOpen]
+            .into_iter()
+            .find(|state| states.contains(state))
+            .unwrap_or(CircuitState::Open)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+
+    #[test]
+    fn scores_latency_errors_and_head_lag() {
+        let fast = EndpointHealth {
+            latency_ms: 50.0,
+            error_rate: 0.0,
+            head: Some(1_000),
+        };
+        let failing = EndpointHealth {
+            latency_ms: 20.0,
+            error_rate: 0.5,
+            head: Some(1_000),
+        };
+        let lagging = EndpointHealth {
+            latency_ms: 20.0,
+            error_rate: 0.0,
+            head: Some(990),
+        };
+
+        assert!(fast.score(1_000) < failing.score(1_000));
+        assert!(fast.score(1_000) < lagging.score(1_000));
+        assert_eq!(lagging.lag(1_000), 10);
+    }
+
+    /// Endpoint reporting a fixed head and failing every other request when `down`.
+    struct FakeEndpoint {
+        head: u64,
+        down: bool,
+        websocket: bool,
+    }
+
+    #[async_trait]
+    impl BlockchainProvider for FakeEndpoint {
+        async fn get_block_number(&self) -> Result<u64, AppError> {
+            Ok(self.head)
+        }
+
+        async fn get_block_number_by_tag(&self, _: BlockNumberOrTag) -> Result<u64, AppError> {
+            match self.down {
+                true => Err(AppError::RpcError("connection refused".into())),
+                false => Ok(self.head),
+            }
+        }
+
+        /// One log per block of the range, up to the head like a lagging node answers.
+        async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, AppError> {
+            let from_block = filter.get_from_block().unwrap_or_default();
+            let to_block = filter.get_to_block().unwrap_or(self.head).min(self.head);
+
+            Ok((from_block..=to_block)
+                .map(|block_number| Log {
+                    block_number: Some(block_number),
+                    ..Default::default()
+                })
+                .collect())
+        }
+
+        async fn get_block_header(&self, _: u64) -> Result<Option<Header>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_code(&self, _: Address, _: u64) -> Result<Bytes, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_receipts(
+            &self,
+            _: u64,
+        ) -> Result<Option<Vec<TransactionReceipt>>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            _: B256,
+        ) -> Result<Option<TransactionReceipt>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_transactions(
+            &self,
+            _: u64,
+        ) -> Result<Option<Vec<Transaction>>, AppError> {
+            unimplemented!()
+        }
+
+        async fn subscribe_logs(&self, _: &Filter) -> Result<LogStream, AppError> {
+            match self.websocket {
+                true => Ok(futures::stream::empty().boxed()),
+                false => Err(AppError::RpcError(
+                    "No WebSocket endpoint configured".into(),
+                )),
+            }
+        }
+
+        fn log_window(&self) -> u64 {
+            unimplemented!()
+        }
+
+        fn supports_subscriptions(&self) -> bool {
+            self.websocket
+        }
+    }
+
+    fn failover(endpoints: Vec<FakeEndpoint>) -> FailoverProvider<FakeEndpoint> {
+        let endpoints = endpoints
+            .into_iter()
+            .enumerate()
+            .map(|(index, endpoint)| (format!("endpoint-{index}"), endpoint))
+            .collect();
+
+        FailoverProvider::new(endpoints, 5)
+    }
+
+    #[tokio::test]
+    async fn fails_over_and_prefers_healthy_endpoints() {
+        let provider = failover(vec![
+            FakeEndpoint {
+                head: 100,
+                down: true,
+                websocket: false,
+            },
+            FakeEndpoint {
+                head: 100,
+                down: false,
+                websocket: false,
+            },
+        ]);
+
+        // Both start unscored, so the failing endpoint is tried first
+        let head = provider
+            .get_block_number_by_tag(BlockNumberOrTag::Latest)
+            .await
+            .unwrap();
+
+        assert_eq!(head, 100);
+        assert_eq!(provider.ranked(), vec![1, 0]);
+    }
+
+    #[tokio::test]
+    async fn ejects_endpoints_lagging_behind_the_best_head() {
+        let provider = failover(vec![
+            FakeEndpoint {
+                head: 90,
+                down: false,
+                websocket: false,
+            },
+            FakeEndpoint {
+                head: 100,
+                down: false,
+                websocket: false,
+            },
+        ]);
+
+        assert_eq!(provider.get_block_number().await.unwrap(), 100);
+        assert_eq!(provider.ranked(), vec![1, 0]);
+    }
+
+    #[tokio::test]
+    async fn routes_logs_to_endpoints_that_reached_the_end_of_the_range() {
+        let provider = failover(vec![
+            FakeEndpoint {
+                head: 97,
+                down: false,
+                websocket: false,
+            },
+            FakeEndpoint {
+                head: 100,
+                down: false,
+                websocket: false,
+            },
+        ]);
+        provider.get_block_number().await.unwrap();
+
+        // The lagging endpoint is still within the lag tolerated and much faster
+        provider.endpoints[1].health.lock().unwrap().latency_ms = 2_000.0;
+        assert_eq!(provider.ranked(), vec![0, 1]);
+
+        let filter = Filter::new().from_block(95).to_block(100);
+        let logs = provider.get_logs(&filter).await.unwrap();
+        assert_eq!(logs.len(), 6);
+
+        let ahead = Filter::new().from_block(95).to_block(101);
+        assert!(matches!(
+            provider.get_logs(&ahead).await,
+            Err(AppError::TransientRpcError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn subscribes_through_the_websocket_endpoint_only() {
+        let provider = failover(vec![
+            FakeEndpoint {
+                head: 100,
+                down: false,
+                websocket: false,
+            },
+            FakeEndpoint {
+                head: 100,
+                down: false,
+                websocket: true,
+            },
+        ]);
+        provider.endpoints[1].health.lock().unwrap().latency_ms = 2_000.0;
+
+        let _stream = provider.subscribe_logs(&Filter::new()).await.unwrap();
+
+        assert!(provider.supports_subscriptions());
+        assert_eq!(provider.endpoints[0].health.lock().unwrap().error_rate, 0.0);
+        assert_eq!(provider.ranked(), vec![0, 1]);
+    }
+}
@@ -0,0 +1,189 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use alloy::rpc::types::{Filter, Log};
+
+use crate::services::usecase::errors::AppError;
+
+/// Fragments of the error messages providers return when an `eth_getLogs` query spans
+/// too many blocks or matches too many logs.
+const LOG_RANGE_ERRORS: &[&str] = &[
+    "query returned more than",
+    "log response size exceeded",
+    "block range is too wide",
+    "block range too large",
+    "exceed maximum block range",
+    "maximum block range",
+    "range limit exceeded",
+    "range is too large",
+    "response size should not greater than",
+    "logs matched by query exceeds limit",
+    "is limited to a",
+    "too many results",
+];
+
+/// Whether an RPC error message means the `eth_getLogs` range has to be narrowed.
+pub fn is_log_range_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    LOG_RANGE_ERRORS
+        .iter()
+        .any(|fragment| message.contains(fragment))
+}
+
+/// Block span used for `eth_getLogs`, learned from the provider's responses: it
+/// halves below any span the provider rejects and grows by a quarter after every
+/// full-size span it accepts.
+#[derive(Debug)]
+pub struct AdaptiveLogWindow {
+    size: AtomicU64,
+    max_size: u64,
+}
+
+impl AdaptiveLogWindow {
+    pub fn new(max_size: u64) -> Self {
+        let max_size = max_size.max(1);
+        Self {
+            size: AtomicU64::new(max_size),
+            max_size,
+        }
+    }
+
+    pub fn size(&self) -> u64 {
+        self.size.load(Ordering::Relaxed)
+    }
+
+    pub fn record_success(&self, span: u64) {
+        let _ = self
+            .size
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |size| {
+                (span >= size && size < self.max_size)
+                    .then(|| (size + size.div_ceil(4)).min(self.max_size))
+            });
+    }
+
+    pub fn record_rejection(&self, span: u64) {
+        self.size.fetch_min((span / 2).max(1), Ordering::Relaxed);
+    }
+}
+
+/// Fetches the logs for `filter`'s block range, bisecting any sub-range the provider
+/// rejects as too large and merging the results in block order.
+pub async fn get_logs_bisecting<F, Fut>(
+    filter: &Filter,
+    window: &AdaptiveLogWindow,
+    fetch: F,
+) -> Result<Vec<Log>, AppError>
+where
+    F: Fn(Filter) -> Fut,
+    Fut: Future<Output = Result<Vec<Log>, AppError>>,
+{
+    let (Some(from_block), Some(to_block)) = (filter.get_from_block(), filter.get_to_block())
+    else {
+        return fetch(filter.clone()).await;
+    };
+
+    let mut logs = Vec::new();
+    let mut pending = vec![(from_block, to_block)];
+    while let Some((from_block, to_block)) = pending.pop() {
+        let span = to_block.saturating_sub(from_block) + 1;
+        let range = filter.clone().from_block(from_block).to_block(to_block);
+
+        match fetch(range).await {
+            Ok(range_logs) => {
+                window.record_success(span);
+                logs.extend(range_logs);
+            }
+            Err(AppError::LogRangeTooLarge(_)) if from_block < to_block => {
+                window.record_rejection(span);
+
+                let mid = from_block + (to_block - from_block) / 2;
+                pending.push((mid + 1, to_block));
+                pending.push((from_block, mid));
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    Ok(logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_at(block_number: u64) -> Log {
+        Log {
+            block_number: Some(block_number),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn recognises_provider_range_errors() {
+        assert!(is_log_range_error(
+            "server returned an error response: error code -32005: query returned more than 10000 results"
+        ));
+        assert!(is_log_range_error(
+            "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range"
+        ));
+        assert!(is_log_range_error("exceed maximum block range: 50000"));
+        assert!(!is_log_range_error("connection reset by peer"));
+    }
+
+    #[test]
+    fn window_shrinks_on_rejection_and_grows_back() {
+        let window = AdaptiveLogWindow::new(10_000);
+
+        window.record_rejection(10_000);
+        assert_eq!(window.size(), 5_000);
+
+        // Spans smaller than the window say nothing about a larger one
+        window.record_success(100);
+        assert_eq!(window.size(), 5_000);
+
+        window.record_success(5_000);
+        assert_eq!(window.size(), 6_250);
+
+        for _ in 0..10 {
+            window.record_success(window.size());
+        }
+        assert_eq!(window.size(), 10_000);
+    }
+
+    #[tokio::test]
+    async fn bisects_rejected_ranges_in_order() {
+        let window = AdaptiveLogWindow::new(1_000);
+        let filter = Filter::new().from_block(0).to_block(999);
+
+        // Rejects anything wider than 300 blocks and returns one log per range start
+        let logs = get_logs_bisecting(&filter, &window, |range| async move {
+            let (from, to) = (
+                range.get_from_block().unwrap(),
+                range.get_to_block().unwrap(),
+            );
+            if to - from + 1 > 300 {
+                Err(AppError::LogRangeTooLarge("too many results".into()))
+            } else {
+                Ok(vec![log_at(from)])
+            }
+        })
+        .await
+        .unwrap();
+
+        let starts: Vec<_> = logs.iter().filter_map(|log| log.block_number).collect();
+        assert_eq!(starts, vec![0, 250, 500, 750]);
+        assert_eq!(window.size(), 313);
+    }
+
+    #[tokio::test]
+    async fn gives_up_on_single_block() {
+        let window = AdaptiveLogWindow::new(4);
+        let filter = Filter::new().from_block(10).to_block(13);
+
+        let result = get_logs_bisecting(&filter, &window, |_| async {
+            Err(AppError::LogRangeTooLarge("too many results".into()))
+        })
+        .await;
+
+        assert!(matches!(result, Err(AppError::LogRangeTooLarge(_))));
+    }
+}
@@ -1 +1,6 @@
+pub mod chain_provider;
+pub mod failover_provider;
+pub mod log_window;
 pub mod provider;
+pub mod request_budget;
+pub mod retry_provider;
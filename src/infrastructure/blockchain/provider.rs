@@ -1,29 +1,126 @@
-use std::str::FromStr;
+use std::{pin::Pin, str::FromStr, sync::Arc};
 
 use alloy::{
-    primitives::Address,
+    eips::BlockId,
+    primitives::{Address, B256, Bytes},
     providers::{Provider, ProviderBuilder},
-    rpc::types::{BlockNumberOrTag, Filter, Log},
+    rpc::types::{
+        BlockNumberOrTag, BlockTransactionsKind, Filter, Header, Log, Transaction,
+        TransactionReceipt,
+    },
+    transports::{RpcError, TransportError, TransportErrorKind},
 };
 use async_trait::async_trait;
+use futures::{Stream, StreamExt};
 
-use crate::services::usecase::errors::AppError;
+use crate::{
+    infrastructure::blockchain::{
+        log_window::{AdaptiveLogWindow, get_logs_bisecting, is_log_range_error},
+        request_budget::RequestBudget,
+        retry_provider::CircuitState,
+    },
+    services::usecase::errors::AppError,
+};
 
 #[async_trait]
 pub trait BlockchainProvider: Send + Sync {
     async fn get_block_number(&self) -> Result<u64, AppError>;
+    async fn get_block_number_by_tag(&self, tag: BlockNumberOrTag) -> Result<u64, AppError>;
     async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, AppError>;
+    async fn get_block_header(&self, block_number: u64) -> Result<Option<Header>, AppError>;
+    async fn get_code(&self, address: Address, block_number: u64) -> Result<Bytes, AppError>;
+
+    /// Receipts of every transaction in the block through `eth_getBlockReceipts`,
+    /// failing with `UnsupportedMethod` on nodes without it.
+    async fn get_block_receipts(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<Vec<TransactionReceipt>>, AppError>;
+    async fn get_transaction_receipt(
+        &self,
+        hash: B256,
+    ) -> Result<Option<TransactionReceipt>, AppError>;
+
+    /// Transactions of the block, with their values, in a single `eth_getBlockByNumber`.
+    async fn get_block_transactions(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<Vec<Transaction>>, AppError>;
+
+    /// Subscribes to the logs matching `filter` as new blocks arrive.
+    async fn subscribe_logs(&self, filter: &Filter) -> Result<LogStream, AppError>;
+
+    /// Number of blocks the provider currently accepts per `eth_getLogs` call.
+    fn log_window(&self) -> u64;
+
+    /// Whether requests currently reach the endpoint or are short-circuited.
+    fn circuit_state(&self) -> CircuitState {
+        CircuitState::Closed
+    }
+
+    /// Whether `subscribe_logs` has a WebSocket endpoint to subscribe through.
+    fn supports_subscriptions(&self) -> bool {
+        false
+    }
+}
+
+/// JSON-RPC error codes providers answer with when rate limiting.
+const RATE_LIMIT_CODES: &[i64] = &[-32005, -32029, 429];
+
+/// JSON-RPC error codes for methods a node does not serve.
+const UNSUPPORTED_METHOD_CODES: &[i64] = &[-32601, -32004];
+
+/// Maps a transport error to the `AppError` its retry handling depends on: oversized
+/// log ranges, rate limits, unsupported methods, transient transport failures, or
+/// permanent errors.
+pub fn rpc_error(err: TransportError) -> AppError {
+    let message = err.to_string();
+    if is_log_range_error(&message) {
+        return AppError::LogRangeTooLarge(message);
+    }
+
+    match &err {
+        RpcError::ErrorResp(payload) if RATE_LIMIT_CODES.contains(&payload.code) => {
+            AppError::RateLimited(message)
+        }
+        RpcError::ErrorResp(payload) if UNSUPPORTED_METHOD_CODES.contains(&payload.code) => {
+            AppError::UnsupportedMethod(message)
+        }
+        RpcError::Transport(TransportErrorKind::HttpError(http)) => match http.status {
+            429 => AppError::RateLimited(message),
+            500.. => AppError::TransientRpcError(message),
+            _ => AppError::RpcError(message),
+        },
+        RpcError::Transport(_) | RpcError::NullResp => AppError::TransientRpcError(message),
+        _ => AppError::RpcError(message),
+    }
 }
 
+/// Logs pushed by an `eth_subscribe` subscription; ends when the connection drops.
+pub type LogStream = Pin<Box<dyn Stream<Item = Log> + Send>>;
+
 pub struct EVMProvider {
     provider: Box<dyn Provider>,
+    ws_url: Option<String>,
+    log_window: AdaptiveLogWindow,
+
+    /// Spent before every request sent to the endpoint
+    budget: Arc<RequestBudget>,
 }
 
 impl EVMProvider {
-    pub async fn new(rpc_url: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    pub async fn new(
+        rpc_url: &str,
+        ws_url: Option<&str>,
+        max_log_window: u64,
+        budget: Arc<RequestBudget>,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
         let provider = ProviderBuilder::new().on_builtin(rpc_url).await?;
         Ok(Self {
             provider: Box::new(provider),
+            ws_url: ws_url.map(str::to_string),
+            log_window: AdaptiveLogWindow::new(max_log_window),
+            budget,
         })
     }
 }
@@ -31,18 +128,152 @@ impl EVMProvider {
 #[async_trait]
 impl BlockchainProvider for EVMProvider {
     async fn get_block_number(&self) -> Result<u64, AppError> {
-        self.provider
-            .get_block_number()
+        self.budget.acquire("eth_blockNumber").await;
+        self.provider.get_block_number().await.map_err(rpc_error)
+    }
+
+    async fn get_block_number_by_tag(&self, tag: BlockNumberOrTag) -> Result<u64, AppError> {
+        self.budget.acquire("eth_getBlockByNumber").await;
+        let block = self
+            .provider
+            .get_block_by_number(tag, BlockTransactionsKind::Hashes)
             .await
-            .map_err(|e| AppError::RpcError(e.to_string()))
+            .map_err(rpc_error)?
+            .ok_or_else(|| AppError::RpcError(format!("Block `{tag}` not found")))?;
+
+        Ok(block.header.number)
     }
 
     async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, AppError> {
+        get_logs_bisecting(filter, &self.log_window, |range| async move {
+            self.budget.acquire("eth_getLogs").await;
+            self.provider.get_logs(&range).await.map_err(rpc_error)
+        })
+        .await
+    }
+
+    async fn get_block_header(&self, block_number: u64) -> Result<Option<Header>, AppError> {
+        self.budget.acquire("eth_getBlockByNumber").await;
+        let block = self
+            .provider
+            .get_block_by_number(
+                BlockNumberOrTag::Number(block_number),
+                BlockTransactionsKind::Hashes,
+            )
+            .await
+            .map_err(rpc_error)?;
+
+        Ok(block.map(|block| block.header))
+    }
+
+    async fn get_code(&self, address: Address, block_number: u64) -> Result<Bytes, AppError> {
+        self.budget.acquire("eth_getCode").await;
+        self.provider
+            .get_code_at(address)
+            .number(block_number)
+            .await
+            .map_err(rpc_error)
+    }
+
+    async fn get_block_receipts(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<Vec<TransactionReceipt>>, AppError> {
+        self.budget.acquire("eth_getBlockReceipts").await;
         self.provider
-            .get_logs(filter)
+            .get_block_receipts(BlockId::number(block_number))
+            .await
+            .map_err(rpc_error)
+    }
+
+    async fn get_transaction_receipt(
+        &self,
+        hash: B256,
+    ) -> Result<Option<TransactionReceipt>, AppError> {
+        self.budget.acquire("eth_getTransactionReceipt").await;
+        self.provider
+            .get_transaction_receipt(hash)
+            .await
+            .map_err(rpc_error)
+    }
+
+    async fn get_block_transactions(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<Vec<Transaction>>, AppError> {
+        self.budget.acquire("eth_getBlockByNumber").await;
+        let block = self
+            .provider
+            .get_block_by_number(
+                BlockNumberOrTag::Number(block_number),
+                BlockTransactionsKind::Full,
+            )
             .await
-            .map_err(|e| AppError::RpcError(e.to_string()))
+            .map_err(rpc_error)?;
+
+        Ok(block.map(|block| block.transactions.into_transactions().collect()))
+    }
+
+    async fn subscribe_logs(&self, filter: &Filter) -> Result<LogStream, AppError> {
+        let ws_url = self
+            .ws_url
+            .as_deref()
+            .ok_or_else(|| AppError::RpcError("No WebSocket endpoint configured".into()))?;
+
+        // A fresh connection per subscription, as a dropped one stops reconnecting
+        self.budget.acquire("eth_subscribe").await;
+        let subscriber = ProviderBuilder::new()
+            .on_builtin(ws_url)
+            .await
+            .map_err(rpc_error)?;
+        let subscription = subscriber.subscribe_logs(filter).await.map_err(rpc_error)?;
+
+        // The stream keeps the connection alive until it is dropped
+        Ok(subscription
+            .into_stream()
+            .map(move |log| {
+                let _ = &subscriber;
+                log
+            })
+            .boxed())
+    }
+
+    fn log_window(&self) -> u64 {
+        self.log_window.size()
+    }
+
+    fn supports_subscriptions(&self) -> bool {
+        self.ws_url.is_some()
+    }
+}
+
+/// Binary searches `eth_getCode` for the first block at which `address` has code.
+///
+/// Needs a node that serves historical state; returns `None` when there is no code
+/// at `latest_block`.
+pub async fn find_deployment_block<P>(
+    provider: &P,
+    address: Address,
+    latest_block: u64,
+) -> Result<Option<u64>, AppError>
+where
+    P: BlockchainProvider + ?Sized,
+{
+    if provider.get_code(address, latest_block).await?.is_empty() {
+        return Ok(None);
+    }
+
+    let (mut low, mut high) = (0, latest_block);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if provider.get_code(address, mid).await?.is_empty() {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
     }
+
+    Ok(Some(low))
 }
 
 pub fn create_log_filter(
@@ -50,14 +281,40 @@ pub fn create_log_filter(
     from_block: u64,
     to_block: u64,
 ) -> Result<Filter, AppError> {
-    let addr = Address::from_str(address).map_err(|e| AppError::InvalidAddress(e.to_string()))?;
+    create_multi_address_log_filter(&[address], from_block, to_block)
+}
+
+/// Creates a filter matching the logs of any of `addresses` in the block range.
+pub fn create_multi_address_log_filter(
+    addresses: &[&str],
+    from_block: u64,
+    to_block: u64,
+) -> Result<Filter, AppError> {
+    let addrs = addresses
+        .iter()
+        .map(|address| {
+            Address::from_str(address).map_err(|e| AppError::InvalidAddress(e.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
 
     Ok(Filter::new()
-        .address(addr)
+        .address(addrs)
         .from_block(BlockNumberOrTag::Number(from_block))
         .to_block(BlockNumberOrTag::Number(to_block)))
 }
 
+/// Creates a filter matching the new logs of any of `addresses`, for subscriptions.
+pub fn create_subscription_log_filter(addresses: &[&str]) -> Result<Filter, AppError> {
+    let addrs = addresses
+        .iter()
+        .map(|address| {
+            Address::from_str(address).map_err(|e| AppError::InvalidAddress(e.to_string()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(Filter::new().address(addrs))
+}
+
 #[cfg(test)]
 mod filter_tests {
     use super::*;
@@ -77,4 +334,155 @@ mod filter_tests {
         let result = create_log_filter("INVALID_ADDR", 10, 20);
         assert!(matches!(result, Err(AppError::InvalidAddress(_))));
     }
+
+    #[test]
+    fn create_multi_address_log_filter_success() {
+        let filter = create_multi_address_log_filter(
+            &[
+                "0x1111111111111111111111111111111111111111",
+                "0x2222222222222222222222222222222222222222",
+            ],
+            100,
+            200,
+        )
+        .unwrap();
+
+        assert_eq!(filter.address.iter().count(), 2);
+        assert_eq!(filter.get_from_block(), Some(100));
+        assert_eq!(filter.get_to_block(), Some(200));
+    }
+}
+
+#[cfg(test)]
+mod rpc_error_tests {
+    use super::*;
+
+    fn error_response(code: i64, message: &str) -> TransportError {
+        let payload = serde_json::json!({ "code": code, "message": message });
+        RpcError::ErrorResp(serde_json::from_value(payload).unwrap())
+    }
+
+    #[test]
+    fn classifies_rate_limits_transient_and_permanent_errors() {
+        assert!(matches!(
+            rpc_error(TransportErrorKind::http_error(
+                429,
+                "Too Many Requests".into()
+            )),
+            AppError::RateLimited(_)
+        ));
+        assert!(matches!(
+            rpc_error(error_response(-32005, "limit exceeded")),
+            AppError::RateLimited(_)
+        ));
+        assert!(matches!(
+            rpc_error(error_response(
+                -32005,
+                "query returned more than 10000 results"
+            )),
+            AppError::LogRangeTooLarge(_)
+        ));
+        assert!(matches!(
+            rpc_error(TransportErrorKind::http_error(
+                503,
+                "Service Unavailable".into()
+            )),
+            AppError::TransientRpcError(_)
+        ));
+        assert!(matches!(
+            rpc_error(TransportErrorKind::backend_gone()),
+            AppError::TransientRpcError(_)
+        ));
+        assert!(matches!(
+            rpc_error(error_response(-32601, "method not found")),
+            AppError::UnsupportedMethod(_)
+        ));
+        assert!(matches!(
+            rpc_error(error_response(-32602, "invalid params")),
+            AppError::RpcError(_)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod deployment_block_tests {
+    use super::*;
+
+    struct DeployedAt(u64);
+
+    #[async_trait]
+    impl BlockchainProvider for DeployedAt {
+        async fn get_block_number(&self) -> Result<u64, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_number_by_tag(&self, _: BlockNumberOrTag) -> Result<u64, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_logs(&self, _: &Filter) -> Result<Vec<Log>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_header(&self, _: u64) -> Result<Option<Header>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_code(&self, _: Address, block_number: u64) -> Result<Bytes, AppError> {
+            if block_number >= self.0 {
+                Ok(Bytes::from_static(&[0x60, 0x80]))
+            } else {
+                Ok(Bytes::new())
+            }
+        }
+
+        async fn get_block_receipts(
+            &self,
+            _: u64,
+        ) -> Result<Option<Vec<TransactionReceipt>>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            _: B256,
+        ) -> Result<Option<TransactionReceipt>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_transactions(
+            &self,
+            _: u64,
+        ) -> Result<Option<Vec<Transaction>>, AppError> {
+            unimplemented!()
+        }
+
+        async fn subscribe_logs(&self, _: &Filter) -> Result<LogStream, AppError> {
+            unimplemented!()
+        }
+
+        fn log_window(&self) -> u64 {
+            unimplemented!()
+        }
+    }
+
+    #[tokio::test]
+    async fn finds_first_block_with_code() {
+        for deployed_at in [0, 1, 4_321, 999_999, 1_000_000] {
+            let block = find_deployment_block(&DeployedAt(deployed_at), Address::ZERO, 1_000_000)
+                .await
+                .unwrap();
+
+            assert_eq!(block, Some(deployed_at));
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_none_without_code_at_latest() {
+        let block = find_deployment_block(&DeployedAt(2_000), Address::ZERO, 1_000)
+            .await
+            .unwrap();
+
+        assert_eq!(block, None);
+    }
 }
@@ -0,0 +1,182 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::time::sleep;
+
+use crate::{config::ListenerConfig, services::usecase::errors::AppError};
+
+/// Units refilled continuously at `rate` per second, up to one second's worth.
+#[derive(Debug)]
+struct TokenBucket {
+    tokens: f64,
+    rate: f64,
+    updated_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, now: Instant) -> Self {
+        Self {
+            tokens: rate,
+            rate,
+            updated_at: now,
+        }
+    }
+
+    /// Spends `weight` units when available at `now`, otherwise returns how long until
+    /// they are. Requests heavier than the whole bucket go through once it is full and
+    /// leave it in debt.
+    fn try_spend(&mut self, weight: f64, now: Instant) -> Option<Duration> {
+        let elapsed = now.saturating_duration_since(self.updated_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.rate);
+        self.updated_at = now;
+
+        let needed = weight.min(self.rate);
+        if self.tokens >= needed {
+            self.tokens -= weight;
+            return None;
+        }
+
+        Some(Duration::from_secs_f64((needed - self.tokens) / self.rate))
+    }
+}
+
+/// Request units an RPC endpoint may be sent per second, shared by every task
+/// calling it. Each RPC method costs its configured weight, and tasks waiting for
+/// units are served in the order they asked.
+#[derive(Debug)]
+pub struct RequestBudget {
+    weights: Arc<HashMap<String, u32>>,
+
+    /// Unlimited when unset
+    bucket: Option<tokio::sync::Mutex<TokenBucket>>,
+}
+
+impl RequestBudget {
+    pub fn new(units_per_sec: u32, weights: Arc<HashMap<String, u32>>) -> Self {
+        let bucket = (units_per_sec > 0).then(|| {
+            tokio::sync::Mutex::new(TokenBucket::new(units_per_sec as f64, Instant::now()))
+        });
+
+        Self { weights, bucket }
+    }
+
+    /// Waits until the endpoint's budget covers a call to `method`.
+    pub async fn acquire(&self, method: &str) {
+        let Some(bucket) = &self.bucket else {
+            return;
+        };
+        let weight = self.weights.get(method).copied().unwrap_or(1) as f64;
+
+        // The lock queues waiting tasks first come, first served
+        let mut bucket = bucket.lock().await;
+        while let Some(wait) = bucket.try_spend(weight, Instant::now()) {
+            sleep(wait).await;
+        }
+    }
+}
+
+/// Split of an endpoint's budget each of `processes` processes spends, at least one
+/// unit a second when the budget is limited.
+fn process_share(units_per_sec: u32, processes: u32) -> u32 {
+    match units_per_sec {
+        0 => 0,
+        units_per_sec => (units_per_sec / processes.max(1)).max(1),
+    }
+}
+
+/// Request budgets of the RPC endpoints a process talks to, keyed by URL so that
+/// every provider built for an endpoint shares its budget. Processes do not share
+/// their budgets, so each one is given a share of the configured rate.
+#[derive(Debug)]
+pub struct RequestBudgets {
+    units_per_sec: u32,
+    weights: Arc<HashMap<String, u32>>,
+    budgets: Mutex<HashMap<String, Arc<RequestBudget>>>,
+}
+
+impl RequestBudgets {
+    pub fn new(units_per_sec: u32, weights: HashMap<String, u32>) -> Self {
+        Self {
+            units_per_sec,
+            weights: Arc::new(weights),
+            budgets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Budgets from this process' share of `rpc_budget_per_sec` and the
+    /// `method:weight` pairs of `rpc_method_weights`.
+    pub fn from_config(config: &ListenerConfig) -> Result<Self, AppError> {
+        let mut weights = HashMap::new();
+        for method_weight in config.rpc_method_weights.split(",") {
+            if method_weight.trim().is_empty() {
+                continue;
+            }
+
+            let (method, weight) = method_weight
+                .split_once(":")
+                .and_then(|(method, weight)| Some((method, weight.trim().parse().ok()?)))
+                .ok_or_else(|| {
+                    AppError::ConfigError(format!("Invalid RPC method weight `{method_weight}`"))
+                })?;
+            weights.insert(method.trim().to_string(), weight);
+        }
+
+        Ok(Self::new(
+            process_share(config.rpc_budget_per_sec, config.rpc_budget_processes),
+            weights,
+        ))
+    }
+
+    pub fn endpoint(&self, rpc_url: &str) -> Arc<RequestBudget> {
+        let mut budgets = self.budgets.lock().unwrap();
+        let budget = budgets.entry(rpc_url.to_string()).or_insert_with(|| {
+            Arc::new(RequestBudget::new(
+                self.units_per_sec,
+                Arc::clone(&self.weights),
+            ))
+        });
+
+        Arc::clone(budget)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refills_at_rate_and_lets_heavy_requests_into_debt() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(10.0, start);
+
+        assert_eq!(bucket.try_spend(10.0, start), None);
+        assert_eq!(
+            bucket.try_spend(5.0, start),
+            Some(Duration::from_millis(500))
+        );
+        assert_eq!(
+            bucket.try_spend(5.0, start + Duration::from_millis(500)),
+            None
+        );
+
+        // Heavier than the bucket: waits for a full bucket, then owes the rest
+        let full = start + Duration::from_millis(1_500);
+        assert_eq!(bucket.try_spend(30.0, full), None);
+        assert_eq!(
+            bucket.try_spend(1.0, full),
+            Some(Duration::from_millis(2_100))
+        );
+    }
+
+    #[test]
+    fn splits_the_budget_between_processes() {
+        assert_eq!(process_share(100, 1), 100);
+        assert_eq!(process_share(100, 3), 33);
+        assert_eq!(process_share(2, 5), 1);
+        assert_eq!(process_share(100, 0), 100);
+        assert_eq!(process_share(0, 3), 0);
+    }
+}
@@ -0,0 +1,504 @@
+use std::{
+    hash::{BuildHasher, Hasher, RandomState},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use alloy::{
+    primitives::{Address, B256, Bytes},
+    rpc::types::{BlockNumberOrTag, Filter, Header, Log, Transaction, TransactionReceipt},
+};
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use tokio::time::sleep;
+
+use crate::{
+    infrastructure::blockchain::provider::{BlockchainProvider, LogStream},
+    services::usecase::errors::AppError,
+};
+
+/// Errors worth sending the same request again for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RetryClass {
+    /// Connection failures, timeouts and server errors
+    Transient,
+
+    /// HTTP 429 and rate limit JSON-RPC errors, backed off twice as long
+    RateLimited,
+}
+
+/// Returns how `err` should be retried, or `None` for permanent errors.
+pub fn retry_class(err: &AppError) -> Option<RetryClass> {
+    match err {
+        AppError::TransientRpcError(_) => Some(RetryClass::Transient),
+        AppError::RateLimited(_) => Some(RetryClass::RateLimited),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Retries after the first attempt
+    pub max_retries: u32,
+
+    /// Backoff before the first retry, doubling with every further one
+    pub base_delay: Duration,
+
+    /// Upper bound for a single backoff
+    pub max_delay: Duration,
+
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+
+    /// How long an open circuit rejects requests before letting a trial through
+    pub open_duration: Duration,
+}
+
+/// Exponential backoff before retry `attempt` (0 based) with equal jitter: the delay
+/// lies between half and all of the exponential step, picked by `entropy`.
+pub fn backoff_delay(
+    policy: &RetryPolicy,
+    attempt: u32,
+    class: RetryClass,
+    entropy: u64,
+) -> Duration {
+    let multiplier = match class {
+        RetryClass::Transient => 1,
+        RetryClass::RateLimited => 2,
+    };
+    let step = policy
+        .base_delay
+        .saturating_mul(multiplier)
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(policy.max_delay);
+
+    let half = step / 2;
+    let jitter_ms = (half.as_millis() as u64).max(1);
+    half + Duration::from_millis(entropy % (jitter_ms + 1))
+}
+
+fn jitter_entropy() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests go through
+    Closed,
+
+    /// Requests are rejected without reaching the endpoint
+    Open,
+
+    /// A single trial request decides whether the circuit closes again
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Instant,
+    trial_in_flight: bool,
+}
+
+/// Stops sending requests to an endpoint after `failure_threshold` consecutive
+/// failures, for `open_duration`.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    name: String,
+    failure_threshold: u32,
+    open_duration: Duration,
+    inner: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: String, failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            name,
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            inner: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: Instant::now(),
+                trial_in_flight: false,
+            }),
+        }
+    }
+
+    pub fn state(&self) -> CircuitState {
+        let inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Open if inner.opened_at.elapsed() >= self.open_duration => {
+                CircuitState::HalfOpen
+            }
+            state => state,
+        }
+    }
+
+    /// Whether a request may be sent now; lets one trial through once the open
+    /// duration has passed.
+    fn try_acquire(&self) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.state {
+            CircuitState::Closed => true,
+            CircuitState::Open if inner.opened_at.elapsed() >= self.open_duration => {
+                println!(
+                    "RPC circuit for {} half open, sending a trial request",
+                    self.name
+                );
+                inner.state = CircuitState::HalfOpen;
+                inner.trial_in_flight = true;
+                true
+            }
+            CircuitState::Open => false,
+            CircuitState::HalfOpen if inner.trial_in_flight => false,
+            CircuitState::HalfOpen => {
+                inner.trial_in_flight = true;
+                true
+            }
+        }
+    }
+
+    fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state != CircuitState::Closed {
+            println!("RPC circuit for {} closed", self.name);
+        }
+
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.trial_in_flight = false;
+    }
+
+    fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.consecutive_failures += 1;
+        inner.trial_in_flight = false;
+
+        let failed_trial = inner.state == CircuitState::HalfOpen;
+        if failed_trial || inner.consecutive_failures >= self.failure_threshold {
+            if inner.state != CircuitState::Open {
+                eprintln!(
+                    "RPC circuit for {} opened after {} consecutive failures",
+                    self.name, inner.consecutive_failures
+                );
+            }
+
+            inner.state = CircuitState::Open;
+            inner.opened_at = Instant::now();
+        }
+    }
+}
+
+/// Retries transient and rate limited failures of the wrapped provider with jittered
+/// exponential backoff, behind a circuit breaker.
+pub struct RetryProvider<P> {
+    inner: P,
+    policy: RetryPolicy,
+    breaker: CircuitBreaker,
+}
+
+impl<P: BlockchainProvider> RetryProvider<P> {
+    pub fn new(name: String, inner: P, policy: RetryPolicy) -> Self {
+        let breaker = CircuitBreaker::new(name, policy.failure_threshold, policy.open_duration);
+        Self {
+            inner,
+            policy,
+            breaker,
+        }
+    }
+
+    async fn call<'a, T, F>(&'a self, request: F) -> Result<T, AppError>
+    where
+        F: Fn(&'a P) -> BoxFuture<'a, Result<T, AppError>>,
+    {
+        let mut attempt = 0;
+        loop {
+            if !self.breaker.try_acquire() {
+                return Err(AppError::CircuitOpen(self.breaker.name.clone()));
+            }
+
+            let err = match request(&self.inner).await {
+                Ok(value) => {
+                    self.breaker.record_success();
+                    return Ok(value);
+                }
+                Err(err) => err,
+            };
+
+            // Permanent errors are answers too, the endpoint itself is fine
+            let Some(class) = retry_class(&err) else {
+                self.breaker.record_success();
+                return Err(err);
+            };
+
+            self.breaker.record_failure();
+            if attempt >= self.policy.max_retries || self.breaker.state() == CircuitState::Open {
+                return Err(err);
+            }
+
+            sleep(backoff_delay(
+                &self.policy,
+                attempt,
+                class,
+                jitter_entropy(),
+            ))
+            .await;
+            attempt += 1;
+        }
+    }
+}
+
+#[async_trait]
+impl<P: BlockchainProvider> BlockchainProvider for RetryProvider<P> {
+    async fn get_block_number(&self) -> Result<u64, AppError> {
+        self.call(|provider| provider.get_block_number()).await
+    }
+
+    async fn get_block_number_by_tag(&self, tag: BlockNumberOrTag) -> Result<u64, AppError> {
+        self.call(|provider| provider.get_block_number_by_tag(tag))
+            .await
+    }
+
+    async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, AppError> {
+        self.call(|provider| provider.get_logs(filter)).await
+    }
+
+    async fn get_block_header(&self, block_number: u64) -> Result<Option<Header>, AppError> {
+        self.call(|provider| provider.get_block_header(block_number))
+            .await
+    }
+
+    async fn get_code(&self, address: Address, block_number: u64) -> Result<Bytes, AppError> {
+        self.call(|provider| provider.get_code(address, block_number))
+            .await
+    }
+
+    async fn get_block_receipts(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<Vec<TransactionReceipt>>, AppError> {
+        self.call(|provider| provider.get_block_receipts(block_number))
+            .await
+    }
+
+    async fn get_transaction_receipt(
+        &self,
+        hash: B256,
+    ) -> Result<Option<TransactionReceipt>, AppError> {
+        self.call(|provider| provider.get_transaction_receipt(hash))
+            .await
+    }
+
+    async fn get_block_transactions(
+        &self,
+        block_number: u64,
+    ) -> Result<Option<Vec<Transaction>>, AppError> {
+        self.call(|provider| provider.get_block_transactions(block_number))
+            .await
+    }
+
+    async fn subscribe_logs(&self, filter: &Filter) -> Result<LogStream, AppError> {
+        self.call(|provider| provider.subscribe_logs(filter)).await
+    }
+
+    fn log_window(&self) -> u64 {
+        self.inner.log_window()
+    }
+
+    fn circuit_state(&self) -> CircuitState {
+        self.breaker.state()
+    }
+
+    fn supports_subscriptions(&self) -> bool {
+        self.inner.supports_subscriptions()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    use super::*;
+    use crate::infrastructure::blockchain::failover_provider::FailoverProvider;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(8),
+            failure_threshold: 3,
+            open_duration: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_within_jitter_bounds() {
+        let policy = RetryPolicy {
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1_000),
+            ..policy()
+        };
+
+        for entropy in [0, 7, u64::MAX] {
+            let first = backoff_delay(&policy, 0, RetryClass::Transient, entropy);
+            let third = backoff_delay(&policy, 2, RetryClass::Transient, entropy);
+            let capped = backoff_delay(&policy, 10, RetryClass::RateLimited, entropy);
+
+            assert!((50..=100).contains(&first.as_millis()));
+            assert!((200..=400).contains(&third.as_millis()));
+            assert!((500..=1_000).contains(&capped.as_millis()));
+        }
+    }
+
+    #[test]
+    fn breaker_opens_and_lets_a_single_trial_through() {
+        let breaker = CircuitBreaker::new("test".into(), 2, Duration::ZERO);
+
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        breaker.record_failure();
+        assert_eq!(breaker.inner.lock().unwrap().state, CircuitState::Open);
+
+        assert!(breaker.try_acquire());
+        assert!(!breaker.try_acquire());
+
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+    }
+
+    /// Fails `get_block_number` with `error` until `failures` calls were made.
+    struct Flaky {
+        calls: AtomicU32,
+        failures: u32,
+        error: fn() -> AppError,
+    }
+
+    #[async_trait]
+    impl BlockchainProvider for Flaky {
+        async fn get_block_number(&self) -> Result<u64, AppError> {
+            match self.calls.fetch_add(1, Ordering::Relaxed) < self.failures {
+                true => Err((self.error)()),
+                false => Ok(42),
+            }
+        }
+
+        async fn get_block_number_by_tag(&self, _: BlockNumberOrTag) -> Result<u64, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_logs(&self, _: &Filter) -> Result<Vec<Log>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_header(&self, _: u64) -> Result<Option<Header>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_code(&self, _: Address, _: u64) -> Result<Bytes, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_receipts(
+            &self,
+            _: u64,
+        ) -> Result<Option<Vec<TransactionReceipt>>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            _: B256,
+        ) -> Result<Option<TransactionReceipt>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_transactions(
+            &self,
+            _: u64,
+        ) -> Result<Option<Vec<Transaction>>, AppError> {
+            unimplemented!()
+        }
+
+        async fn subscribe_logs(&self, _: &Filter) -> Result<LogStream, AppError> {
+            unimplemented!()
+        }
+
+        fn log_window(&self) -> u64 {
+            unimplemented!()
+        }
+    }
+
+    fn flaky(failures: u32, error: fn() -> AppError) -> RetryProvider<Flaky> {
+        let inner = Flaky {
+            calls: AtomicU32::new(0),
+            failures,
+            error,
+        };
+
+        RetryProvider::new("test".into(), inner, policy())
+    }
+
+    #[tokio::test]
+    async fn retries_transient_errors_only() {
+        let provider = flaky(2, || AppError::RateLimited("429 Too Many Requests".into()));
+        assert_eq!(provider.get_block_number().await.unwrap(), 42);
+        assert_eq!(provider.inner.calls.load(Ordering::Relaxed), 3);
+
+        let provider = flaky(1, || AppError::RpcError("invalid params".into()));
+        assert!(matches!(
+            provider.get_block_number().await,
+            Err(AppError::RpcError(_))
+        ));
+        assert_eq!(provider.inner.calls.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn open_circuit_rejects_without_calling_the_endpoint() {
+        let provider = flaky(u32::MAX, || AppError::TransientRpcError("timeout".into()));
+
+        assert!(matches!(
+            provider.get_block_number().await,
+            Err(AppError::TransientRpcError(_))
+        ));
+        assert_eq!(provider.circuit_state(), CircuitState::Open);
+
+        assert!(matches!(
+            provider.get_block_number().await,
+            Err(AppError::CircuitOpen(_))
+        ));
+        assert_eq!(provider.inner.calls.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn failover_sees_the_circuit_open_then_close_after_a_trial() {
+        let inner = Flaky {
+            calls: AtomicU32::new(0),
+            failures: 3,
+            error: || AppError::TransientRpcError("timeout".into()),
+        };
+        let endpoint = RetryProvider::new(
+            "test".into(),
+            inner,
+            RetryPolicy {
+                open_duration: Duration::from_millis(20),
+                ..policy()
+            },
+        );
+        let provider = FailoverProvider::new(vec![("test".into(), endpoint)], 5);
+
+        assert!(provider.get_block_number().await.is_err());
+        assert_eq!(provider.circuit_state(), CircuitState::Open);
+        assert!(matches!(
+            provider.get_block_number().await,
+            Err(AppError::CircuitOpen(_))
+        ));
+
+        sleep(Duration::from_millis(25)).await;
+        assert_eq!(provider.circuit_state(), CircuitState::HalfOpen);
+
+        assert_eq!(provider.get_block_number().await.unwrap(), 42);
+        assert_eq!(provider.circuit_state(), CircuitState::Closed);
+    }
+}
@@ -1,33 +1,48 @@
 use alloy::{
     hex,
     json_abi::JsonAbi,
-    primitives::{Address, Log, keccak256},
+    primitives::{Address, keccak256},
+    rpc::types::Log,
 };
 
 use crate::{
-    infrastructure::{abi::abi_loader::AbiLoader, contracts::ContractHandler},
-    services::usecase::errors::AppError,
+    infrastructure::{abi::abi_loader::AbiLoader, contracts::decoded_event::DecodedEvent},
+    services::{dtos::index_engine::EventSource, usecase::errors::AppError},
 };
 
+/// Contract a handler decodes the logs of, with the ABI it was loaded with. Handlers
+/// keep only the decoding of their own events on top of it.
 pub struct AlloyContractHandler {
+    pub chain_id: u64,
+    #[allow(dead_code)]
     pub address: Address,
     pub abi: JsonAbi,
+    /// Name of the ABI, reported when an event is missing from it
+    pub abi_name: String,
 }
 
-impl ContractHandler for AlloyContractHandler {
-    const NAME: &str = "uniswap_v3_factory";
-
-    fn new(address: &str, loader: AbiLoader) -> Result<Self, AppError> {
+impl AlloyContractHandler {
+    pub fn new(
+        chain_id: u64,
+        address: &str,
+        loader: AbiLoader,
+        abi_name: &str,
+    ) -> Result<Self, AppError> {
         let addr = address
             .parse::<Address>()
             .map_err(|_| AppError::InvalidAddress(address.into()))?;
 
-        let abi = loader.load(Self::NAME)?;
+        let abi = loader.load(abi_name)?;
 
-        Ok(Self { address: addr, abi })
+        Ok(Self {
+            chain_id,
+            address: addr,
+            abi,
+            abi_name: abi_name.into(),
+        })
     }
 
-    fn event_signature_to_name(&self, signature: [u8; 32]) -> Result<String, AppError> {
+    pub fn event_signature_to_name(&self, signature: [u8; 32]) -> Result<String, AppError> {
         let log_sig_hex = format!("0x{}", hex::encode(signature));
 
         let event = self.abi.events.iter().find(|(_name, params)| {
@@ -39,26 +54,60 @@ impl ContractHandler for AlloyContractHandler {
         if let Some((name, _)) = event {
             Ok(name.clone())
         } else {
-            Err(AppError::MissingEvent(Self::NAME.into(), log_sig_hex))
+            Err(AppError::MissingEvent(self.abi_name.clone(), log_sig_hex))
+        }
+    }
+
+    /// Decodes `log` with the event `event_name` of the ABI.
+    pub fn decode(&self, event_name: &str, log: &Log) -> Result<DecodedEvent, AppError> {
+        DecodedEvent::from_abi(&self.abi, &self.abi_name, event_name, log)
+    }
+
+    pub fn source(&self, log: &Log) -> Result<EventSource, AppError> {
+        EventSource::new(self.chain_id, log)
+    }
+}
+
+/// Logs and handlers shared by the decoding tests of every handler.
+#[cfg(test)]
+pub mod fixtures {
+    use alloy::primitives::{B256, Bytes, LogData};
+
+    use super::*;
+
+    pub const CHAIN_ID: u64 = 8453;
+
+    /// Loader of the ABIs shipped with the listener.
+    pub fn artifacts() -> AbiLoader {
+        AbiLoader::new(format!(
+            "{}/src/infrastructure/abi/artifacts",
+            env!("CARGO_MANIFEST_DIR")
+        ))
+    }
+
+    /// Log of `address` at block 42, the fourth of transaction `0xaa…`.
+    pub fn log(address: Address, topics: Vec<B256>, data: Vec<u8>) -> Log {
+        Log {
+            inner: alloy::primitives::Log {
+                address,
+                data: LogData::new_unchecked(topics, Bytes::from(data)),
+            },
+            block_number: Some(42),
+            transaction_hash: Some(B256::repeat_byte(0xaa)),
+            log_index: Some(3),
+            ..Default::default()
         }
     }
 
-    fn handle_event(
-        &self,
-        event_name: &str,
-        _: &Log,
-    ) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
-        async move {
-            match event_name {
-                "PoolCreated" => {
-                    println!("PoolCreated event");
-                    Ok(())
-                }
-                unsupported => Err(AppError::MissingEventHandler(
-                    Self::NAME.into(),
-                    unsupported.into(),
-                )),
-            }
+    /// Source of a `log` of `address`.
+    pub fn source(address: Address) -> EventSource {
+        EventSource {
+            chain_id: CHAIN_ID,
+            address: address.0.0,
+            block_number: 42,
+            transaction_hash: [0xaa; 32],
+            log_index: 3,
+            block_timestamp: None,
         }
     }
 }
@@ -1,31 +1,139 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::RwLock};
 
 use crate::{
     infrastructure::{
         abi::abi_loader::AbiLoader,
-        contracts::{ContractHandler, uniswap::UniswapV3Factory},
+        contracts::{
+            ContractHandler,
+            erc20::Erc20,
+            erc721::Erc721,
+            erc1155::Erc1155,
+            uniswap::{UniswapV3Factory, pool::UniswapV3Pool},
+            uniswap_v2::{UniswapV2Factory, pair::UniswapV2Pair},
+        },
+    },
+    services::{
+        dtos::index_engine::IndexedEvent,
+        entities::{evm_logs::EVMLogs, tracked_contracts::TrackedContracts},
+        usecase::errors::AppError,
     },
-    services::usecase::errors::AppError,
     utils,
 };
 
+/// Handler of a registered contract.
+pub enum ContractProcessor {
+    UniswapV3Factory(UniswapV3Factory),
+    UniswapV3Pool(UniswapV3Pool),
+    Erc20(Erc20),
+    Erc721(Erc721),
+    Erc1155(Erc1155),
+    UniswapV2Factory(UniswapV2Factory),
+    UniswapV2Pair(UniswapV2Pair),
+}
+
+impl ContractProcessor {
+    pub async fn process(&self, unprocessed_log: EVMLogs) -> Result<Vec<IndexedEvent>, AppError> {
+        match self {
+            Self::UniswapV3Factory(handler) => handler.process(unprocessed_log).await,
+            Self::UniswapV3Pool(handler) => handler.process(unprocessed_log).await,
+            Self::Erc20(handler) => handler.process(unprocessed_log).await,
+            Self::Erc721(handler) => handler.process(unprocessed_log).await,
+            Self::Erc1155(handler) => handler.process(unprocessed_log).await,
+            Self::UniswapV2Factory(handler) => handler.process(unprocessed_log).await,
+            Self::UniswapV2Pair(handler) => handler.process(unprocessed_log).await,
+        }
+    }
+}
+
+/// Handler a tracked contract is processed with, and the deployment of its ABI.
+#[derive(Clone)]
+struct RegisteredContract {
+    contract_name: String,
+    deployment: Option<String>,
+}
+
+/// Tracked contracts, keyed by chain and lowercase hex address.
 pub struct ContractRegistry {
-    registry: HashMap<String, String>,
+    registry: RwLock<HashMap<(u64, String), RegisteredContract>>,
     loader: AbiLoader,
 }
 
 impl ContractRegistry {
-    pub fn new(registry: HashMap<String, String>, loader: AbiLoader) -> Self {
-        Self { registry, loader }
+    pub fn new(loader: AbiLoader) -> Self {
+        Self {
+            registry: RwLock::new(HashMap::new()),
+            loader,
+        }
+    }
+
+    /// Replaces the registered contracts with the currently tracked ones.
+    pub fn reload(&self, contracts: &[TrackedContracts]) {
+        let registry = contracts
+            .iter()
+            .map(|contract| {
+                (
+                    (
+                        contract.chain_id as u64,
+                        utils::vec_to_hex(contract.address.to_vec()),
+                    ),
+                    RegisteredContract {
+                        contract_name: contract.contract_name.clone(),
+                        deployment: contract.deployment.clone(),
+                    },
+                )
+            })
+            .collect();
+
+        *self.registry.write().unwrap() = registry;
     }
 
-    pub fn get_processor(&self, address: [u8; 20]) -> Result<impl ContractHandler, AppError> {
+    pub fn get_processor(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+    ) -> Result<ContractProcessor, AppError> {
         let log_address = utils::vec_to_hex(address.to_vec());
-        let contract = self.registry.get(&log_address);
+        let contract = self
+            .registry
+            .read()
+            .unwrap()
+            .get(&(chain_id, log_address.clone()))
+            .cloned();
 
-        if let Some(contract_name) = contract {
+        if let Some(RegisteredContract {
+            contract_name,
+            deployment,
+        }) = contract
+        {
             match contract_name.as_str() {
-                UniswapV3Factory::NAME => UniswapV3Factory::new(&log_address, self.loader.clone()),
+                UniswapV3Factory::NAME => {
+                    UniswapV3Factory::new(chain_id, &log_address, self.loader.clone())
+                        .map(ContractProcessor::UniswapV3Factory)
+                }
+                UniswapV3Pool::NAME => {
+                    UniswapV3Pool::new(chain_id, &log_address, self.loader.clone())
+                        .map(ContractProcessor::UniswapV3Pool)
+                }
+                Erc20::NAME => Erc20::new(chain_id, &log_address, self.loader.clone())
+                    .map(ContractProcessor::Erc20),
+                Erc721::NAME => Erc721::new(chain_id, &log_address, self.loader.clone())
+                    .map(ContractProcessor::Erc721),
+                Erc1155::NAME => Erc1155::new(chain_id, &log_address, self.loader.clone())
+                    .map(ContractProcessor::Erc1155),
+                UniswapV2Factory::NAME => UniswapV2Factory::new(
+                    chain_id,
+                    &log_address,
+                    self.loader.clone(),
+                    deployment.as_deref(),
+                )
+                .map(ContractProcessor::UniswapV2Factory),
+                UniswapV2Pair::NAME => UniswapV2Pair::new(
+                    chain_id,
+                    &log_address,
+                    self.loader.clone(),
+                    deployment.as_deref(),
+                )
+                .map(ContractProcessor::UniswapV2Pair),
                 unsupported => Err(AppError::UnsupportedContract(unsupported.into())),
             }
         } else {
@@ -0,0 +1,137 @@
+use std::{collections::HashMap, str::FromStr};
+
+use alloy::{
+    dyn_abi::{DynSolValue, EventExt},
+    json_abi::{Event, JsonAbi},
+    primitives::{I256, U256},
+    rpc::types::Log,
+};
+
+use sqlx::types::BigDecimal;
+
+use crate::services::usecase::errors::AppError;
+
+/// Fields of a log decoded with the ABI of its event, looked up by parameter name.
+#[derive(Debug)]
+pub struct DecodedEvent {
+    name: String,
+    fields: HashMap<String, DynSolValue>,
+}
+
+impl DecodedEvent {
+    /// Decodes `log` with the event `event_name` of the ABI of `contract`.
+    pub fn from_abi(
+        abi: &JsonAbi,
+        contract: &str,
+        event_name: &str,
+        log: &Log,
+    ) -> Result<Self, AppError> {
+        let event = abi
+            .events
+            .get(event_name)
+            .and_then(|overloads| overloads.first())
+            .ok_or_else(|| AppError::MissingEvent(contract.into(), event_name.into()))?;
+
+        Self::decode(event, log)
+    }
+
+    pub fn decode(event: &Event, log: &Log) -> Result<Self, AppError> {
+        let decoded = event
+            .decode_log(log.data(), true)
+            .map_err(|e| AppError::ParseError(format!("{}: {e}", event.name)))?;
+
+        // Indexed and body values each keep the order of their inputs
+        let mut indexed = decoded.indexed.into_iter();
+        let mut body = decoded.body.into_iter();
+        let fields = event
+            .inputs
+            .iter()
+            .filter_map(|input| {
+                let value = if input.indexed {
+                    indexed.next()
+                } else {
+                    body.next()
+                };
+                value.map(|value| (input.name.clone(), value))
+            })
+            .collect();
+
+        Ok(Self {
+            name: event.name.clone(),
+            fields,
+        })
+    }
+
+    pub fn address(&self, field: &str) -> Result<[u8; 20], AppError> {
+        self.field(field)?
+            .as_address()
+            .map(|address| address.0.0)
+            .ok_or_else(|| self.invalid(field))
+    }
+
+    pub fn uint(&self, field: &str) -> Result<U256, AppError> {
+        self.field(field)?
+            .as_uint()
+            .map(|(value, _)| value)
+            .ok_or_else(|| self.invalid(field))
+    }
+
+    pub fn int(&self, field: &str) -> Result<I256, AppError> {
+        self.field(field)?
+            .as_int()
+            .map(|(value, _)| value)
+            .ok_or_else(|| self.invalid(field))
+    }
+
+    /// An unsigned field narrowed to `T`, such as the `uint24` of a fee.
+    pub fn uint_as<T: TryFrom<U256>>(&self, field: &str) -> Result<T, AppError> {
+        T::try_from(self.uint(field)?).map_err(|_| self.invalid(field))
+    }
+
+    /// A signed field narrowed to `T`, such as the `int24` of a tick.
+    pub fn int_as<T: TryFrom<I256>>(&self, field: &str) -> Result<T, AppError> {
+        T::try_from(self.int(field)?).map_err(|_| self.invalid(field))
+    }
+
+    /// An integer field of any width, such as a token amount.
+    pub fn decimal(&self, field: &str) -> Result<BigDecimal, AppError> {
+        self.to_decimal(field, self.field(field)?)
+    }
+
+    /// An array of integers, such as the ids of an ERC-1155 batch transfer.
+    pub fn decimals(&self, field: &str) -> Result<Vec<BigDecimal>, AppError> {
+        self.field(field)?
+            .as_array()
+            .ok_or_else(|| self.invalid(field))?
+            .iter()
+            .map(|value| self.to_decimal(field, value))
+            .collect()
+    }
+
+    pub fn string(&self, field: &str) -> Result<String, AppError> {
+        self.field(field)?
+            .as_str()
+            .map(String::from)
+            .ok_or_else(|| self.invalid(field))
+    }
+
+    fn to_decimal(&self, field: &str, value: &DynSolValue) -> Result<BigDecimal, AppError> {
+        let value = match value {
+            DynSolValue::Uint(value, _) => value.to_string(),
+            DynSolValue::Int(value, _) => value.to_string(),
+            _ => return Err(self.invalid(field)),
+        };
+
+        BigDecimal::from_str(&value).map_err(|_| self.invalid(field))
+    }
+
+    fn field(&self, field: &str) -> Result<&DynSolValue, AppError> {
+        self.fields
+            .get(field)
+            .ok_or_else(|| AppError::ParseError(format!("{} without `{field}`", self.name)))
+    }
+
+    fn invalid(&self, field: &str) -> AppError {
+        AppError::ParseError(format!("Invalid `{field}` in {}", self.name))
+    }
+}
@@ -0,0 +1,201 @@
+use alloy::rpc::types::Log;
+
+use crate::{
+    infrastructure::{
+        abi::abi_loader::AbiLoader,
+        contracts::{ContractHandler, alloy_contract_handler::AlloyContractHandler},
+    },
+    services::{
+        dtos::index_engine::{Erc1155TransferRequest, Erc1155UriRequest, IndexedEvent},
+        usecase::errors::AppError,
+    },
+};
+
+/// Any ERC-1155 collection. Its single and batch transfers keep the balance of each
+/// holder per token id, and its URI events the metadata URI of each id.
+pub struct Erc1155 {
+    pub contract: AlloyContractHandler,
+}
+
+impl Erc1155 {
+    pub fn new(chain_id: u64, address: &str, loader: AbiLoader) -> Result<Self, AppError> {
+        Ok(Self {
+            contract: AlloyContractHandler::new(chain_id, address, loader, Self::NAME)?,
+        })
+    }
+}
+
+impl ContractHandler for Erc1155 {
+    const NAME: &str = "erc1155";
+
+    fn event_signature_to_name(&self, signature: [u8; 32]) -> Result<String, AppError> {
+        self.contract.event_signature_to_name(signature)
+    }
+
+    async fn handle_event(
+        &self,
+        event_name: &str,
+        log: &Log,
+    ) -> Result<Vec<IndexedEvent>, AppError> {
+        match event_name {
+            "TransferSingle" => {
+                let event = self.contract.decode(event_name, log)?;
+
+                Ok(vec![IndexedEvent::Erc1155Transfer(
+                    Erc1155TransferRequest {
+                        source: self.contract.source(log)?,
+                        batch_index: 0,
+                        operator: event.address("operator")?,
+                        from: event.address("from")?,
+                        to: event.address("to")?,
+                        id: event.decimal("id")?,
+                        value: event.decimal("value")?,
+                    },
+                )])
+            }
+            "TransferBatch" => {
+                let event = self.contract.decode(event_name, log)?;
+                let ids = event.decimals("ids")?;
+                let values = event.decimals("values")?;
+                if ids.len() != values.len() {
+                    return Err(AppError::ParseError(format!(
+                        "{event_name} with {} ids and {} values",
+                        ids.len(),
+                        values.len()
+                    )));
+                }
+
+                let source = self.contract.source(log)?;
+                let operator = event.address("operator")?;
+                let from = event.address("from")?;
+                let to = event.address("to")?;
+
+                Ok(ids
+                    .into_iter()
+                    .zip(values)
+                    .enumerate()
+                    .map(|(batch_index, (id, value))| {
+                        IndexedEvent::Erc1155Transfer(Erc1155TransferRequest {
+                            source: source.clone(),
+                            batch_index: batch_index as u32,
+                            operator,
+                            from,
+                            to,
+                            id,
+                            value,
+                        })
+                    })
+                    .collect())
+            }
+            "URI" => {
+                let event = self.contract.decode(event_name, log)?;
+
+                Ok(vec![IndexedEvent::Erc1155Uri(Erc1155UriRequest {
+                    source: self.contract.source(log)?,
+                    id: event.decimal("id")?,
+                    uri: event.string("value")?,
+                })])
+            }
+            // Approvals do not change balances
+            "ApprovalForAll" => Ok(vec![]),
+            unsupported => Err(AppError::MissingEventHandler(
+                Self::NAME.into(),
+                unsupported.into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Address, B256, U256, keccak256},
+        sol_types::SolValue,
+    };
+    use sqlx::types::BigDecimal;
+
+    use crate::services::entities::evm_logs::EVMLogs;
+
+    use super::*;
+    use crate::infrastructure::contracts::alloy_contract_handler::fixtures::{
+        CHAIN_ID, artifacts, log,
+    };
+
+    #[tokio::test]
+    async fn batch_transfer_is_one_transfer_per_id() {
+        let collection = Erc1155::new(
+            CHAIN_ID,
+            "0xe3e3e3e3e3e3e3e3e3e3e3e3e3e3e3e3e3e3e3e3",
+            artifacts(),
+        )
+        .unwrap();
+
+        let log = log(
+            collection.contract.address,
+            vec![
+                collection.contract.abi.events["TransferBatch"][0].selector(),
+                Address::repeat_byte(0x01).into_word(),
+                Address::ZERO.into_word(),
+                Address::repeat_byte(0x02).into_word(),
+            ],
+            (
+                vec![U256::from(7u64), U256::from(9u64)],
+                vec![U256::from(100u64), U256::from(1u64)],
+            )
+                .abi_encode_params(),
+        );
+
+        let events = collection
+            .handle_event("TransferBatch", &log)
+            .await
+            .unwrap();
+
+        let [
+            IndexedEvent::Erc1155Transfer(first),
+            IndexedEvent::Erc1155Transfer(second),
+        ] = events.as_slice()
+        else {
+            panic!("expected two transfers, got {events:?}");
+        };
+        assert_eq!(first.batch_index, 0);
+        assert_eq!(first.id, BigDecimal::from(7));
+        assert_eq!(first.value, BigDecimal::from(100));
+        assert_eq!(second.batch_index, 1);
+        assert_eq!(second.id, BigDecimal::from(9));
+        assert_eq!(second.value, BigDecimal::from(1));
+        assert_eq!(second.operator, [0x01; 20]);
+        assert_eq!(second.from, [0; 20]);
+        assert_eq!(second.to, [0x02; 20]);
+        assert_eq!(second.source.log_index, 3);
+    }
+
+    #[tokio::test]
+    async fn skips_events_missing_from_the_abi() {
+        let collection = Erc1155::new(
+            CHAIN_ID,
+            "0xe3e3e3e3e3e3e3e3e3e3e3e3e3e3e3e3e3e3e3e3",
+            artifacts(),
+        )
+        .unwrap();
+        let ownership_transferred = keccak256("OwnershipTransferred(address,address)");
+        let log = alloy::rpc::types::Log {
+            block_hash: Some(B256::repeat_byte(0xbb)),
+            ..log(
+                collection.contract.address,
+                vec![
+                    ownership_transferred,
+                    Address::ZERO.into_word(),
+                    Address::repeat_byte(0x01).into_word(),
+                ],
+                Vec::new(),
+            )
+        };
+
+        let events = collection
+            .process(EVMLogs::try_from(log).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(events, vec![]);
+    }
+}
@@ -0,0 +1,170 @@
+use alloy::rpc::types::Log;
+
+use crate::{
+    infrastructure::{
+        abi::abi_loader::AbiLoader,
+        contracts::{ContractHandler, alloy_contract_handler::AlloyContractHandler},
+    },
+    services::{
+        dtos::index_engine::{Erc20ApprovalRequest, Erc20TransferRequest, IndexedEvent},
+        usecase::errors::AppError,
+    },
+};
+
+/// Any ERC-20 token. Its transfers keep holder balances and the total supply up to
+/// date, minting from and burning to the zero address.
+pub struct Erc20 {
+    pub contract: AlloyContractHandler,
+}
+
+impl Erc20 {
+    pub fn new(chain_id: u64, address: &str, loader: AbiLoader) -> Result<Self, AppError> {
+        Ok(Self {
+            contract: AlloyContractHandler::new(chain_id, address, loader, Self::NAME)?,
+        })
+    }
+}
+
+impl ContractHandler for Erc20 {
+    const NAME: &str = "erc20";
+
+    fn event_signature_to_name(&self, signature: [u8; 32]) -> Result<String, AppError> {
+        self.contract.event_signature_to_name(signature)
+    }
+
+    async fn handle_event(
+        &self,
+        event_name: &str,
+        log: &Log,
+    ) -> Result<Vec<IndexedEvent>, AppError> {
+        match event_name {
+            "Transfer" => {
+                let event = self.contract.decode(event_name, log)?;
+
+                Ok(vec![IndexedEvent::Erc20Transfer(Erc20TransferRequest {
+                    source: self.contract.source(log)?,
+                    from: event.address("from")?,
+                    to: event.address("to")?,
+                    value: event.decimal("value")?,
+                })])
+            }
+            "Approval" => {
+                let event = self.contract.decode(event_name, log)?;
+
+                Ok(vec![IndexedEvent::Erc20Approval(Erc20ApprovalRequest {
+                    source: self.contract.source(log)?,
+                    owner: event.address("owner")?,
+                    spender: event.address("spender")?,
+                    value: event.decimal("value")?,
+                })])
+            }
+            unsupported => Err(AppError::MissingEventHandler(
+                Self::NAME.into(),
+                unsupported.into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Address, U256, keccak256},
+        sol_types::SolValue,
+    };
+    use sqlx::types::{BigDecimal, chrono};
+
+    use crate::services::entities::evm_logs::EVMLogs;
+
+    use super::*;
+    use crate::infrastructure::contracts::alloy_contract_handler::fixtures::{
+        CHAIN_ID, artifacts, log, source,
+    };
+
+    fn token() -> Erc20 {
+        Erc20::new(
+            CHAIN_ID,
+            "0xe2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e2e2",
+            artifacts(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn transfer_from_the_zero_address_is_a_mint() {
+        let token = token();
+        let address = token.contract.address;
+        let log = log(
+            address,
+            vec![
+                token.contract.abi.events["Transfer"][0].selector(),
+                Address::ZERO.into_word(),
+                Address::repeat_byte(0x02).into_word(),
+            ],
+            U256::from(1_000_000u64).abi_encode(),
+        );
+
+        let events = token.handle_event("Transfer", &log).await.unwrap();
+
+        assert_eq!(
+            events,
+            vec![IndexedEvent::Erc20Transfer(Erc20TransferRequest {
+                source: source(address),
+                from: [0; 20],
+                to: [0x02; 20],
+                value: BigDecimal::from(1_000_000),
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn approval_records_the_allowance() {
+        let token = token();
+        let address = token.contract.address;
+        let log = log(
+            address,
+            vec![
+                token.contract.abi.events["Approval"][0].selector(),
+                Address::repeat_byte(0x01).into_word(),
+                Address::repeat_byte(0x02).into_word(),
+            ],
+            U256::MAX.abi_encode(),
+        );
+
+        let events = token.handle_event("Approval", &log).await.unwrap();
+
+        assert_eq!(
+            events,
+            vec![IndexedEvent::Erc20Approval(Erc20ApprovalRequest {
+                source: source(address),
+                owner: [0x01; 20],
+                spender: [0x02; 20],
+                value: U256::MAX.to_string().parse().unwrap(),
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_events_missing_from_the_abi() {
+        let token = token();
+        let log = EVMLogs {
+            id: 1,
+            chain_id: CHAIN_ID as i64,
+            block_number: BigDecimal::from(42),
+            block_hash: [0xbb; 32],
+            address: token.contract.address.0.0,
+            transaction_hash: [0xaa; 32],
+            data: Address::repeat_byte(0x01).abi_encode(),
+            event_signature: keccak256("Paused(address)").0,
+            topics: vec![keccak256("Paused(address)").0],
+            transaction_index: 0,
+            log_index: 3,
+            removed: false,
+            unconfirmed: false,
+            block_timestamp: None,
+            created_at: chrono::NaiveDateTime::default(),
+        };
+
+        assert_eq!(token.process(log).await.unwrap(), vec![]);
+    }
+}
@@ -0,0 +1,135 @@
+use alloy::rpc::types::Log;
+
+use crate::{
+    infrastructure::{
+        abi::abi_loader::AbiLoader,
+        contracts::{ContractHandler, alloy_contract_handler::AlloyContractHandler},
+    },
+    services::{
+        dtos::index_engine::{Erc721TransferRequest, IndexedEvent},
+        usecase::errors::AppError,
+    },
+};
+
+/// Any ERC-721 collection. Its transfers keep the current owner of each token, the
+/// zero address owning burned ones.
+pub struct Erc721 {
+    pub contract: AlloyContractHandler,
+}
+
+impl Erc721 {
+    pub fn new(chain_id: u64, address: &str, loader: AbiLoader) -> Result<Self, AppError> {
+        Ok(Self {
+            contract: AlloyContractHandler::new(chain_id, address, loader, Self::NAME)?,
+        })
+    }
+}
+
+impl ContractHandler for Erc721 {
+    const NAME: &str = "erc721";
+
+    fn event_signature_to_name(&self, signature: [u8; 32]) -> Result<String, AppError> {
+        self.contract.event_signature_to_name(signature)
+    }
+
+    async fn handle_event(
+        &self,
+        event_name: &str,
+        log: &Log,
+    ) -> Result<Vec<IndexedEvent>, AppError> {
+        match event_name {
+            "Transfer" => {
+                let event = self.contract.decode(event_name, log)?;
+
+                Ok(vec![IndexedEvent::Erc721Transfer(Erc721TransferRequest {
+                    source: self.contract.source(log)?,
+                    from: event.address("from")?,
+                    to: event.address("to")?,
+                    token_id: event.decimal("tokenId")?,
+                })])
+            }
+            // Approvals do not change ownership
+            "Approval" | "ApprovalForAll" => Ok(vec![]),
+            unsupported => Err(AppError::MissingEventHandler(
+                Self::NAME.into(),
+                unsupported.into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Address, B256, U256, keccak256};
+    use sqlx::types::BigDecimal;
+
+    use crate::services::entities::evm_logs::EVMLogs;
+
+    use super::*;
+    use crate::infrastructure::contracts::alloy_contract_handler::fixtures::{
+        CHAIN_ID, artifacts, log, source,
+    };
+
+    #[tokio::test]
+    async fn transfer_reads_the_indexed_token_id() {
+        let collection = Erc721::new(
+            CHAIN_ID,
+            "0xe7e7e7e7e7e7e7e7e7e7e7e7e7e7e7e7e7e7e7e7",
+            artifacts(),
+        )
+        .unwrap();
+        let address = collection.contract.address;
+        let log = log(
+            address,
+            vec![
+                collection.contract.abi.events["Transfer"][0].selector(),
+                Address::repeat_byte(0x01).into_word(),
+                Address::ZERO.into_word(),
+                B256::from(U256::from(7u64)),
+            ],
+            Vec::new(),
+        );
+
+        let events = collection.handle_event("Transfer", &log).await.unwrap();
+
+        assert_eq!(
+            events,
+            vec![IndexedEvent::Erc721Transfer(Erc721TransferRequest {
+                source: source(address),
+                from: [0x01; 20],
+                to: [0; 20],
+                token_id: BigDecimal::from(7),
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn skips_events_missing_from_the_abi() {
+        let collection = Erc721::new(
+            CHAIN_ID,
+            "0xe7e7e7e7e7e7e7e7e7e7e7e7e7e7e7e7e7e7e7e7",
+            artifacts(),
+        )
+        .unwrap();
+        let ownership_transferred = keccak256("OwnershipTransferred(address,address)");
+        let log = alloy::rpc::types::Log {
+            block_hash: Some(B256::repeat_byte(0xbb)),
+            ..log(
+                collection.contract.address,
+                vec![
+                    ownership_transferred,
+                    Address::ZERO.into_word(),
+                    Address::repeat_byte(0x01).into_word(),
+                ],
+                Vec::new(),
+            )
+        };
+
+        let events = collection
+            .process(EVMLogs::try_from(log).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(events, vec![]);
+    }
+}
@@ -1,21 +1,45 @@
+pub mod alloy_contract_handler;
 pub mod contract_registry;
+pub mod decoded_event;
+pub mod erc1155;
+pub mod erc20;
+pub mod erc721;
 pub mod uniswap;
+pub mod uniswap_v2;
 use alloy::rpc::types::Log;
 
-use crate::services::{entities::evm_logs::EVMLogs, usecase::errors::AppError};
+use crate::services::{
+    dtos::index_engine::IndexedEvent, entities::evm_logs::EVMLogs, usecase::errors::AppError,
+};
 pub trait ContractHandler: Send + Sync {
     const NAME: &str;
 
     fn event_signature_to_name(&self, signature: [u8; 32]) -> Result<String, AppError>;
 
+    /// Handles a decoded event; `log.block_timestamp` is the unix time of its block.
+    /// Returns what the event changed, for the index engine to store, including the
+    /// child contracts factories deployed, to be indexed next.
     fn handle_event(
         &self,
         event_name: &str,
         log: &Log,
-    ) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    ) -> impl std::future::Future<Output = Result<Vec<IndexedEvent>, AppError>> + Send;
 
+    /// Processes a stored log, skipping events missing from the ABI of the handler.
     fn process(
         &self,
         unprocessed_log: EVMLogs,
-    ) -> impl std::future::Future<Output = Result<(), AppError>> + Send;
+    ) -> impl std::future::Future<Output = Result<Vec<IndexedEvent>, AppError>> + Send {
+        async move {
+            // Contracts emit events beyond their standard, such as Paused or
+            // OwnershipTransferred, which change nothing indexed
+            let event_name = match self.event_signature_to_name(unprocessed_log.event_signature) {
+                Ok(event_name) => event_name,
+                Err(AppError::MissingEvent(_, _)) => return Ok(vec![]),
+                Err(err) => return Err(err),
+            };
+            let log: Log = unprocessed_log.try_into()?;
+            self.handle_event(&event_name, &log).await
+        }
+    }
 }
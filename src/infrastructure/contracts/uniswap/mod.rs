@@ -1,85 +1,211 @@
-use alloy::{
-    hex,
-    json_abi::JsonAbi,
-    primitives::{Address, keccak256},
-    rpc::types::Log,
-};
-use async_trait::async_trait;
+use alloy::rpc::types::Log;
 
 use crate::{
-    infrastructure::{abi::abi_loader::AbiLoader, contracts::ContractHandler},
-    services::{entities::evm_logs::EVMLogs, usecase::errors::AppError},
+    infrastructure::{
+        abi::abi_loader::AbiLoader,
+        contracts::{ContractHandler, alloy_contract_handler::AlloyContractHandler},
+    },
+    services::{
+        dtos::index_engine::{
+            DiscoveredContract, FeeAmountEnabledRequest, IndexedEvent, OwnerChangedRequest,
+            PoolCreatedRequest,
+        },
+        usecase::errors::AppError,
+    },
 };
 
+pub mod pool;
+
 pub struct UniswapV3Factory {
-    #[allow(dead_code)]
-    pub address: Address,
-    pub abi: JsonAbi,
+    pub contract: AlloyContractHandler,
 }
 
 impl UniswapV3Factory {
-    pub fn new(address: &str, loader: AbiLoader) -> Result<Self, AppError> {
-        let addr = address
-            .parse::<Address>()
-            .map_err(|_| AppError::InvalidAddress(address.into()))?;
-
-        let abi = loader.load(Self::NAME)?;
-
-        Ok(Self { address: addr, abi })
+    pub fn new(chain_id: u64, address: &str, loader: AbiLoader) -> Result<Self, AppError> {
+        Ok(Self {
+            contract: AlloyContractHandler::new(chain_id, address, loader, Self::NAME)?,
+        })
     }
 }
 
-#[async_trait]
 impl ContractHandler for UniswapV3Factory {
     const NAME: &str = "uniswap_v3_factory";
 
     fn event_signature_to_name(&self, signature: [u8; 32]) -> Result<String, AppError> {
-        let log_sig_hex = format!("0x{}", hex::encode(signature));
-
-        let event = self.abi.events.iter().find(|(_name, params)| {
-            let selector = keccak256(params[0].signature()).to_string();
-
-            selector == log_sig_hex
-        });
-
-        if let Some((name, _)) = event {
-            Ok(name.clone())
-        } else {
-            Err(AppError::MissingEvent(Self::NAME.into(), log_sig_hex))
-        }
+        self.contract.event_signature_to_name(signature)
     }
 
-    fn handle_event(
+    async fn handle_event(
         &self,
         event_name: &str,
-        _: &Log,
-    ) -> impl std::future::Future<Output = Result<(), AppError>> + Send {
-        async move {
-            match event_name {
-                "PoolCreated" => {
-                    println!("PoolCreated event");
-                    Ok(())
-                }
-                unsupported => Err(AppError::MissingEventHandler(
-                    Self::NAME.into(),
-                    unsupported.into(),
-                )),
+        log: &Log,
+    ) -> Result<Vec<IndexedEvent>, AppError> {
+        match event_name {
+            "PoolCreated" => {
+                let event = self.contract.decode(event_name, log)?;
+                let source = self.contract.source(log)?;
+                let pool = event.address("pool")?;
+
+                // The pool is indexed from the block that deployed it
+                let discovered = DiscoveredContract {
+                    address: pool,
+                    contract_name: pool::UniswapV3Pool::NAME.into(),
+                    deployment: None,
+                    start_block: source.block_number,
+                };
+
+                Ok(vec![
+                    IndexedEvent::PoolCreated(PoolCreatedRequest {
+                        source,
+                        pool,
+                        token0: event.address("token0")?,
+                        token1: event.address("token1")?,
+                        fee: event.uint_as("fee")?,
+                        tick_spacing: event.int_as("tickSpacing")?,
+                    }),
+                    IndexedEvent::ContractDiscovered(discovered),
+                ])
+            }
+            "OwnerChanged" => {
+                let event = self.contract.decode(event_name, log)?;
+
+                Ok(vec![IndexedEvent::OwnerChanged(OwnerChangedRequest {
+                    source: self.contract.source(log)?,
+                    previous: event.address("oldOwner")?,
+                    new_owner: event.address("newOwner")?,
+                })])
             }
+            "FeeAmountEnabled" => {
+                let event = self.contract.decode(event_name, log)?;
+
+                Ok(vec![IndexedEvent::FeeAmountEnabled(
+                    FeeAmountEnabledRequest {
+                        source: self.contract.source(log)?,
+                        fee: event.uint_as("fee")?,
+                        tick_spacing: event.int_as("tickSpacing")?,
+                    },
+                )])
+            }
+            unsupported => Err(AppError::MissingEventHandler(
+                Self::NAME.into(),
+                unsupported.into(),
+            )),
         }
     }
+}
 
-    fn process(
-        &self,
-        unprocessed_log: EVMLogs,
-    ) -> impl std::future::Future<Output = Result<(), AppError>> + Send
-    where
-        Self: Sync,
-    {
-        async move {
-            let event_name = self.event_signature_to_name(unprocessed_log.event_signature)?;
-            let log: Log = unprocessed_log.try_into()?;
-            self.handle_event(&event_name, &log).await?;
-            Ok(())
-        }
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Address, B256},
+        sol_types::SolValue,
+    };
+
+    use super::*;
+    use crate::infrastructure::contracts::alloy_contract_handler::fixtures::{
+        CHAIN_ID, artifacts, log, source,
+    };
+
+    fn factory() -> UniswapV3Factory {
+        UniswapV3Factory::new(
+            CHAIN_ID,
+            "0x4752ba5dbc23f44d87826276bf6fd6b1c372ad24",
+            artifacts(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn pool_created_records_and_discovers_the_pool() {
+        let factory = factory();
+        let address = factory.contract.address;
+        let pool = Address::repeat_byte(0xef);
+        let log = log(
+            address,
+            vec![
+                factory.contract.abi.events["PoolCreated"][0].selector(),
+                Address::repeat_byte(0x01).into_word(),
+                Address::repeat_byte(0x02).into_word(),
+                B256::left_padding_from(&3000u32.to_be_bytes()),
+            ],
+            (60i32, pool).abi_encode(),
+        );
+
+        let events = factory.handle_event("PoolCreated", &log).await.unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                IndexedEvent::PoolCreated(PoolCreatedRequest {
+                    source: source(address),
+                    pool: pool.0.0,
+                    token0: [0x01; 20],
+                    token1: [0x02; 20],
+                    fee: 3000,
+                    tick_spacing: 60,
+                }),
+                IndexedEvent::ContractDiscovered(DiscoveredContract {
+                    address: pool.into(),
+                    contract_name: "uniswap_v3_pool".into(),
+                    deployment: None,
+                    start_block: 42,
+                }),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn owner_changed_records_both_owners() {
+        let factory = factory();
+        let address = factory.contract.address;
+        let log = log(
+            address,
+            vec![
+                factory.contract.abi.events["OwnerChanged"][0].selector(),
+                Address::repeat_byte(0x01).into_word(),
+                Address::repeat_byte(0x02).into_word(),
+            ],
+            Vec::new(),
+        );
+
+        let events = factory.handle_event("OwnerChanged", &log).await.unwrap();
+
+        assert_eq!(
+            events,
+            vec![IndexedEvent::OwnerChanged(OwnerChangedRequest {
+                source: source(address),
+                previous: [0x01; 20],
+                new_owner: [0x02; 20],
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn fee_amount_enabled_records_the_fee_tier() {
+        let factory = factory();
+        let address = factory.contract.address;
+        let log = log(
+            address,
+            vec![
+                factory.contract.abi.events["FeeAmountEnabled"][0].selector(),
+                B256::left_padding_from(&100u32.to_be_bytes()),
+                B256::left_padding_from(&1i32.to_be_bytes()),
+            ],
+            Vec::new(),
+        );
+
+        let events = factory
+            .handle_event("FeeAmountEnabled", &log)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            events,
+            vec![IndexedEvent::FeeAmountEnabled(FeeAmountEnabledRequest {
+                source: source(address),
+                fee: 100,
+                tick_spacing: 1,
+            })]
+        );
     }
 }
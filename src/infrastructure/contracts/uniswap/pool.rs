@@ -0,0 +1,175 @@
+use alloy::rpc::types::Log;
+
+use crate::{
+    infrastructure::{
+        abi::abi_loader::AbiLoader,
+        contracts::{ContractHandler, alloy_contract_handler::AlloyContractHandler},
+    },
+    services::{
+        dtos::index_engine::{
+            FlashRequest, IndexedEvent, LiquidityRequest, PoolInitializedRequest, SwapRequest,
+        },
+        usecase::errors::AppError,
+    },
+};
+
+/// Pool deployed by a `UniswapV3Factory`, tracked from its `PoolCreated` event. Its
+/// events keep the pool's price, liquidity, volumes and fees up to date.
+pub struct UniswapV3Pool {
+    pub contract: AlloyContractHandler,
+}
+
+impl UniswapV3Pool {
+    pub fn new(chain_id: u64, address: &str, loader: AbiLoader) -> Result<Self, AppError> {
+        Ok(Self {
+            contract: AlloyContractHandler::new(chain_id, address, loader, Self::NAME)?,
+        })
+    }
+}
+
+impl ContractHandler for UniswapV3Pool {
+    const NAME: &str = "uniswap_v3_pool";
+
+    fn event_signature_to_name(&self, signature: [u8; 32]) -> Result<String, AppError> {
+        self.contract.event_signature_to_name(signature)
+    }
+
+    async fn handle_event(
+        &self,
+        event_name: &str,
+        log: &Log,
+    ) -> Result<Vec<IndexedEvent>, AppError> {
+        let decode = || self.contract.decode(event_name, log);
+        let source = || self.contract.source(log);
+
+        match event_name {
+            "Initialize" => {
+                let event = decode()?;
+
+                Ok(vec![IndexedEvent::PoolInitialized(
+                    PoolInitializedRequest {
+                        source: source()?,
+                        sqrt_price_x96: event.decimal("sqrtPriceX96")?,
+                        tick: event.int_as("tick")?,
+                    },
+                )])
+            }
+            "Swap" => {
+                let event = decode()?;
+
+                Ok(vec![IndexedEvent::Swap(SwapRequest {
+                    source: source()?,
+                    sender: event.address("sender")?,
+                    recipient: event.address("recipient")?,
+                    amount0: event.decimal("amount0")?,
+                    amount1: event.decimal("amount1")?,
+                    sqrt_price_x96: event.decimal("sqrtPriceX96")?,
+                    liquidity: event.decimal("liquidity")?,
+                    tick: event.int_as("tick")?,
+                })])
+            }
+            "Mint" | "Burn" => {
+                let event = decode()?;
+                let liquidity = LiquidityRequest {
+                    source: source()?,
+                    owner: event.address("owner")?,
+                    tick_lower: event.int_as("tickLower")?,
+                    tick_upper: event.int_as("tickUpper")?,
+                    amount: event.decimal("amount")?,
+                    amount0: event.decimal("amount0")?,
+                    amount1: event.decimal("amount1")?,
+                };
+
+                Ok(vec![if event_name == "Mint" {
+                    IndexedEvent::Mint(liquidity)
+                } else {
+                    IndexedEvent::Burn(liquidity)
+                }])
+            }
+            "Flash" => {
+                let event = decode()?;
+
+                Ok(vec![IndexedEvent::Flash(FlashRequest {
+                    source: source()?,
+                    sender: event.address("sender")?,
+                    recipient: event.address("recipient")?,
+                    amount0: event.decimal("amount0")?,
+                    amount1: event.decimal("amount1")?,
+                    paid0: event.decimal("paid0")?,
+                    paid1: event.decimal("paid1")?,
+                })])
+            }
+            // Withdrawing owed tokens leaves the price and liquidity of the pool as they are
+            "Collect" => {
+                decode()?;
+                Ok(Vec::new())
+            }
+            "IncreaseObservationCardinalityNext" | "SetFeeProtocol" | "CollectProtocol" => {
+                Ok(Vec::new())
+            }
+            unsupported => Err(AppError::MissingEventHandler(
+                Self::NAME.into(),
+                unsupported.into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use alloy::{
+        primitives::{Address, I256, U256, aliases::I24},
+        sol_types::SolValue,
+    };
+    use sqlx::types::BigDecimal;
+
+    use super::*;
+    use crate::infrastructure::contracts::alloy_contract_handler::fixtures::{
+        CHAIN_ID, artifacts, log,
+    };
+
+    #[tokio::test]
+    async fn swap_keeps_the_sign_of_its_amounts() {
+        let pool = UniswapV3Pool::new(
+            CHAIN_ID,
+            "0xefefefefefefefefefefefefefefefefefefefef",
+            artifacts(),
+        )
+        .unwrap();
+
+        let log = log(
+            pool.contract.address,
+            vec![
+                pool.contract.abi.events["Swap"][0].selector(),
+                Address::repeat_byte(0x01).into_word(),
+                Address::repeat_byte(0x02).into_word(),
+            ],
+            (
+                I256::try_from(1_000_000).unwrap(),
+                I256::try_from(-990_000).unwrap(),
+                U256::from(1u128 << 96),
+                U256::from(5_000u64),
+                I24::try_from(-887_272).unwrap(),
+            )
+                .abi_encode_params(),
+        );
+
+        let events = pool.handle_event("Swap", &log).await.unwrap();
+
+        let [IndexedEvent::Swap(swap)] = events.as_slice() else {
+            panic!("expected a swap, got {events:?}");
+        };
+        assert_eq!(swap.sender, [0x01; 20]);
+        assert_eq!(swap.recipient, [0x02; 20]);
+        assert_eq!(swap.amount0, BigDecimal::from(1_000_000));
+        assert_eq!(swap.amount1, BigDecimal::from(-990_000));
+        assert_eq!(
+            swap.sqrt_price_x96,
+            BigDecimal::from_str("79228162514264337593543950336").unwrap()
+        );
+        assert_eq!(swap.liquidity, BigDecimal::from(5_000));
+        assert_eq!(swap.tick, -887_272);
+    }
+}
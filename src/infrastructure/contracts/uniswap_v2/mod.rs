@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+
+use alloy::{
+    json_abi::{Event, JsonAbi},
+    rpc::types::Log,
+};
+
+use crate::{
+    infrastructure::{
+        abi::abi_loader::AbiLoader,
+        contracts::{
+            ContractHandler, alloy_contract_handler::AlloyContractHandler,
+            decoded_event::DecodedEvent,
+        },
+    },
+    services::{
+        dtos::index_engine::{DiscoveredContract, IndexedEvent, PairCreatedRequest},
+        usecase::errors::AppError,
+    },
+};
+
+pub mod pair;
+
+/// Type of an event parameter.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Param {
+    Address,
+    /// Reserves and amounts, which forks may widen but not narrow below the 112 bits
+    /// reserves are stored in, unlike fees and other settings
+    Uint,
+}
+
+impl Param {
+    fn matches(self, ty: &str) -> bool {
+        match self {
+            Param::Address => ty == "address",
+            Param::Uint => ty
+                .strip_prefix("uint")
+                .and_then(|bits| match bits {
+                    "" => Some(256),
+                    bits => bits.parse::<u16>().ok(),
+                })
+                .is_some_and(|bits| bits >= 112),
+        }
+    }
+}
+
+/// Finds the event `event` of `abi`, with the parameters of `shape`, each an indexed or
+/// body `Param`. Forks rename events and parameters but keep their shape, so the
+/// handlers read the fields of the event found by their position in it. The event is
+/// the one `names` maps `event` to, or the one named `event`, or else the only one of
+/// its shape.
+fn find_event(
+    abi: &JsonAbi,
+    contract: &str,
+    names: &HashMap<String, String>,
+    event: &str,
+    shape: &[(Param, bool)],
+) -> Result<Event, AppError> {
+    let has_shape = |candidate: &&Event| {
+        !candidate.anonymous
+            && candidate.inputs.len() == shape.len()
+            && candidate
+                .inputs
+                .iter()
+                .zip(shape)
+                .all(|(input, (param, indexed))| {
+                    param.matches(&input.ty) && input.indexed == *indexed
+                })
+    };
+
+    let name = names.get(event).map_or(event, String::as_str);
+    if let Some(overloads) = abi.events.get(name) {
+        return overloads.iter().find(has_shape).cloned().ok_or_else(|| {
+            AppError::ConfigError(format!(
+                "Event `{name}` of `{contract}` does not have the parameters of {event}"
+            ))
+        });
+    }
+    if names.contains_key(event) {
+        return Err(AppError::MissingEvent(contract.into(), name.into()));
+    }
+
+    let mut candidates = abi.events.values().flatten().filter(has_shape);
+    match (candidates.next(), candidates.next()) {
+        (Some(found), None) => Ok(found.clone()),
+        (None, _) => Err(AppError::MissingEvent(contract.into(), event.into())),
+        (Some(first), Some(second)) => Err(AppError::ConfigError(format!(
+            "Events `{}` and `{}` of `{contract}` both have the parameters of {event}, \
+             name the one it is in `{contract}.events.json`",
+            first.name, second.name
+        ))),
+    }
+}
+
+/// Name of the parameter at `index` of `event`, to look its value up by.
+fn field(event: &Event, index: usize) -> &str {
+    &event.inputs[index].name
+}
+
+/// Uniswap V2 factory or one of its forks, whose ABI is that of its `deployment`.
+/// The pairs it creates are tracked with the same deployment.
+pub struct UniswapV2Factory {
+    pub contract: AlloyContractHandler,
+    pub deployment: Option<String>,
+    /// `PairCreated(token0, token1, pair, allPairsLength)`, whatever its name
+    pair_created: Event,
+}
+
+impl UniswapV2Factory {
+    pub fn new(
+        chain_id: u64,
+        address: &str,
+        loader: AbiLoader,
+        deployment: Option<&str>,
+    ) -> Result<Self, AppError> {
+        let abi_name = AbiLoader::abi_name(Self::NAME, deployment);
+        let names = loader.event_names(&abi_name)?;
+        let contract = AlloyContractHandler::new(chain_id, address, loader, &abi_name)?;
+        let pair_created = find_event(
+            &contract.abi,
+            &abi_name,
+            &names,
+            "PairCreated",
+            &[
+                (Param::Address, true),
+                (Param::Address, true),
+                (Param::Address, false),
+                (Param::Uint, false),
+            ],
+        )?;
+
+        Ok(Self {
+            contract,
+            deployment: deployment.map(String::from),
+            pair_created,
+        })
+    }
+}
+
+impl ContractHandler for UniswapV2Factory {
+    const NAME: &str = "uniswap_v2_factory";
+
+    fn event_signature_to_name(&self, signature: [u8; 32]) -> Result<String, AppError> {
+        self.contract.event_signature_to_name(signature)
+    }
+
+    async fn handle_event(
+        &self,
+        event_name: &str,
+        log: &Log,
+    ) -> Result<Vec<IndexedEvent>, AppError> {
+        match event_name {
+            name if name == self.pair_created.name => {
+                let event = DecodedEvent::decode(&self.pair_created, log)?;
+                let source = self.contract.source(log)?;
+                let pair = event.address(field(&self.pair_created, 2))?;
+
+                // The pair is indexed from the block that deployed it
+                let discovered = DiscoveredContract {
+                    address: pair,
+                    contract_name: pair::UniswapV2Pair::NAME.into(),
+                    deployment: self.deployment.clone(),
+                    start_block: source.block_number,
+                };
+
+                Ok(vec![
+                    IndexedEvent::PairCreated(PairCreatedRequest {
+                        source,
+                        pair,
+                        token0: event.address(field(&self.pair_created, 0))?,
+                        token1: event.address(field(&self.pair_created, 1))?,
+                    }),
+                    IndexedEvent::ContractDiscovered(discovered),
+                ])
+            }
+            unsupported => Err(AppError::MissingEventHandler(
+                Self::NAME.into(),
+                unsupported.into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use alloy::{
+        primitives::{Address, U256},
+        sol_types::SolValue,
+    };
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::infrastructure::contracts::alloy_contract_handler::fixtures::{
+        CHAIN_ID, log, source,
+    };
+
+    #[tokio::test]
+    async fn pair_created_tracks_the_pair_with_the_factory_deployment() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("forkswap")).unwrap();
+        fs::copy(
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/src/infrastructure/abi/artifacts/uniswap_v2_factory.json"
+            ),
+            dir.path().join("forkswap").join("uniswap_v2_factory.json"),
+        )
+        .unwrap();
+        let factory = UniswapV2Factory::new(
+            CHAIN_ID,
+            "0xe5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5e5",
+            AbiLoader::new(dir.path().to_string_lossy().to_string()),
+            Some("forkswap"),
+        )
+        .unwrap();
+        let address = factory.contract.address;
+        let log = log(
+            address,
+            vec![
+                factory.pair_created.selector(),
+                Address::repeat_byte(0x0a).into_word(),
+                Address::repeat_byte(0x0b).into_word(),
+            ],
+            (Address::repeat_byte(0x0c), U256::from(1u64)).abi_encode_params(),
+        );
+
+        let events = factory.handle_event("PairCreated", &log).await.unwrap();
+
+        assert_eq!(
+            events,
+            vec![
+                IndexedEvent::PairCreated(PairCreatedRequest {
+                    source: source(address),
+                    pair: [0x0c; 20],
+                    token0: [0x0a; 20],
+                    token1: [0x0b; 20],
+                }),
+                IndexedEvent::ContractDiscovered(DiscoveredContract {
+                    address: [0x0c; 20],
+                    contract_name: "uniswap_v2_pair".into(),
+                    deployment: Some("forkswap".into()),
+                    start_block: 42,
+                }),
+            ]
+        );
+    }
+}
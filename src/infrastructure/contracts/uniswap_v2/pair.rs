@@ -0,0 +1,376 @@
+use alloy::{json_abi::Event, rpc::types::Log};
+
+use crate::{
+    infrastructure::{
+        abi::abi_loader::AbiLoader,
+        contracts::{
+            ContractHandler,
+            alloy_contract_handler::AlloyContractHandler,
+            decoded_event::DecodedEvent,
+            uniswap_v2::{Param, field, find_event},
+        },
+    },
+    services::{
+        dtos::index_engine::{
+            IndexedEvent, PairLiquidityRequest, PairSwapRequest, PairSyncRequest,
+        },
+        usecase::errors::AppError,
+    },
+};
+
+/// Pair deployed by a `UniswapV2Factory`, tracked from its `PairCreated` event. Its
+/// Sync events keep the reserves of the pair up to date.
+pub struct UniswapV2Pair {
+    pub contract: AlloyContractHandler,
+    /// `Sync(reserve0, reserve1)`
+    sync: Event,
+    /// `Swap(sender, amount0In, amount1In, amount0Out, amount1Out, to)`
+    swap: Event,
+    /// `Mint(sender, amount0, amount1)`
+    mint: Event,
+    /// `Burn(sender, amount0, amount1, to)`
+    burn: Event,
+}
+
+impl UniswapV2Pair {
+    pub fn new(
+        chain_id: u64,
+        address: &str,
+        loader: AbiLoader,
+        deployment: Option<&str>,
+    ) -> Result<Self, AppError> {
+        let abi_name = AbiLoader::abi_name(Self::NAME, deployment);
+        let names = loader.event_names(&abi_name)?;
+        let contract = AlloyContractHandler::new(chain_id, address, loader, &abi_name)?;
+        let abi = &contract.abi;
+        let (address_topic, uint) = ((Param::Address, true), (Param::Uint, false));
+
+        Ok(Self {
+            sync: find_event(abi, &abi_name, &names, "Sync", &[uint, uint])?,
+            swap: find_event(
+                abi,
+                &abi_name,
+                &names,
+                "Swap",
+                &[address_topic, uint, uint, uint, uint, address_topic],
+            )?,
+            mint: find_event(abi, &abi_name, &names, "Mint", &[address_topic, uint, uint])?,
+            burn: find_event(
+                abi,
+                &abi_name,
+                &names,
+                "Burn",
+                &[address_topic, uint, uint, address_topic],
+            )?,
+            contract,
+        })
+    }
+
+    fn liquidity(
+        &self,
+        event: &Event,
+        log: &Log,
+        recipient: bool,
+    ) -> Result<PairLiquidityRequest, AppError> {
+        let decoded = DecodedEvent::decode(event, log)?;
+
+        Ok(PairLiquidityRequest {
+            source: self.contract.source(log)?,
+            sender: decoded.address(field(event, 0))?,
+            recipient: if recipient {
+                Some(decoded.address(field(event, 3))?)
+            } else {
+                None
+            },
+            amount0: decoded.decimal(field(event, 1))?,
+            amount1: decoded.decimal(field(event, 2))?,
+        })
+    }
+}
+
+impl ContractHandler for UniswapV2Pair {
+    const NAME: &str = "uniswap_v2_pair";
+
+    fn event_signature_to_name(&self, signature: [u8; 32]) -> Result<String, AppError> {
+        self.contract.event_signature_to_name(signature)
+    }
+
+    async fn handle_event(
+        &self,
+        event_name: &str,
+        log: &Log,
+    ) -> Result<Vec<IndexedEvent>, AppError> {
+        match event_name {
+            name if name == self.sync.name => {
+                let event = DecodedEvent::decode(&self.sync, log)?;
+
+                Ok(vec![IndexedEvent::PairSync(PairSyncRequest {
+                    source: self.contract.source(log)?,
+                    reserve0: event.decimal(field(&self.sync, 0))?,
+                    reserve1: event.decimal(field(&self.sync, 1))?,
+                })])
+            }
+            name if name == self.swap.name => {
+                let event = DecodedEvent::decode(&self.swap, log)?;
+
+                Ok(vec![IndexedEvent::PairSwap(PairSwapRequest {
+                    source: self.contract.source(log)?,
+                    sender: event.address(field(&self.swap, 0))?,
+                    amount0_in: event.decimal(field(&self.swap, 1))?,
+                    amount1_in: event.decimal(field(&self.swap, 2))?,
+                    amount0_out: event.decimal(field(&self.swap, 3))?,
+                    amount1_out: event.decimal(field(&self.swap, 4))?,
+                    recipient: event.address(field(&self.swap, 5))?,
+                })])
+            }
+            name if name == self.mint.name => Ok(vec![IndexedEvent::PairMint(
+                self.liquidity(&self.mint, log, false)?,
+            )]),
+            name if name == self.burn.name => Ok(vec![IndexedEvent::PairBurn(
+                self.liquidity(&self.burn, log, true)?,
+            )]),
+            // The LP token's Transfer and Approval do not move the reserves
+            name if self.contract.abi.events.contains_key(name) => Ok(vec![]),
+            unsupported => Err(AppError::MissingEventHandler(
+                Self::NAME.into(),
+                unsupported.into(),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use alloy::{
+        primitives::{Address, U256},
+        sol_types::SolValue,
+    };
+    use serde_json::json;
+    use sqlx::types::BigDecimal;
+    use tempfile::tempdir;
+
+    use super::*;
+    use crate::infrastructure::contracts::alloy_contract_handler::fixtures::{
+        CHAIN_ID, artifacts, log, source,
+    };
+
+    /// The pair events of a fork, renamed along with their parameters.
+    const FORK_ABI: &str = r#"
+       [
+           {
+               "type": "event",
+               "name": "ReservesUpdated",
+               "anonymous": false,
+               "inputs": [
+                   { "name": "reserveA", "type": "uint112", "indexed": false },
+                   { "name": "reserveB", "type": "uint112", "indexed": false }
+               ]
+           },
+           {
+               "type": "event",
+               "name": "Exchange",
+               "anonymous": false,
+               "inputs": [
+                   { "name": "caller", "type": "address", "indexed": true },
+                   { "name": "inA", "type": "uint256", "indexed": false },
+                   { "name": "inB", "type": "uint256", "indexed": false },
+                   { "name": "outA", "type": "uint256", "indexed": false },
+                   { "name": "outB", "type": "uint256", "indexed": false },
+                   { "name": "receiver", "type": "address", "indexed": true }
+               ]
+           },
+           {
+               "type": "event",
+               "name": "LiquidityAdded",
+               "anonymous": false,
+               "inputs": [
+                   { "name": "caller", "type": "address", "indexed": true },
+                   { "name": "amountA", "type": "uint256", "indexed": false },
+                   { "name": "amountB", "type": "uint256", "indexed": false }
+               ]
+           },
+           {
+               "type": "event",
+               "name": "LiquidityRemoved",
+               "anonymous": false,
+               "inputs": [
+                   { "name": "caller", "type": "address", "indexed": true },
+                   { "name": "amountA", "type": "uint256", "indexed": false },
+                   { "name": "amountB", "type": "uint256", "indexed": false },
+                   { "name": "receiver", "type": "address", "indexed": true }
+               ]
+           }
+       ]
+       "#;
+
+    /// Loader of the pair ABI of the `forkswap` deployment, `FORK_ABI` with `events`
+    /// added, and of its event `names` if any.
+    fn fork(
+        dir: &tempfile::TempDir,
+        events: &[serde_json::Value],
+        names: Option<&str>,
+    ) -> AbiLoader {
+        let mut abi: Vec<serde_json::Value> = serde_json::from_str(FORK_ABI).unwrap();
+        abi.extend_from_slice(events);
+        let deployment = dir.path().join("forkswap");
+        fs::create_dir_all(&deployment).unwrap();
+        fs::write(
+            deployment.join("uniswap_v2_pair.json"),
+            serde_json::to_string(&abi).unwrap(),
+        )
+        .unwrap();
+        if let Some(names) = names {
+            fs::write(deployment.join("uniswap_v2_pair.events.json"), names).unwrap();
+        }
+
+        AbiLoader::new(dir.path().to_string_lossy().to_string())
+    }
+
+    /// Event of two body parameters of type `ty`.
+    fn pair_of(name: &str, ty: &str) -> serde_json::Value {
+        json!({
+            "type": "event",
+            "name": name,
+            "anonymous": false,
+            "inputs": [
+                { "name": "a", "type": ty, "indexed": false },
+                { "name": "b", "type": ty, "indexed": false }
+            ]
+        })
+    }
+
+    fn forkswap_pair(loader: AbiLoader) -> Result<UniswapV2Pair, AppError> {
+        UniswapV2Pair::new(
+            CHAIN_ID,
+            "0xe9e9e9e9e9e9e9e9e9e9e9e9e9e9e9e9e9e9e9e9",
+            loader,
+            Some("forkswap"),
+        )
+    }
+
+    #[tokio::test]
+    async fn fork_with_renamed_events_keeps_reserves() {
+        let dir = tempdir().unwrap();
+        let pair = forkswap_pair(fork(&dir, &[], None)).unwrap();
+
+        let selector = pair.contract.abi.events["ReservesUpdated"][0].selector();
+        let log = log(
+            pair.contract.address,
+            vec![selector],
+            (U256::from(1_000u64), U256::from(2_500u64)).abi_encode_params(),
+        );
+
+        let event_name = pair.event_signature_to_name(selector.0).unwrap();
+        let events = pair.handle_event(&event_name, &log).await.unwrap();
+
+        let [IndexedEvent::PairSync(sync)] = events.as_slice() else {
+            panic!("expected a sync, got {events:?}");
+        };
+        assert_eq!(sync.reserve0, BigDecimal::from(1_000));
+        assert_eq!(sync.reserve1, BigDecimal::from(2_500));
+    }
+
+    #[test]
+    fn narrow_settings_are_not_taken_for_reserves() {
+        let dir = tempdir().unwrap();
+        let loader = fork(&dir, &[pair_of("FeePercentUpdated", "uint16")], None);
+
+        let pair = forkswap_pair(loader).unwrap();
+
+        assert_eq!(pair.sync.name, "ReservesUpdated");
+    }
+
+    #[test]
+    fn events_of_the_same_shape_are_told_apart_by_name() {
+        let dir = tempdir().unwrap();
+        let skim = pair_of("Skimmed", "uint256");
+
+        let ambiguous = forkswap_pair(fork(&dir, std::slice::from_ref(&skim), None));
+        assert!(matches!(ambiguous, Err(AppError::ConfigError(_))));
+
+        let names = r#"{ "Sync": "ReservesUpdated" }"#;
+        let pair = forkswap_pair(fork(&dir, &[skim], Some(names))).unwrap();
+        assert_eq!(pair.sync.name, "ReservesUpdated");
+
+        // The canonical name comes before the shape
+        let dir = tempdir().unwrap();
+        let pair = forkswap_pair(fork(&dir, &[pair_of("Sync", "uint112")], None)).unwrap();
+        assert_eq!(pair.sync.name, "Sync");
+    }
+
+    fn pair() -> UniswapV2Pair {
+        UniswapV2Pair::new(
+            CHAIN_ID,
+            "0xe8e8e8e8e8e8e8e8e8e8e8e8e8e8e8e8e8e8e8e8",
+            artifacts(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn swap_reads_amounts_in_and_out() {
+        let pair = pair();
+        let log = log(
+            pair.contract.address,
+            vec![
+                pair.swap.selector(),
+                Address::repeat_byte(0x01).into_word(),
+                Address::repeat_byte(0x02).into_word(),
+            ],
+            (1u64, 2u64, 3u64, 4u64).abi_encode_params(),
+        );
+
+        let events = pair.handle_event("Swap", &log).await.unwrap();
+
+        assert_eq!(
+            events,
+            vec![IndexedEvent::PairSwap(PairSwapRequest {
+                source: source(pair.contract.address),
+                sender: [0x01; 20],
+                amount0_in: BigDecimal::from(1),
+                amount1_in: BigDecimal::from(2),
+                amount0_out: BigDecimal::from(3),
+                amount1_out: BigDecimal::from(4),
+                recipient: [0x02; 20],
+            })]
+        );
+    }
+
+    #[tokio::test]
+    async fn mint_and_burn_read_amounts_and_the_burn_recipient() {
+        let pair = pair();
+        let mint = log(
+            pair.contract.address,
+            vec![pair.mint.selector(), Address::repeat_byte(0x01).into_word()],
+            (5u64, 6u64).abi_encode_params(),
+        );
+        let burn = log(
+            pair.contract.address,
+            vec![
+                pair.burn.selector(),
+                Address::repeat_byte(0x01).into_word(),
+                Address::repeat_byte(0x02).into_word(),
+            ],
+            (7u64, 8u64).abi_encode_params(),
+        );
+
+        let minted = pair.handle_event("Mint", &mint).await.unwrap();
+        let burned = pair.handle_event("Burn", &burn).await.unwrap();
+
+        let liquidity = |recipient, amount0: i64, amount1: i64| PairLiquidityRequest {
+            source: source(pair.contract.address),
+            sender: [0x01; 20],
+            recipient,
+            amount0: BigDecimal::from(amount0),
+            amount1: BigDecimal::from(amount1),
+        };
+        assert_eq!(minted, vec![IndexedEvent::PairMint(liquidity(None, 5, 6))]);
+        assert_eq!(
+            burned,
+            vec![IndexedEvent::PairBurn(liquidity(Some([0x02; 20]), 7, 8))]
+        );
+    }
+}
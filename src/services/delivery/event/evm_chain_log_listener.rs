@@ -0,0 +1,61 @@
+use std::{
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use tokio::sync::watch;
+use tower::Service;
+
+use crate::services::{
+    dtos::index_logs::TrackedContract,
+    entities::evm_chains::EvmChains,
+    usecase::{IndexLogUC, errors::AppError},
+};
+
+/// Listens on every tracked contract of a chain with one log filter per block range.
+pub struct EVMChainLogListener<U> {
+    /// Domain use case
+    pub usecase: Arc<U>,
+
+    /// Chain the contracts live on
+    pub chain: EvmChains,
+
+    /// Contracts to listen on, those tracked since joining the next filter
+    pub contracts: watch::Receiver<Vec<TrackedContract>>,
+}
+
+impl<U> EVMChainLogListener<U> {
+    pub fn new(
+        usecase: Arc<U>,
+        chain: EvmChains,
+        contracts: watch::Receiver<Vec<TrackedContract>>,
+    ) -> Self {
+        Self {
+            usecase,
+            chain,
+            contracts,
+        }
+    }
+}
+
+impl<U> Service<()> for EVMChainLogListener<U>
+where
+    U: IndexLogUC + Send + Sync + 'static,
+{
+    type Response = ();
+    type Error = AppError;
+    type Future = Pin<Box<dyn Future<Output = Result<(), AppError>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, _req: ()) -> Self::Future {
+        let usecase = self.usecase.clone();
+        let chain = self.chain.clone();
+        let contracts = self.contracts.borrow().clone();
+
+        Box::pin(async move { usecase.execute_many(&chain, &contracts).await })
+    }
+}
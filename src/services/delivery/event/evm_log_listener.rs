@@ -6,25 +6,29 @@ use std::{
 
 use tower::Service;
 
-use crate::services::usecase::{IndexLogUC, errors::AppError};
+use crate::services::{
+    dtos::index_logs::TrackedContract,
+    entities::evm_chains::EvmChains,
+    usecase::{IndexLogUC, errors::AppError},
+};
 
 pub struct EVMLogListener<U> {
     /// Domain use case
     pub usecase: Arc<U>,
 
-    /// Chain identifier
-    pub chain_id: u64,
+    /// Chain the contract lives on
+    pub chain: EvmChains,
 
-    /// Contract address to listen on
-    pub address: String,
+    /// Contract to listen on
+    pub contract: TrackedContract,
 }
 
 impl<U> EVMLogListener<U> {
-    pub fn new(usecase: Arc<U>, chain_id: u64, address: String) -> Self {
+    pub fn new(usecase: Arc<U>, chain: EvmChains, contract: TrackedContract) -> Self {
         Self {
             usecase,
-            chain_id,
-            address,
+            chain,
+            contract,
         }
     }
 }
@@ -43,9 +47,9 @@ where
 
     fn call(&mut self, _req: ()) -> Self::Future {
         let usecase = self.usecase.clone();
-        let chain_id = self.chain_id;
-        let address = self.address.clone();
+        let chain = self.chain.clone();
+        let contract = self.contract.clone();
 
-        Box::pin(async move { usecase.execute(chain_id, address).await })
+        Box::pin(async move { usecase.execute(&chain, &contract).await })
     }
 }
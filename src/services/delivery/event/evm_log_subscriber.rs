@@ -0,0 +1,212 @@
+use std::{sync::Arc, time::Duration};
+
+use tokio::{
+    sync::watch,
+    time::{Instant, sleep},
+};
+
+use crate::services::{
+    dtos::index_logs::TrackedContract, entities::evm_chains::EvmChains, usecase::IndexLogUC,
+};
+
+/// Streams the logs of every tracked contract of a chain from a log subscription,
+/// polling in its place while the subscription is down.
+pub struct EVMLogSubscriber<U> {
+    /// Domain use case
+    pub usecase: Arc<U>,
+
+    /// Chain the contracts live on
+    pub chain: EvmChains,
+
+    /// Contracts to listen on, a change ending the subscription to subscribe with them
+    pub contracts: watch::Receiver<Vec<TrackedContract>>,
+
+    /// How long to poll before subscribing again after a disconnect
+    pub retry_interval: Duration,
+}
+
+impl<U> EVMLogSubscriber<U>
+where
+    U: IndexLogUC + Send + Sync + 'static,
+{
+    pub fn new(
+        usecase: Arc<U>,
+        chain: EvmChains,
+        contracts: watch::Receiver<Vec<TrackedContract>>,
+        retry_interval: Duration,
+    ) -> Self {
+        Self {
+            usecase,
+            chain,
+            contracts,
+            retry_interval,
+        }
+    }
+
+    /// Subscribes forever, backfilling every gap from the cursors through polling,
+    /// including the history of contracts tracked since the last subscription.
+    pub async fn run(&self) {
+        let block_time = Duration::from_secs(self.chain.block_time as u64);
+        let mut contracts = self.contracts.clone();
+
+        loop {
+            let subscribed = contracts.borrow_and_update().clone();
+            tokio::select! {
+                result = self.usecase.stream(&self.chain, &subscribed) => {
+                    if let Err(err) = result {
+                        eprintln!("Log subscription failed: {:?}", err);
+                    }
+                }
+                Ok(()) = contracts.changed() => {}
+            }
+
+            let retry_at = Instant::now() + self.retry_interval;
+            loop {
+                let polled = contracts.borrow().clone();
+                if let Err(err) = self.usecase.execute_many(&self.chain, &polled).await {
+                    eprintln!("Failed to indexed: {:?}", err);
+                }
+
+                if Instant::now() >= retry_at {
+                    break;
+                }
+                sleep(block_time).await;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use sqlx::types::chrono::NaiveDateTime;
+
+    use super::*;
+    use crate::services::usecase::errors::AppError;
+
+    /// Use case whose subscription drops at once, recording every call along with
+    /// the number of contracts it was made for.
+    #[derive(Default)]
+    struct Calls(Mutex<Vec<(&'static str, usize)>>);
+
+    #[async_trait]
+    impl IndexLogUC for Calls {
+        async fn execute(&self, _: &EvmChains, _: &TrackedContract) -> Result<(), AppError> {
+            unimplemented!()
+        }
+
+        async fn execute_many(
+            &self,
+            _: &EvmChains,
+            contracts: &[TrackedContract],
+        ) -> Result<(), AppError> {
+            self.0.lock().unwrap().push(("poll", contracts.len()));
+            Ok(())
+        }
+
+        async fn stream(
+            &self,
+            _: &EvmChains,
+            contracts: &[TrackedContract],
+        ) -> Result<(), AppError> {
+            let subscriptions = {
+                let mut calls = self.0.lock().unwrap();
+                calls.push(("stream", contracts.len()));
+                calls.iter().filter(|(call, _)| *call == "stream").count()
+            };
+
+            // Subscriptions from the third on stay connected
+            if subscriptions >= 3 {
+                std::future::pending::<()>().await;
+            }
+            Err(AppError::SubscriptionClosed)
+        }
+
+        async fn ingest_range(
+            &self,
+            _: &EvmChains,
+            _: &TrackedContract,
+            _: u64,
+            _: u64,
+        ) -> Result<u64, AppError> {
+            unimplemented!()
+        }
+
+        async fn hand_over_cursor(
+            &self,
+            _: &EvmChains,
+            _: &TrackedContract,
+        ) -> Result<Option<(u64, u64)>, AppError> {
+            unimplemented!()
+        }
+    }
+
+    fn contract(address: &str) -> TrackedContract {
+        TrackedContract {
+            address: address.to_string(),
+            unconfirmed: false,
+            start_block: None,
+            event_signatures: Vec::new(),
+        }
+    }
+
+    fn chain() -> EvmChains {
+        EvmChains {
+            id: 1,
+            name: "testnet".into(),
+            last_synced_block_number: 0,
+            block_time: 1,
+            confirmation_depth: 0,
+            finality_tag: None,
+            enabled: true,
+            rpc_urls: Vec::new(),
+            ws_url: Some("ws://localhost:8546".into()),
+            created_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn polls_from_the_cursors_before_subscribing_again() {
+        let usecase = Arc::new(Calls::default());
+        let (_contracts, receiver) = watch::channel(vec![contract("11"), contract("22")]);
+        let subscriber =
+            EVMLogSubscriber::new(Arc::clone(&usecase), chain(), receiver, Duration::ZERO);
+
+        let running = tokio::time::timeout(Duration::from_millis(50), subscriber.run()).await;
+
+        assert!(running.is_err());
+        assert_eq!(
+            *usecase.0.lock().unwrap(),
+            vec![
+                ("stream", 2),
+                ("poll", 2),
+                ("stream", 2),
+                ("poll", 2),
+                ("stream", 2)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn subscribes_again_with_contracts_tracked_since() {
+        let usecase = Arc::new(Calls::default());
+        let (contracts, receiver) = watch::channel(vec![contract("11"), contract("22")]);
+        let subscriber =
+            EVMLogSubscriber::new(Arc::clone(&usecase), chain(), receiver, Duration::ZERO);
+
+        let running = tokio::time::timeout(Duration::from_millis(50), async {
+            tokio::join!(subscriber.run(), async {
+                // While the third subscription is connected
+                sleep(Duration::from_millis(10)).await;
+                contracts.send_modify(|contracts| contracts.push(contract("33")));
+            })
+        })
+        .await;
+
+        assert!(running.is_err());
+        assert_eq!(usecase.0.lock().unwrap()[5..], [("poll", 3), ("stream", 3)]);
+    }
+}
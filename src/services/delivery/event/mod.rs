@@ -1 +1,3 @@
+pub mod evm_chain_log_listener;
 pub mod evm_log_listener;
+pub mod evm_log_subscriber;
@@ -0,0 +1,24 @@
+use crate::services::dtos::index_logs::TrackedContract;
+
+/// A historical block range to index alongside the live cursor.
+#[derive(Debug, Clone)]
+pub struct BackfillJob {
+    pub contract: TrackedContract,
+
+    /// Inclusive block range, the blocks skipped by handing the cursor over to the
+    /// confirmed head when unset
+    pub range: Option<(u64, u64)>,
+
+    /// Blocks per recorded chunk
+    pub chunk_size: u64,
+
+    /// Chunks indexed concurrently
+    pub workers: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BackfillReport {
+    pub completed_chunks: usize,
+    pub failed_chunks: usize,
+    pub logs: u64,
+}
@@ -0,0 +1,19 @@
+/// Blocks ingested for a contract between its start block and its cursor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CoverageReport {
+    pub start_block: u64,
+    pub cursor: u64,
+    pub covered_blocks: u64,
+
+    /// Inclusive block ranges no ingested range covers
+    pub gaps: Vec<(u64, u64)>,
+
+    /// Inclusive block ranges ingested more than once
+    pub overlaps: Vec<(u64, u64)>,
+}
+
+impl CoverageReport {
+    pub fn expected_blocks(&self) -> u64 {
+        (self.cursor + 1).saturating_sub(self.start_block)
+    }
+}
@@ -1,22 +1,236 @@
+use alloy::rpc::types::Log;
+use sqlx::types::BigDecimal;
+
 use crate::services::usecase::errors::AppError;
 
+/// Log a handled event was decoded from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventSource {
+    pub chain_id: u64,
+
+    /// Contract that emitted the event
+    pub address: [u8; 20],
+    pub block_number: u64,
+    pub transaction_hash: [u8; 32],
+    pub log_index: u64,
+
+    /// Unix time of the block, when the listener stored it
+    pub block_timestamp: Option<u64>,
+}
+
+impl EventSource {
+    pub fn new(chain_id: u64, log: &Log) -> Result<Self, AppError> {
+        let missing = |field: &str| AppError::ParseError(format!("Log without {field}"));
+
+        Ok(Self {
+            chain_id,
+            address: log.address().0.0,
+            block_number: log.block_number.ok_or_else(|| missing("block number"))?,
+            transaction_hash: log
+                .transaction_hash
+                .ok_or_else(|| missing("transaction hash"))?
+                .0,
+            log_index: log.log_index.ok_or_else(|| missing("log index"))?,
+            block_timestamp: log.block_timestamp,
+        })
+    }
+}
+
+/// What a contract handler decoded from a log, applied by the index engine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexedEvent {
+    ContractDiscovered(DiscoveredContract),
+    PoolCreated(PoolCreatedRequest),
+    OwnerChanged(OwnerChangedRequest),
+    FeeAmountEnabled(FeeAmountEnabledRequest),
+    PoolInitialized(PoolInitializedRequest),
+    Swap(SwapRequest),
+    Mint(LiquidityRequest),
+    Burn(LiquidityRequest),
+    Flash(FlashRequest),
+    Erc20Transfer(Erc20TransferRequest),
+    Erc20Approval(Erc20ApprovalRequest),
+    Erc721Transfer(Erc721TransferRequest),
+    Erc1155Transfer(Erc1155TransferRequest),
+    Erc1155Uri(Erc1155UriRequest),
+    PairCreated(PairCreatedRequest),
+    PairSync(PairSyncRequest),
+    PairSwap(PairSwapRequest),
+    PairMint(PairLiquidityRequest),
+    PairBurn(PairLiquidityRequest),
+}
+
+/// Pool deployed by the Uniswap V3 factory at `source.address`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PoolCreatedRequest {
-    pub pool: String,
-    pub token0: String,
-    pub token1: String,
+    pub source: EventSource,
+    pub pool: [u8; 20],
+    pub token0: [u8; 20],
+    pub token1: [u8; 20],
     pub fee: u32,
+    pub tick_spacing: i32,
+}
+
+/// Child contract a factory event deployed, indexed from its creation block with the
+/// handler named `contract_name` and the ABIs of the factory's `deployment`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredContract {
+    pub address: [u8; 20],
+    pub contract_name: String,
+    pub deployment: Option<String>,
+    pub start_block: u64,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct OwnerChangedRequest {
-    pub previous: String,
-    pub new_owner: String,
+    pub source: EventSource,
+    pub previous: [u8; 20],
+    pub new_owner: [u8; 20],
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FeeAmountEnabledRequest {
+    pub source: EventSource,
     pub fee: u32,
     pub tick_spacing: i32,
 }
 
+/// First price of the Uniswap V3 pool at `source.address`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolInitializedRequest {
+    pub source: EventSource,
+    pub sqrt_price_x96: BigDecimal,
+    pub tick: i32,
+}
+
+/// Swap through a Uniswap V3 pool. Positive amounts were paid into the pool, and the
+/// price, liquidity and tick are the pool's after the swap.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwapRequest {
+    pub source: EventSource,
+    pub sender: [u8; 20],
+    pub recipient: [u8; 20],
+    pub amount0: BigDecimal,
+    pub amount1: BigDecimal,
+    pub sqrt_price_x96: BigDecimal,
+    pub liquidity: BigDecimal,
+    pub tick: i32,
+}
+
+/// Liquidity added to or removed from a tick range of a Uniswap V3 pool.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiquidityRequest {
+    pub source: EventSource,
+    pub owner: [u8; 20],
+    pub tick_lower: i32,
+    pub tick_upper: i32,
+    pub amount: BigDecimal,
+    pub amount0: BigDecimal,
+    pub amount1: BigDecimal,
+}
+
+/// Flash loan from a Uniswap V3 pool, `paid0` and `paid1` being the fees paid back.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlashRequest {
+    pub source: EventSource,
+    pub sender: [u8; 20],
+    pub recipient: [u8; 20],
+    pub amount0: BigDecimal,
+    pub amount1: BigDecimal,
+    pub paid0: BigDecimal,
+    pub paid1: BigDecimal,
+}
+
+/// Tokens of the ERC-20 at `source.address` moved between holders, minted from the
+/// zero address or burned to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc20TransferRequest {
+    pub source: EventSource,
+    pub from: [u8; 20],
+    pub to: [u8; 20],
+    pub value: BigDecimal,
+}
+
+/// Allowance `owner` granted `spender` on the ERC-20 at `source.address`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc20ApprovalRequest {
+    pub source: EventSource,
+    pub owner: [u8; 20],
+    pub spender: [u8; 20],
+    pub value: BigDecimal,
+}
+
+/// Token `token_id` of the ERC-721 at `source.address` moved to `to`, minted from the
+/// zero address or burned to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc721TransferRequest {
+    pub source: EventSource,
+    pub from: [u8; 20],
+    pub to: [u8; 20],
+    pub token_id: BigDecimal,
+}
+
+/// `value` of token `id` of the ERC-1155 at `source.address` moved from `from` to `to`.
+/// A batch transfer is one request per id, `batch_index` being its position in the batch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc1155TransferRequest {
+    pub source: EventSource,
+    pub batch_index: u32,
+    pub operator: [u8; 20],
+    pub from: [u8; 20],
+    pub to: [u8; 20],
+    pub id: BigDecimal,
+    pub value: BigDecimal,
+}
+
+/// Metadata URI of token `id` of the ERC-1155 at `source.address`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Erc1155UriRequest {
+    pub source: EventSource,
+    pub id: BigDecimal,
+    pub uri: String,
+}
+
+/// Pair deployed by the Uniswap V2 factory, or a fork of it, at `source.address`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairCreatedRequest {
+    pub source: EventSource,
+    pub pair: [u8; 20],
+    pub token0: [u8; 20],
+    pub token1: [u8; 20],
+}
+
+/// Reserves of the Uniswap V2 pair at `source.address` after a swap or a change of
+/// liquidity.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairSyncRequest {
+    pub source: EventSource,
+    pub reserve0: BigDecimal,
+    pub reserve1: BigDecimal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairSwapRequest {
+    pub source: EventSource,
+    pub sender: [u8; 20],
+    pub recipient: [u8; 20],
+    pub amount0_in: BigDecimal,
+    pub amount1_in: BigDecimal,
+    pub amount0_out: BigDecimal,
+    pub amount1_out: BigDecimal,
+}
+
+/// Liquidity added to or removed from a Uniswap V2 pair, only removals having a
+/// `recipient`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairLiquidityRequest {
+    pub source: EventSource,
+    pub sender: [u8; 20],
+    pub recipient: Option<[u8; 20]>,
+    pub amount0: BigDecimal,
+    pub amount1: BigDecimal,
+}
+
 #[derive(Debug)]
 pub enum BatchResult {
     NoLogsFound,
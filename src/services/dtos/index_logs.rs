@@ -0,0 +1,96 @@
+use alloy::{hex, primitives::B256, rpc::types::Log};
+
+use crate::{
+    infrastructure::abi::abi_loader::AbiLoader,
+    services::{
+        entities::{
+            evm_blocks::EVMBlocks, evm_transactions::EVMTransactions,
+            tracked_contracts::TrackedContracts,
+        },
+        usecase::errors::AppError,
+    },
+};
+
+/// A contract the listener keeps a sync cursor for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedContract {
+    pub address: String,
+
+    /// Index up to the chain head instead of the confirmed bound,
+    /// flagging logs past that bound as unconfirmed
+    pub unconfirmed: bool,
+
+    /// First block to index, discovered from the deployment block when unset
+    pub start_block: Option<u64>,
+
+    /// Topic0 hashes of the events to index, every event when empty
+    pub event_signatures: Vec<B256>,
+}
+
+impl TrackedContract {
+    /// Settings of a `tracked_contracts` row, its events resolved to their topic0
+    /// through the ABI of its handler.
+    pub fn from_tracked(
+        contract: &TrackedContracts,
+        abi_loader: &AbiLoader,
+    ) -> Result<Self, AppError> {
+        let event_signatures = match &contract.events {
+            Some(events) => abi_loader.event_signatures(
+                &AbiLoader::abi_name(&contract.contract_name, contract.deployment.as_deref()),
+                events,
+            )?,
+            None => Vec::new(),
+        };
+
+        Ok(Self {
+            address: hex::encode(contract.address),
+            unconfirmed: contract.unconfirmed,
+            start_block: contract.start_block.map(|block| block as u64),
+            event_signatures,
+        })
+    }
+
+    pub fn wants_event(&self, signature: Option<&B256>) -> bool {
+        self.event_signatures.is_empty()
+            || signature.is_some_and(|signature| self.event_signatures.contains(signature))
+    }
+}
+
+/// A sync cursor moved to the end of an ingested block range.
+#[derive(Debug, Clone)]
+pub struct CursorAdvance {
+    pub address: [u8; 20],
+    pub block_number: u64,
+
+    /// Block the cursor was at when the range was fetched, the range is only
+    /// committed while it still is
+    pub previous_block: Option<u64>,
+}
+
+/// Inclusive block range whose logs were ingested for a contract.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IngestedRange {
+    pub address: [u8; 20],
+    pub from_block: u64,
+    pub to_block: u64,
+}
+
+/// Everything stored for a block range, committed as a whole.
+#[derive(Debug, Clone)]
+pub struct IngestRange {
+    pub chain_id: u64,
+
+    /// Headers of the range's blocks, replacing those stored at the same heights
+    pub blocks: Vec<EVMBlocks>,
+
+    pub logs: Vec<Log>,
+
+    /// Logs above this block are stored as unconfirmed
+    pub confirmed_block: u64,
+
+    pub transactions: Vec<EVMTransactions>,
+    pub cursors: Vec<CursorAdvance>,
+
+    /// Coverage recorded for the contracts the range was fetched for
+    pub ranges: Vec<IngestedRange>,
+}
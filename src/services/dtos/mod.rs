@@ -1 +1,4 @@
+pub mod backfill;
+pub mod coverage;
 pub mod index_engine;
+pub mod index_logs;
@@ -0,0 +1,11 @@
+use sqlx::types::chrono;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EVMBackfillChunks {
+    pub chain_id: i64,
+    pub address: [u8; 20],
+    pub from_block: i64,
+    pub to_block: i64,
+    pub completed_at: Option<chrono::NaiveDateTime>,
+    pub created_at: chrono::NaiveDateTime,
+}
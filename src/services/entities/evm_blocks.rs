@@ -0,0 +1,65 @@
+use alloy::rpc::types::Header;
+use sqlx::types::chrono;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EVMBlocks {
+    pub chain_id: i64,
+    pub number: i64,
+    pub hash: [u8; 32],
+    pub parent_hash: [u8; 32],
+    pub timestamp: i64,
+    pub gas_used: i64,
+    pub base_fee_per_gas: Option<i64>,
+    pub miner: [u8; 20],
+    pub created_at: chrono::NaiveDateTime,
+}
+
+impl From<&Header> for EVMBlocks {
+    fn from(header: &Header) -> Self {
+        Self {
+            chain_id: 0, // Not part of the RPC header
+            number: header.number as i64,
+            hash: header.hash.0,
+            parent_hash: header.parent_hash.0,
+            timestamp: header.timestamp as i64,
+            gas_used: header.gas_used as i64,
+            base_fee_per_gas: header.base_fee_per_gas.map(|fee| fee as i64),
+            miner: header.beneficiary.0.0,
+            created_at: chrono::Utc::now().naive_utc(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy::primitives::{Address, B256};
+
+    #[test]
+    fn converts_the_rpc_header() {
+        let header = Header {
+            hash: B256::repeat_byte(1),
+            inner: alloy::consensus::Header {
+                number: 12345,
+                parent_hash: B256::repeat_byte(2),
+                timestamp: 1_700_000_000,
+                gas_used: 21_000,
+                base_fee_per_gas: Some(7),
+                beneficiary: Address::repeat_byte(3),
+                ..Default::default()
+            },
+            total_difficulty: None,
+            size: None,
+        };
+
+        let block = EVMBlocks::from(&header);
+
+        assert_eq!(block.number, 12345);
+        assert_eq!(block.hash, [1u8; 32]);
+        assert_eq!(block.parent_hash, [2u8; 32]);
+        assert_eq!(block.timestamp, 1_700_000_000);
+        assert_eq!(block.gas_used, 21_000);
+        assert_eq!(block.base_fee_per_gas, Some(7));
+        assert_eq!(block.miner, [3u8; 20]);
+    }
+}
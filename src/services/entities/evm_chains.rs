@@ -6,6 +6,11 @@ pub struct EvmChains {
     pub name: String,
     pub last_synced_block_number: i64,
     pub block_time: i32,
+    pub confirmation_depth: i32,
+    pub finality_tag: Option<String>,
+    pub enabled: bool,
+    pub rpc_urls: Vec<String>,
+    pub ws_url: Option<String>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
 }
@@ -22,6 +22,7 @@ pub enum EVMLogsError {
 #[derive(Debug, Clone, FromRow)]
 pub struct EVMLogs {
     pub id: i32,
+    pub chain_id: i64,
     pub block_number: BigDecimal,
     pub block_hash: [u8; 32],
     pub address: [u8; 20],
@@ -32,6 +33,8 @@ pub struct EVMLogs {
     pub transaction_index: i64,
     pub log_index: i64,
     pub removed: bool,
+    pub unconfirmed: bool,
+    pub block_timestamp: Option<i64>,
     pub created_at: chrono::NaiveDateTime,
 }
 
@@ -71,7 +74,7 @@ impl TryFrom<EVMLogs> for Log {
             inner,
             block_number: Some(block_number),
             block_hash: Some(block_hash),
-            block_timestamp: None,
+            block_timestamp: evm_log.block_timestamp.map(|timestamp| timestamp as u64),
             transaction_hash: Some(transaction_hash),
             transaction_index: Some(evm_log.transaction_index as u64),
             log_index: Some(evm_log.log_index as u64),
@@ -88,25 +91,23 @@ impl TryFrom<Log> for EVMLogs {
         // Extract required fields with error handling
         let address: [u8; 20] = log.address().0.into();
 
-        let transaction_hash: [u8; 32] = log
-            .transaction_hash
-            .ok_or(EVMLogsError::InvalidLogData)?
-            .0
-            .into();
+        let transaction_hash: [u8; 32] =
+            log.transaction_hash.ok_or(EVMLogsError::InvalidLogData)?.0;
 
-        let block_hash: [u8; 32] = log.block_hash.ok_or(EVMLogsError::InvalidLogData)?.0.into();
+        let block_hash: [u8; 32] = log.block_hash.ok_or(EVMLogsError::InvalidLogData)?.0;
 
         let block_number = log.block_number.ok_or(EVMLogsError::InvalidLogData)?;
 
         // Convert topics
-        let topics: Vec<[u8; 32]> = log.topics().iter().map(|t| t.0.into()).collect();
+        let topics: Vec<[u8; 32]> = log.topics().iter().map(|t| t.0).collect();
 
         // First topic is the event signature
         let event_signature = topics.first().copied().unwrap_or([0u8; 32]);
 
         // Build EVMLogs
         Ok(Self {
-            id: 0, // Auto-generated by database
+            id: 0,       // Auto-generated by database
+            chain_id: 0, // Not part of the RPC log
             block_number: BigDecimal::from(block_number),
             block_hash,
             address,
@@ -117,6 +118,8 @@ impl TryFrom<Log> for EVMLogs {
             transaction_index: log.transaction_index.unwrap_or(0) as i64,
             log_index: log.log_index.unwrap_or(0) as i64,
             removed: log.removed,
+            unconfirmed: false,
+            block_timestamp: log.block_timestamp.map(|timestamp| timestamp as i64),
             created_at: chrono::Utc::now().naive_utc(),
         })
     }
@@ -140,7 +143,7 @@ mod tests {
             inner,
             block_number: Some(12345),
             block_hash: Some(FixedBytes::<32>::from([4u8; 32])),
-            block_timestamp: None,
+            block_timestamp: Some(1_700_000_000),
             transaction_hash: Some(FixedBytes::<32>::from([5u8; 32])),
             transaction_index: Some(1),
             log_index: Some(2),
@@ -160,5 +163,6 @@ mod tests {
         assert_eq!(converted_log.address(), rpc_log.address());
         assert_eq!(converted_log.block_number, rpc_log.block_number);
         assert_eq!(converted_log.transaction_hash, rpc_log.transaction_hash);
+        assert_eq!(converted_log.block_timestamp, rpc_log.block_timestamp);
     }
 }
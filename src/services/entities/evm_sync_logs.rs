@@ -3,7 +3,21 @@ use sqlx::types::chrono;
 #[derive(Debug, sqlx::FromRow)]
 pub struct EVMSyncLogs {
     pub address: [u8; 20],
-    pub last_synced_block_number: i64,
+    pub chain_id: i64,
+
+    /// Last block indexed, None until the first one is
+    pub last_synced_block_number: Option<i64>,
+
+    /// First block the cursor indexed, None for cursors created before it was stored
+    pub start_block: Option<i64>,
     pub created_at: chrono::NaiveDateTime,
     pub updated_at: chrono::NaiveDateTime,
 }
+
+impl EVMSyncLogs {
+    /// First block the cursor still has to index.
+    pub fn next_block(&self) -> u64 {
+        self.last_synced_block_number
+            .map_or(0, |block_number| block_number as u64 + 1)
+    }
+}
@@ -0,0 +1,75 @@
+use std::str::FromStr;
+
+use alloy::{
+    consensus::Transaction as _,
+    rpc::types::{Transaction, TransactionReceipt},
+};
+use sqlx::{
+    prelude::FromRow,
+    types::{BigDecimal, chrono},
+};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum EVMTransactionsError {
+    #[error("Receipt of transaction `{0}` is not mined")]
+    PendingReceipt(String),
+
+    #[error("Receipt does not belong to transaction `{0}`")]
+    MismatchedReceipt(String),
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct EVMTransactions {
+    pub chain_id: i64,
+    pub transaction_hash: [u8; 32],
+    pub block_number: i64,
+    pub block_hash: [u8; 32],
+    pub transaction_index: i64,
+    pub from_address: [u8; 20],
+    pub to_address: Option<[u8; 20]>,
+    pub value: BigDecimal,
+    pub gas_used: BigDecimal,
+    pub effective_gas_price: BigDecimal,
+    pub status: bool,
+    pub created_at: chrono::NaiveDateTime,
+}
+
+/// Convert an Alloy transaction and its receipt (from RPC) to EVMTransactions (for
+/// database storage)
+impl TryFrom<(&Transaction, &TransactionReceipt)> for EVMTransactions {
+    type Error = EVMTransactionsError;
+
+    fn try_from(
+        (transaction, receipt): (&Transaction, &TransactionReceipt),
+    ) -> Result<Self, Self::Error> {
+        let hash = receipt.transaction_hash;
+        if *transaction.inner.tx_hash() != hash {
+            return Err(EVMTransactionsError::MismatchedReceipt(hash.to_string()));
+        }
+
+        let (Some(block_number), Some(block_hash)) = (receipt.block_number, receipt.block_hash)
+        else {
+            return Err(EVMTransactionsError::PendingReceipt(hash.to_string()));
+        };
+
+        // U256 does not fit any primitive, so it goes through its decimal string
+        let value = BigDecimal::from_str(&transaction.value().to_string())
+            .expect("U256 formats as a decimal integer");
+
+        Ok(Self {
+            chain_id: 0, // Not part of the RPC transaction
+            transaction_hash: hash.0,
+            block_number: block_number as i64,
+            block_hash: block_hash.0,
+            transaction_index: receipt.transaction_index.unwrap_or(0) as i64,
+            from_address: receipt.from.0.0,
+            to_address: receipt.to.map(|to| to.0.0),
+            value,
+            gas_used: BigDecimal::from(receipt.gas_used),
+            effective_gas_price: BigDecimal::from(receipt.effective_gas_price),
+            status: receipt.status(),
+            created_at: chrono::Utc::now().naive_utc(),
+        })
+    }
+}
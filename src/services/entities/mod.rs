@@ -1,3 +1,8 @@
+pub mod evm_backfill_chunks;
+pub mod evm_blocks;
 pub mod evm_chains;
+pub mod evm_ingested_ranges;
 pub mod evm_logs;
 pub mod evm_sync_logs;
+pub mod evm_transactions;
+pub mod tracked_contracts;
@@ -0,0 +1,20 @@
+use sqlx::types::chrono;
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct TrackedContracts {
+    pub chain_id: i64,
+    pub address: [u8; 20],
+    /// Handler the contract's logs are processed with
+    pub contract_name: String,
+    pub start_block: Option<i64>,
+    pub enabled: bool,
+    pub parent_address: Option<[u8; 20]>,
+    /// Artifacts subdirectory of the ABIs of a fork, the default ABIs when unset
+    pub deployment: Option<String>,
+    /// Indexed up to the chain head, ahead of confirmations
+    pub unconfirmed: bool,
+    /// Names of the only events indexed, every event when unset
+    pub events: Option<Vec<String>>,
+    pub created_at: chrono::NaiveDateTime,
+    pub updated_at: chrono::NaiveDateTime,
+}
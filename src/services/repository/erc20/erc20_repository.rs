@@ -0,0 +1,251 @@
+use async_trait::async_trait;
+use sqlx::{PgConnection, PgPool, types::BigDecimal};
+
+use crate::services::{
+    dtos::index_engine::{Erc20ApprovalRequest, Erc20TransferRequest},
+    repository::Erc20Repository,
+};
+
+/// Tokens are minted from and burned to the zero address.
+const ZERO_ADDRESS: [u8; 20] = [0; 20];
+
+/// Adds `delta` to the balance of `holder`.
+async fn change_balance(
+    conn: &mut PgConnection,
+    transfer: &Erc20TransferRequest,
+    holder: [u8; 20],
+    delta: BigDecimal,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO erc20_balances (chain_id, token_address, holder, balance)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (chain_id, token_address, holder) DO UPDATE SET
+            balance = erc20_balances.balance + EXCLUDED.balance
+        "#,
+    )
+    .bind(transfer.source.chain_id as i64)
+    .bind(transfer.source.address)
+    .bind(holder)
+    .bind(delta)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct Erc20RepositoryImpl {
+    pool: PgPool,
+}
+
+impl Erc20RepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl Erc20Repository for Erc20RepositoryImpl {
+    async fn record_transfer(&self, transfer: &Erc20TransferRequest) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO erc20_transfers (
+                chain_id, token_address, block_number, log_index, transaction_hash,
+                block_timestamp, from_address, to_address, value
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (chain_id, token_address, block_number, log_index) DO NOTHING
+            "#,
+        )
+        .bind(transfer.source.chain_id as i64)
+        .bind(transfer.source.address)
+        .bind(transfer.source.block_number as i64)
+        .bind(transfer.source.log_index as i64)
+        .bind(transfer.source.transaction_hash)
+        .bind(
+            transfer
+                .source
+                .block_timestamp
+                .map(|timestamp| timestamp as i64),
+        )
+        .bind(transfer.from)
+        .bind(transfer.to)
+        .bind(&transfer.value)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // A transfer stored before was applied already
+        if inserted == 0 {
+            return tx.commit().await;
+        }
+
+        let mut supply_delta = BigDecimal::from(0);
+        if transfer.from == ZERO_ADDRESS {
+            supply_delta += &transfer.value;
+        } else {
+            change_balance(&mut tx, transfer, transfer.from, -transfer.value.clone()).await?;
+        }
+        if transfer.to == ZERO_ADDRESS {
+            supply_delta -= &transfer.value;
+        } else {
+            change_balance(&mut tx, transfer, transfer.to, transfer.value.clone()).await?;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO erc20_tokens (chain_id, token_address, total_supply)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (chain_id, token_address) DO UPDATE SET
+                total_supply = erc20_tokens.total_supply + EXCLUDED.total_supply
+            "#,
+        )
+        .bind(transfer.source.chain_id as i64)
+        .bind(transfer.source.address)
+        .bind(supply_delta)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    async fn record_approval(&self, approval: &Erc20ApprovalRequest) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO erc20_allowances (
+                chain_id, token_address, owner, spender, value, block_number, log_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (chain_id, token_address, owner, spender) DO UPDATE SET
+                value = EXCLUDED.value,
+                block_number = EXCLUDED.block_number,
+                log_index = EXCLUDED.log_index
+            WHERE (erc20_allowances.block_number, erc20_allowances.log_index)
+                < (EXCLUDED.block_number, EXCLUDED.log_index)
+            "#;
+
+        sqlx::query(query)
+            .bind(approval.source.chain_id as i64)
+            .bind(approval.source.address)
+            .bind(approval.owner)
+            .bind(approval.spender)
+            .bind(&approval.value)
+            .bind(approval.source.block_number as i64)
+            .bind(approval.source.log_index as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::services::dtos::index_engine::EventSource;
+
+    use super::*;
+
+    const CHAIN_ID: u64 = 1;
+    const TOKEN: [u8; 20] = [0x20; 20];
+    const ALICE: [u8; 20] = [0x0a; 20];
+    const BOB: [u8; 20] = [0x0b; 20];
+
+    fn source(block_number: u64, log_index: u64) -> EventSource {
+        EventSource {
+            chain_id: CHAIN_ID,
+            address: TOKEN,
+            block_number,
+            transaction_hash: [block_number as u8; 32],
+            log_index,
+            block_timestamp: None,
+        }
+    }
+
+    fn transfer(
+        block_number: u64,
+        from: [u8; 20],
+        to: [u8; 20],
+        value: i64,
+    ) -> Erc20TransferRequest {
+        Erc20TransferRequest {
+            source: source(block_number, 0),
+            from,
+            to,
+            value: BigDecimal::from(value),
+        }
+    }
+
+    fn approval(block_number: u64, value: i64) -> Erc20ApprovalRequest {
+        Erc20ApprovalRequest {
+            source: source(block_number, 0),
+            owner: ALICE,
+            spender: BOB,
+            value: BigDecimal::from(value),
+        }
+    }
+
+    async fn balance(pool: &PgPool, holder: [u8; 20]) -> Option<BigDecimal> {
+        sqlx::query_scalar(
+            "SELECT balance FROM erc20_balances WHERE chain_id = $1 AND token_address = $2 AND holder = $3",
+        )
+        .bind(CHAIN_ID as i64)
+        .bind(TOKEN)
+        .bind(holder)
+        .fetch_optional(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn mints_transfers_and_burns_move_balances_and_supply_once(pool: PgPool) {
+        let repo = Erc20RepositoryImpl::new(pool.clone());
+
+        repo.record_transfer(&transfer(1, ZERO_ADDRESS, ALICE, 100))
+            .await
+            .unwrap();
+        repo.record_transfer(&transfer(2, ALICE, BOB, 30))
+            .await
+            .unwrap();
+        repo.record_transfer(&transfer(2, ALICE, BOB, 30))
+            .await
+            .unwrap();
+        repo.record_transfer(&transfer(3, BOB, ZERO_ADDRESS, 10))
+            .await
+            .unwrap();
+
+        assert_eq!(balance(&pool, ALICE).await, Some(BigDecimal::from(70)));
+        assert_eq!(balance(&pool, BOB).await, Some(BigDecimal::from(20)));
+        assert_eq!(balance(&pool, ZERO_ADDRESS).await, None);
+        let supply: BigDecimal = sqlx::query_scalar(
+            "SELECT total_supply FROM erc20_tokens WHERE chain_id = $1 AND token_address = $2",
+        )
+        .bind(CHAIN_ID as i64)
+        .bind(TOKEN)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(supply, BigDecimal::from(90));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn keeps_the_allowance_of_the_latest_approval(pool: PgPool) {
+        let repo = Erc20RepositoryImpl::new(pool.clone());
+        let allowance = || {
+            sqlx::query_scalar::<_, BigDecimal>(
+                "SELECT value FROM erc20_allowances WHERE owner = $1 AND spender = $2",
+            )
+            .bind(ALICE)
+            .bind(BOB)
+            .fetch_one(&pool)
+        };
+
+        repo.record_approval(&approval(5, 50)).await.unwrap();
+        repo.record_approval(&approval(3, 10)).await.unwrap();
+        assert_eq!(allowance().await.unwrap(), BigDecimal::from(50));
+
+        repo.record_approval(&approval(6, 0)).await.unwrap();
+        assert_eq!(allowance().await.unwrap(), BigDecimal::from(0));
+    }
+}
@@ -0,0 +1 @@
+pub mod erc20_repository;
@@ -0,0 +1,87 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::services::{
+    entities::evm_backfill_chunks::EVMBackfillChunks, repository::EVMBackfillChunksRepository,
+};
+
+#[derive(Clone)]
+pub struct EVMBackfillChunksRepositoryImpl {
+    pool: PgPool,
+}
+
+impl EVMBackfillChunksRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EVMBackfillChunksRepository for EVMBackfillChunksRepositoryImpl {
+    async fn create_bulk(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        chunks: Vec<(u64, u64)>,
+    ) -> Result<(), sqlx::Error> {
+        let (from_blocks, to_blocks): (Vec<i64>, Vec<i64>) = chunks
+            .into_iter()
+            .map(|(from_block, to_block)| (from_block as i64, to_block as i64))
+            .unzip();
+
+        let query = r#"
+            INSERT INTO evm_backfill_chunks (chain_id, address, from_block, to_block)
+            SELECT $1, $2, * FROM UNNEST($3::BIGINT[], $4::BIGINT[])
+            ON CONFLICT (chain_id, address, from_block) DO NOTHING
+            "#;
+
+        sqlx::query(query)
+            .bind(chain_id as i64)
+            .bind(address)
+            .bind(from_blocks)
+            .bind(to_blocks)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn find_pending(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+    ) -> Result<Vec<EVMBackfillChunks>, sqlx::Error> {
+        let query = r#"
+            SELECT * FROM evm_backfill_chunks
+            WHERE chain_id = $1 AND address = $2 AND completed_at IS NULL
+            ORDER BY from_block
+            "#;
+
+        sqlx::query_as::<_, EVMBackfillChunks>(query)
+            .bind(chain_id as i64)
+            .bind(address)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn complete(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        from_block: u64,
+    ) -> Result<(), sqlx::Error> {
+        let query = r#"
+            UPDATE evm_backfill_chunks SET completed_at = NOW()
+            WHERE chain_id = $1 AND address = $2 AND from_block = $3
+            "#;
+
+        sqlx::query(query)
+            .bind(chain_id as i64)
+            .bind(address)
+            .bind(from_block as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
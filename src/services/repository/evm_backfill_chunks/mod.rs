@@ -0,0 +1 @@
+pub mod evm_backfill_chunk_repository;
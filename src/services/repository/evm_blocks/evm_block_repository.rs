@@ -0,0 +1,147 @@
+use async_trait::async_trait;
+use sqlx::{PgConnection, PgPool};
+
+use crate::services::{entities::evm_blocks::EVMBlocks, repository::EVMBlocksRepository};
+
+/// Stores block headers, replacing those of the same height after a reorg.
+pub(crate) async fn upsert_blocks(
+    conn: &mut PgConnection,
+    chain_id: u64,
+    blocks: &[EVMBlocks],
+) -> Result<(), sqlx::Error> {
+    if blocks.is_empty() {
+        return Ok(());
+    }
+
+    let mut numbers = Vec::with_capacity(blocks.len());
+    let mut hashes = Vec::with_capacity(blocks.len());
+    let mut parent_hashes = Vec::with_capacity(blocks.len());
+    let mut timestamps = Vec::with_capacity(blocks.len());
+    let mut gas_used = Vec::with_capacity(blocks.len());
+    let mut base_fees = Vec::with_capacity(blocks.len());
+    let mut miners = Vec::with_capacity(blocks.len());
+    for block in blocks {
+        numbers.push(block.number);
+        hashes.push(block.hash.to_vec());
+        parent_hashes.push(block.parent_hash.to_vec());
+        timestamps.push(block.timestamp);
+        gas_used.push(block.gas_used);
+        base_fees.push(block.base_fee_per_gas);
+        miners.push(block.miner.to_vec());
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO evm_blocks (
+            chain_id, number, hash, parent_hash, timestamp, gas_used, base_fee_per_gas, miner
+        )
+        SELECT $1, * FROM UNNEST(
+            $2::BIGINT[], $3::BYTEA[], $4::BYTEA[], $5::BIGINT[], $6::BIGINT[], $7::BIGINT[],
+            $8::BYTEA[]
+        )
+        ON CONFLICT (chain_id, number) DO UPDATE SET
+            hash = EXCLUDED.hash,
+            parent_hash = EXCLUDED.parent_hash,
+            timestamp = EXCLUDED.timestamp,
+            gas_used = EXCLUDED.gas_used,
+            base_fee_per_gas = EXCLUDED.base_fee_per_gas,
+            miner = EXCLUDED.miner
+        "#,
+    )
+    .bind(chain_id as i64)
+    .bind(numbers)
+    .bind(hashes)
+    .bind(parent_hashes)
+    .bind(timestamps)
+    .bind(gas_used)
+    .bind(base_fees)
+    .bind(miners)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct EVMBlocksRepositoryImpl {
+    pool: PgPool,
+}
+
+impl EVMBlocksRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EVMBlocksRepository for EVMBlocksRepositoryImpl {
+    async fn find_recent(
+        &self,
+        chain_id: u64,
+        up_to_block: u64,
+        limit: i64,
+    ) -> Result<Vec<EVMBlocks>, sqlx::Error> {
+        let query = r#"
+            SELECT * FROM evm_blocks
+            WHERE chain_id = $1 AND number <= $2
+            ORDER BY number DESC
+            LIMIT $3
+            "#;
+
+        sqlx::query_as::<_, EVMBlocks>(query)
+            .bind(chain_id as i64)
+            .bind(up_to_block as i64)
+            .bind(limit)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn find_by_numbers(
+        &self,
+        chain_id: u64,
+        numbers: &[u64],
+    ) -> Result<Vec<EVMBlocks>, sqlx::Error> {
+        let numbers: Vec<i64> = numbers.iter().map(|number| *number as i64).collect();
+        let query = r#"SELECT * FROM evm_blocks WHERE chain_id = $1 AND number = ANY($2)"#;
+
+        sqlx::query_as::<_, EVMBlocks>(query)
+            .bind(chain_id as i64)
+            .bind(numbers)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn average_block_time(
+        &self,
+        chain_id: u64,
+        sample_size: i64,
+    ) -> Result<Option<f64>, sqlx::Error> {
+        let query = r#"
+            SELECT (MAX(timestamp) - MIN(timestamp))::FLOAT8 / NULLIF(MAX(number) - MIN(number), 0)
+            FROM (
+                SELECT number, timestamp FROM evm_blocks
+                WHERE chain_id = $1
+                ORDER BY number DESC
+                LIMIT $2
+            ) recent
+            "#;
+
+        sqlx::query_scalar::<_, Option<f64>>(query)
+            .bind(chain_id as i64)
+            .bind(sample_size)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    async fn delete_after(&self, chain_id: u64, block_number: u64) -> Result<u64, sqlx::Error> {
+        let query = r#"DELETE FROM evm_blocks WHERE chain_id = $1 AND number > $2"#;
+
+        let result = sqlx::query(query)
+            .bind(chain_id as i64)
+            .bind(block_number as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -24,6 +24,14 @@ impl EVMChainRepository for EVMChainRepositoryImpl {
             .await
     }
 
+    async fn find_enabled(&self) -> Result<Vec<EvmChains>, sqlx::Error> {
+        let query = r#"SELECT * FROM evm_chains WHERE enabled ORDER BY id"#;
+
+        sqlx::query_as::<_, EvmChains>(query)
+            .fetch_all(&self.pool)
+            .await
+    }
+
     async fn update_last_synced_block_number(
         &self,
         id: u64,
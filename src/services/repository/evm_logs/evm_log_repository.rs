@@ -1,31 +1,27 @@
 use alloy::rpc::types::Log;
 use async_trait::async_trait;
-use sqlx::{PgPool, types::BigDecimal};
+use sqlx::{PgConnection, PgPool, Postgres, QueryBuilder, types::BigDecimal};
 
 use crate::services::{entities::evm_logs::EVMLogs, repository::EVMLogsRepository};
 
-#[derive(Clone)]
-pub struct EVMLogsRepositoryImpl {
-    pool: PgPool,
-}
-
-impl EVMLogsRepositoryImpl {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
-    }
+/// Column values of a log about to be inserted.
+struct NewLog {
+    block_hash: Vec<u8>,
+    block_number: BigDecimal,
+    address: Vec<u8>,
+    transaction_hash: Vec<u8>,
+    transaction_index: i64,
+    event_signature: Vec<u8>,
+    topics: Vec<Vec<u8>>,
+    data: Vec<u8>,
+    log_index: i64,
+    block_timestamp: Option<i64>,
 }
 
-#[async_trait]
-impl EVMLogsRepository for EVMLogsRepositoryImpl {
-    async fn create_bulk(&self, logs: Vec<Log>) -> Result<(), sqlx::Error> {
-        for log in logs {
-            Self::create(self, log).await?;
-        }
-
-        Ok(())
-    }
+impl TryFrom<&Log> for NewLog {
+    type Error = sqlx::Error;
 
-    async fn create(&self, log: Log) -> Result<EVMLogs, sqlx::Error> {
+    fn try_from(log: &Log) -> Result<Self, Self::Error> {
         let block_hash = log
             .block_hash
             .ok_or_else(|| sqlx::Error::Decode("Missing block hash".into()))?
@@ -53,38 +49,192 @@ impl EVMLogsRepository for EVMLogsRepositoryImpl {
             .ok_or_else(|| sqlx::Error::Decode("Missing transaction hash".into()))?
             .to_vec();
 
-        let address = log.address().to_vec();
-        let event_signature: &[u8] = log.topics()[0].as_slice();
-        let topics: Vec<&[u8]> = log.topics().iter().map(|topic| topic.as_slice()).collect();
-        let log_data: Vec<u8> = log.inner.data.data.to_vec();
+        let event_signature = log
+            .topics()
+            .first()
+            .ok_or_else(|| sqlx::Error::Decode("Missing event signature".into()))?
+            .to_vec();
+
+        Ok(Self {
+            block_hash,
+            block_number,
+            address: log.address().to_vec(),
+            transaction_hash,
+            transaction_index,
+            event_signature,
+            topics: log.topics().iter().map(|topic| topic.to_vec()).collect(),
+            data: log.inner.data.data.to_vec(),
+            log_index,
+            block_timestamp: log.block_timestamp.map(|timestamp| timestamp as i64),
+        })
+    }
+}
+
+/// Columns written per log, Postgres accepting at most 65535 bind parameters per
+/// statement.
+const LOG_COLUMNS: usize = 13;
+
+/// Logs inserted per statement unless configured otherwise.
+pub const DEFAULT_INSERT_BATCH_SIZE: usize = 1_000;
+
+/// Largest batch fitting the bind parameter limit of a single statement.
+pub const MAX_INSERT_BATCH_SIZE: usize = u16::MAX as usize / LOG_COLUMNS;
+
+/// Condition of the logs the processor handles: confirmed, and of a contract still
+/// enabled.
+const PROCESSABLE: &str = r#"
+    NOT unconfirmed AND EXISTS (
+        SELECT 1 FROM tracked_contracts
+        WHERE tracked_contracts.chain_id = evm_logs.chain_id
+          AND tracked_contracts.address = evm_logs.address
+          AND tracked_contracts.enabled
+    )
+"#;
+
+/// Inserts `logs` with one multi-row statement per `batch_size` logs, flagging those
+/// above `confirmed_block` as unconfirmed and skipping logs already stored, as well as
+/// anonymous events, which have no signature to be processed by. Returns the number
+/// of logs inserted.
+pub(crate) async fn insert_logs(
+    conn: &mut PgConnection,
+    chain_id: u64,
+    logs: &[Log],
+    confirmed_block: u64,
+    batch_size: usize,
+) -> Result<u64, sqlx::Error> {
+    let batch_size = batch_size.clamp(1, MAX_INSERT_BATCH_SIZE);
+
+    let mut inserted = 0;
+    for batch in logs.chunks(batch_size) {
+        let rows = batch
+            .iter()
+            .filter_map(|log| match NewLog::try_from(log) {
+                Ok(row) => Some(Ok((row, log))),
+                Err(err) if log.topics().is_empty() => {
+                    eprintln!(
+                        "Skipping log {:?} of transaction {:?}: {err}",
+                        log.log_index, log.transaction_hash
+                    );
+                    None
+                }
+                Err(err) => Some(Err(err)),
+            })
+            .collect::<Result<Vec<_>, sqlx::Error>>()?;
+        if rows.is_empty() {
+            continue;
+        }
+
+        let mut query = QueryBuilder::<Postgres>::new(
+            r#"
+            INSERT INTO evm_logs (
+                block_hash, block_number, address, transaction_hash,
+                transaction_index, event_signature, topics, data,
+                log_index, removed, unconfirmed, chain_id, block_timestamp
+            )
+            "#,
+        );
+        query.push_values(rows, |mut values, (row, log)| {
+            let unconfirmed = log
+                .block_number
+                .is_some_and(|block_number| block_number > confirmed_block);
+
+            values
+                .push_bind(row.block_hash)
+                .push_bind(row.block_number)
+                .push_bind(row.address)
+                .push_bind(row.transaction_hash)
+                .push_bind(row.transaction_index)
+                .push_bind(row.event_signature)
+                .push_bind(row.topics)
+                .push_bind(row.data)
+                .push_bind(row.log_index)
+                .push_bind(log.removed)
+                .push_bind(unconfirmed)
+                .push_bind(chain_id as i64)
+                .push_bind(row.block_timestamp);
+        });
+        query.push(" ON CONFLICT (chain_id, transaction_hash, log_index) DO NOTHING");
+
+        let result = query.build().execute(&mut *conn).await?;
+        inserted += result.rows_affected();
+    }
+
+    Ok(inserted)
+}
+
+#[derive(Clone)]
+pub struct EVMLogsRepositoryImpl {
+    pool: PgPool,
+}
+
+impl EVMLogsRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EVMLogsRepository for EVMLogsRepositoryImpl {
+    async fn create_bulk(
+        &self,
+        chain_id: u64,
+        logs: Vec<Log>,
+        confirmed_block: u64,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        insert_logs(
+            &mut tx,
+            chain_id,
+            &logs,
+            confirmed_block,
+            DEFAULT_INSERT_BATCH_SIZE,
+        )
+        .await?;
+        tx.commit().await
+    }
+
+    async fn create(
+        &self,
+        chain_id: u64,
+        log: Log,
+        unconfirmed: bool,
+    ) -> Result<EVMLogs, sqlx::Error> {
+        let row = NewLog::try_from(&log)?;
 
         let query = r#"
                 INSERT INTO evm_logs (
                     block_hash, block_number, address, transaction_hash, 
                     transaction_index, event_signature, topics, data, 
-                    log_index, removed
+                    log_index, removed, unconfirmed, chain_id, block_timestamp
                 )
-                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
                 RETURNING *
             "#;
 
         sqlx::query_as::<_, EVMLogs>(query)
-            .bind(block_hash)
-            .bind(block_number)
-            .bind(address)
-            .bind(transaction_hash)
-            .bind(transaction_index)
-            .bind(event_signature)
-            .bind(topics)
-            .bind(log_data)
-            .bind(log_index)
+            .bind(row.block_hash)
+            .bind(row.block_number)
+            .bind(row.address)
+            .bind(row.transaction_hash)
+            .bind(row.transaction_index)
+            .bind(row.event_signature)
+            .bind(row.topics)
+            .bind(row.data)
+            .bind(row.log_index)
             .bind(log.removed)
+            .bind(unconfirmed)
+            .bind(chain_id as i64)
+            .bind(row.block_timestamp)
             .fetch_one(&self.pool)
             .await
     }
 
     async fn list(&self, page_size: i64) -> Result<Vec<EVMLogs>, sqlx::Error> {
-        sqlx::query_as::<_, EVMLogs>("SELECT * FROM evm_logs LIMIT $1")
+        let query = format!(
+            "SELECT * FROM evm_logs WHERE {PROCESSABLE} ORDER BY block_number, log_index LIMIT $1"
+        );
+
+        sqlx::query_as::<_, EVMLogs>(&query)
             .bind(page_size)
             .fetch_all(&self.pool)
             .await
@@ -99,11 +249,53 @@ impl EVMLogsRepository for EVMLogsRepositoryImpl {
         Ok(())
     }
 
-    async fn count(&self) -> Result<Option<i64>, sqlx::Error> {
-        let count: i64 = sqlx::query_scalar(r#"SELECT COUNT(*) FROM evm_logs"#)
-            .fetch_one(&self.pool)
+    async fn delete_by_address_after_block(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        block_number: u64,
+    ) -> Result<u64, sqlx::Error> {
+        let query =
+            r#"DELETE FROM evm_logs WHERE chain_id = $1 AND address = $2 AND block_number > $3"#;
+
+        let result = sqlx::query(query)
+            .bind(chain_id as i64)
+            .bind(address)
+            .bind(BigDecimal::from(block_number))
+            .execute(&self.pool)
             .await?;
 
+        Ok(result.rows_affected())
+    }
+
+    async fn confirm_logs(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        block_number: u64,
+    ) -> Result<u64, sqlx::Error> {
+        let query = r#"
+            UPDATE evm_logs SET unconfirmed = FALSE
+            WHERE chain_id = $1 AND address = $2 AND unconfirmed AND block_number <= $3
+            "#;
+
+        let result = sqlx::query(query)
+            .bind(chain_id as i64)
+            .bind(address)
+            .bind(BigDecimal::from(block_number))
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn count(&self) -> Result<Option<i64>, sqlx::Error> {
+        let count: i64 = sqlx::query_scalar(&format!(
+            "SELECT COUNT(*) FROM evm_logs WHERE {PROCESSABLE}"
+        ))
+        .fetch_one(&self.pool)
+        .await?;
+
         if count == 0 {
             return Ok(None);
         }
@@ -111,3 +303,143 @@ impl EVMLogsRepository for EVMLogsRepositoryImpl {
         Ok(Some(count))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{Address, B256, Bytes};
+
+    use super::*;
+
+    const CHAIN_ID: u64 = 1;
+
+    fn log(index: u64, topics: Vec<B256>) -> Log {
+        Log {
+            inner: alloy::primitives::Log::new_unchecked(
+                Address::repeat_byte(0x47),
+                topics,
+                Bytes::new(),
+            ),
+            block_hash: Some(B256::repeat_byte(1)),
+            block_number: Some(100 + index / 10),
+            block_timestamp: None,
+            transaction_hash: Some(B256::left_padding_from(&index.to_be_bytes())),
+            transaction_index: Some(0),
+            log_index: Some(index),
+            removed: false,
+        }
+    }
+
+    fn logs(count: u64) -> Vec<Log> {
+        (0..count)
+            .map(|index| log(index, vec![B256::repeat_byte(0xee)]))
+            .collect()
+    }
+
+    /// Tracks the contract of the logs, enabled or not.
+    async fn track(pool: &PgPool, enabled: bool) {
+        sqlx::query("INSERT INTO evm_chains (id, name, block_time) VALUES ($1, 'ethereum', 12)")
+            .bind(CHAIN_ID as i64)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO tracked_contracts (chain_id, address, contract_name, enabled)
+            VALUES ($1, $2, 'erc20', $3)
+            "#,
+        )
+        .bind(CHAIN_ID as i64)
+        .bind([0x47u8; 20])
+        .bind(enabled)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    async fn stored(pool: &PgPool) -> (i64, i64) {
+        sqlx::query_as("SELECT count(*), count(*) FILTER (WHERE unconfirmed) FROM evm_logs")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn inserts_logs_in_batches_flagging_unconfirmed_ones(pool: PgPool) {
+        let mut conn = pool.acquire().await.unwrap();
+
+        let inserted = insert_logs(&mut conn, CHAIN_ID, &logs(25), 101, 4)
+            .await
+            .unwrap();
+        assert_eq!(inserted, 25);
+        assert_eq!(stored(&pool).await, (25, 5));
+
+        // A batch size of zero still inserts, one log per statement
+        let inserted = insert_logs(&mut conn, CHAIN_ID, &logs(30), 101, 0)
+            .await
+            .unwrap();
+        assert_eq!(inserted, 5);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn splits_batches_above_the_bind_parameter_limit(pool: PgPool) {
+        let mut conn = pool.acquire().await.unwrap();
+        let count = MAX_INSERT_BATCH_SIZE as u64 + 1;
+
+        let inserted = insert_logs(&mut conn, CHAIN_ID, &logs(count), u64::MAX, usize::MAX)
+            .await
+            .unwrap();
+
+        assert!(MAX_INSERT_BATCH_SIZE * LOG_COLUMNS <= u16::MAX as usize);
+        assert_eq!(inserted, count);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn skips_anonymous_events(pool: PgPool) {
+        let mut conn = pool.acquire().await.unwrap();
+        let mut batch = logs(3);
+        batch.insert(1, log(3, Vec::new()));
+
+        let inserted = insert_logs(&mut conn, CHAIN_ID, &batch, u64::MAX, 2)
+            .await
+            .unwrap();
+        assert_eq!(inserted, 3);
+
+        let anonymous = insert_logs(&mut conn, CHAIN_ID, &[log(4, Vec::new())], u64::MAX, 2)
+            .await
+            .unwrap();
+        assert_eq!(anonymous, 0);
+        assert_eq!(stored(&pool).await, (3, 0));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn lists_logs_once_they_are_confirmed(pool: PgPool) {
+        track(&pool, true).await;
+        let repo = EVMLogsRepositoryImpl::new(pool.clone());
+        let mut conn = pool.acquire().await.unwrap();
+        insert_logs(&mut conn, CHAIN_ID, &logs(25), 101, 10)
+            .await
+            .unwrap();
+
+        let listed = repo.list(100).await.unwrap();
+        assert_eq!(listed.len(), 20);
+        assert!(listed.iter().all(|log| !log.unconfirmed));
+
+        assert_eq!(repo.count().await.unwrap(), Some(20));
+
+        repo.confirm_logs(CHAIN_ID, [0x47; 20], 102).await.unwrap();
+        assert_eq!(repo.list(100).await.unwrap().len(), 25);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn leaves_logs_of_disabled_contracts_out(pool: PgPool) {
+        track(&pool, false).await;
+        let repo = EVMLogsRepositoryImpl::new(pool.clone());
+        let mut conn = pool.acquire().await.unwrap();
+        insert_logs(&mut conn, CHAIN_ID, &logs(5), u64::MAX, 10)
+            .await
+            .unwrap();
+
+        assert!(repo.list(100).await.unwrap().is_empty());
+        assert_eq!(repo.count().await.unwrap(), None);
+    }
+}
@@ -1,7 +1,78 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
+use sqlx::{PgConnection, PgPool};
 
-use crate::services::{entities::evm_sync_logs::EVMSyncLogs, repository::EVMSyncLogsRepository};
+use crate::services::{
+    dtos::index_logs::{CursorAdvance, IngestedRange},
+    entities::{evm_ingested_ranges::EVMIngestedRanges, evm_sync_logs::EVMSyncLogs},
+    repository::EVMSyncLogsRepository,
+};
+
+/// Moves a cursor from the block it was at when its range was fetched, failing with
+/// `RowNotFound` once another task rewound or advanced it since.
+pub(crate) async fn advance_cursor(
+    conn: &mut PgConnection,
+    chain_id: u64,
+    cursor: &CursorAdvance,
+) -> Result<(), sqlx::Error> {
+    let advanced = sqlx::query(
+        r#"
+        UPDATE evm_sync_logs SET last_synced_block_number = $1
+        WHERE chain_id = $2 AND address = $3
+            AND last_synced_block_number IS NOT DISTINCT FROM $4
+        "#,
+    )
+    .bind(cursor.block_number as i64)
+    .bind(chain_id as i64)
+    .bind(cursor.address)
+    .bind(
+        cursor
+            .previous_block
+            .map(|block_number| block_number as i64),
+    )
+    .execute(&mut *conn)
+    .await?;
+
+    if advanced.rows_affected() == 0 {
+        return Err(sqlx::Error::RowNotFound);
+    }
+
+    Ok(())
+}
+
+/// Records the block ranges ingested for each contract.
+pub(crate) async fn record_ingested_ranges(
+    conn: &mut PgConnection,
+    chain_id: u64,
+    ranges: &[IngestedRange],
+) -> Result<(), sqlx::Error> {
+    if ranges.is_empty() {
+        return Ok(());
+    }
+
+    let mut addresses = Vec::with_capacity(ranges.len());
+    let mut from_blocks = Vec::with_capacity(ranges.len());
+    let mut to_blocks = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        addresses.push(range.address.to_vec());
+        from_blocks.push(range.from_block as i64);
+        to_blocks.push(range.to_block as i64);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO evm_ingested_ranges (chain_id, address, from_block, to_block)
+        SELECT $1, * FROM UNNEST($2::BYTEA[], $3::BIGINT[], $4::BIGINT[])
+        "#,
+    )
+    .bind(chain_id as i64)
+    .bind(addresses)
+    .bind(from_blocks)
+    .bind(to_blocks)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
 
 pub struct EVMSyncLogsRepositoryImpl {
     pool: PgPool,
@@ -21,15 +92,27 @@ impl EVMSyncLogsRepository for EVMSyncLogsRepositoryImpl {
             .await
     }
 
-    async fn find_by_address(&self, address: &str) -> Result<Option<EVMSyncLogs>, sqlx::Error> {
-        let query = r#"SELECT * FROM evm_sync_logs WHERE address = $1::BYTEA"#;
+    async fn find_by_chain_id(&self, chain_id: u64) -> Result<Vec<EVMSyncLogs>, sqlx::Error> {
+        let query = r#"SELECT * FROM evm_sync_logs WHERE chain_id = $1 ORDER BY created_at"#;
+
+        sqlx::query_as::<_, EVMSyncLogs>(query)
+            .bind(chain_id as i64)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn find_by_address(
+        &self,
+        address: &str,
+        chain_id: u64,
+    ) -> Result<Option<EVMSyncLogs>, sqlx::Error> {
+        let query = r#"SELECT * FROM evm_sync_logs WHERE address = $1::BYTEA AND chain_id = $2"#;
 
-        let result = sqlx::query_as::<_, EVMSyncLogs>(query)
+        sqlx::query_as::<_, EVMSyncLogs>(query)
             .bind(format!("\\x{address}"))
+            .bind(chain_id as i64)
             .fetch_optional(&self.pool)
-            .await;
-
-        result
+            .await
     }
 
     async fn create(
@@ -39,19 +122,17 @@ impl EVMSyncLogsRepository for EVMSyncLogsRepositoryImpl {
         last_synced_block_number: Option<i64>,
     ) -> Result<EVMSyncLogs, sqlx::Error> {
         let query = r#"
-            INSERT INTO evm_sync_logs (address, chain_id, last_synced_block_number)
-            VALUES ($1::BYTEA, $2, $3)
+            INSERT INTO evm_sync_logs (address, chain_id, last_synced_block_number, start_block)
+            VALUES ($1::BYTEA, $2, $3, COALESCE($3 + 1, 0))
             RETURNING *
             "#;
 
-        let result = sqlx::query_as::<_, EVMSyncLogs>(query)
+        sqlx::query_as::<_, EVMSyncLogs>(query)
             .bind(format!("\\x{address}"))
             .bind(chain_id as i64)
-            .bind(last_synced_block_number.or(Some(0)))
+            .bind(last_synced_block_number)
             .fetch_one(&self.pool)
-            .await;
-
-        result
+            .await
     }
 
     async fn find_or_create_by_address(
@@ -59,7 +140,7 @@ impl EVMSyncLogsRepository for EVMSyncLogsRepositoryImpl {
         address: &str,
         chain_id: u64,
     ) -> Result<EVMSyncLogs, sqlx::error::Error> {
-        let record = Self::find_by_address(self, address).await?;
+        let record = Self::find_by_address(self, address, chain_id).await?;
         if let Some(log_record) = record {
             return Ok(log_record);
         }
@@ -70,16 +151,143 @@ impl EVMSyncLogsRepository for EVMSyncLogsRepositoryImpl {
 
     async fn update_last_synced_block_number(
         &self,
+        chain_id: u64,
         address: [u8; 20],
         block_number: u64,
     ) -> Result<EVMSyncLogs, sqlx::Error> {
-        let query = r#"UPDATE evm_sync_logs SET last_synced_block_number = $1 WHERE address = $2 RETURNING *"#;
+        let query = r#"UPDATE evm_sync_logs SET last_synced_block_number = $1 WHERE chain_id = $2 AND address = $3 RETURNING *"#;
 
-        let result = sqlx::query_as::<_, EVMSyncLogs>(query)
+        sqlx::query_as::<_, EVMSyncLogs>(query)
             .bind(block_number as i64)
+            .bind(chain_id as i64)
             .bind(address)
             .fetch_one(&self.pool)
-            .await;
-        result
+            .await
+    }
+
+    async fn find_ingested_ranges(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+    ) -> Result<Vec<EVMIngestedRanges>, sqlx::Error> {
+        let query = r#"
+            SELECT * FROM evm_ingested_ranges
+            WHERE chain_id = $1 AND address = $2
+            ORDER BY from_block, to_block
+            "#;
+
+        sqlx::query_as::<_, EVMIngestedRanges>(query)
+            .bind(chain_id as i64)
+            .bind(address)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn delete_ingested_ranges_after(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        block_number: u64,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            r#"DELETE FROM evm_ingested_ranges WHERE chain_id = $1 AND address = $2 AND from_block > $3"#,
+        )
+        .bind(chain_id as i64)
+        .bind(address)
+        .bind(block_number as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r#"UPDATE evm_ingested_ranges SET to_block = $3 WHERE chain_id = $1 AND address = $2 AND to_block > $3"#,
+        )
+        .bind(chain_id as i64)
+        .bind(address)
+        .bind(block_number as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    async fn replace_ingested_ranges(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        ids: &[i64],
+        ranges: &[(u64, u64)],
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(r#"DELETE FROM evm_ingested_ranges WHERE id = ANY($1)"#)
+            .bind(ids)
+            .execute(&mut *tx)
+            .await?;
+
+        let ranges: Vec<IngestedRange> = ranges
+            .iter()
+            .map(|(from_block, to_block)| IngestedRange {
+                address,
+                from_block: *from_block,
+                to_block: *to_block,
+            })
+            .collect();
+        record_ingested_ranges(&mut tx, chain_id, &ranges).await?;
+
+        tx.commit().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "4752ba5dbc23f44d87826276bf6fd6b1c372ad24";
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn finds_cursors_of_an_address_by_chain(pool: PgPool) {
+        sqlx::query(
+            r#"
+            INSERT INTO evm_chains (id, name, block_time)
+            VALUES (1, 'ethereum', 12), (8453, 'base', 2), (84532, 'base-sepolia', 2)
+            "#,
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        let repo = EVMSyncLogsRepositoryImpl::new(pool);
+        repo.create(ADDRESS, 1, Some(100)).await.unwrap();
+        repo.create(ADDRESS, 8453, Some(200)).await.unwrap();
+        repo.create(ADDRESS, 84532, None).await.unwrap();
+
+        let mainnet = repo.find_by_address(ADDRESS, 1).await.unwrap().unwrap();
+        let base = repo.find_by_address(ADDRESS, 8453).await.unwrap().unwrap();
+        let sepolia = repo.find_by_address(ADDRESS, 84532).await.unwrap().unwrap();
+
+        assert_eq!(mainnet.last_synced_block_number, Some(100));
+        assert_eq!(base.last_synced_block_number, Some(200));
+        assert_eq!(sepolia.last_synced_block_number, None);
+        assert_eq!(
+            (mainnet.start_block, sepolia.start_block),
+            (Some(101), Some(0))
+        );
+        assert!(repo.find_by_address(ADDRESS, 10).await.unwrap().is_none());
+        assert_eq!(repo.find_by_chain_id(8453).await.unwrap().len(), 1);
+
+        let advanced = repo
+            .update_last_synced_block_number(1, base.address, 150)
+            .await
+            .unwrap();
+        assert_eq!(advanced.chain_id, 1);
+        assert_eq!(
+            repo.find_by_address(ADDRESS, 8453)
+                .await
+                .unwrap()
+                .unwrap()
+                .last_synced_block_number,
+            Some(200)
+        );
     }
 }
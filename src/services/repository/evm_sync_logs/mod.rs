@@ -1 +1,2 @@
+#[allow(clippy::module_inception)]
 pub mod evm_sync_logs;
@@ -0,0 +1,129 @@
+use async_trait::async_trait;
+use sqlx::{PgConnection, PgPool};
+
+use crate::services::{
+    entities::evm_transactions::EVMTransactions, repository::EVMTransactionsRepository,
+};
+
+/// Inserts `transactions`, moving those already stored to the block they are now in
+/// after a reorg.
+pub(crate) async fn upsert_transactions(
+    conn: &mut PgConnection,
+    chain_id: u64,
+    transactions: &[EVMTransactions],
+) -> Result<(), sqlx::Error> {
+    let query = r#"
+        INSERT INTO evm_transactions (
+            chain_id, transaction_hash, block_number, block_hash, transaction_index,
+            from_address, to_address, value, gas_used, effective_gas_price, status
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (chain_id, transaction_hash) DO UPDATE SET
+            block_number = EXCLUDED.block_number,
+            block_hash = EXCLUDED.block_hash,
+            transaction_index = EXCLUDED.transaction_index,
+            gas_used = EXCLUDED.gas_used,
+            effective_gas_price = EXCLUDED.effective_gas_price,
+            status = EXCLUDED.status
+        "#;
+
+    for transaction in transactions {
+        sqlx::query(query)
+            .bind(chain_id as i64)
+            .bind(transaction.transaction_hash)
+            .bind(transaction.block_number)
+            .bind(transaction.block_hash)
+            .bind(transaction.transaction_index)
+            .bind(transaction.from_address)
+            .bind(transaction.to_address)
+            .bind(&transaction.value)
+            .bind(&transaction.gas_used)
+            .bind(&transaction.effective_gas_price)
+            .bind(transaction.status)
+            .execute(&mut *conn)
+            .await?;
+    }
+
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct EVMTransactionsRepositoryImpl {
+    pool: PgPool,
+}
+
+impl EVMTransactionsRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl EVMTransactionsRepository for EVMTransactionsRepositoryImpl {
+    async fn upsert_bulk(
+        &self,
+        chain_id: u64,
+        transactions: Vec<EVMTransactions>,
+    ) -> Result<(), sqlx::Error> {
+        let mut conn = self.pool.acquire().await?;
+        upsert_transactions(&mut conn, chain_id, &transactions).await
+    }
+
+    async fn delete_after_block(
+        &self,
+        chain_id: u64,
+        block_number: u64,
+    ) -> Result<u64, sqlx::Error> {
+        let query = r#"DELETE FROM evm_transactions WHERE chain_id = $1 AND block_number > $2"#;
+
+        let result = sqlx::query(query)
+            .bind(chain_id as i64)
+            .bind(block_number as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::types::{BigDecimal, chrono};
+
+    use super::*;
+
+    fn transaction(block_number: i64) -> EVMTransactions {
+        EVMTransactions {
+            chain_id: 1,
+            transaction_hash: [block_number as u8; 32],
+            block_number,
+            block_hash: [block_number as u8 + 100; 32],
+            transaction_index: 0,
+            from_address: [0x01; 20],
+            to_address: Some([0x02; 20]),
+            value: BigDecimal::from(0),
+            gas_used: BigDecimal::from(21_000),
+            effective_gas_price: BigDecimal::from(1),
+            status: true,
+            created_at: chrono::NaiveDateTime::default(),
+        }
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn deletes_the_transactions_above_the_block(pool: PgPool) {
+        let repo = EVMTransactionsRepositoryImpl::new(pool.clone());
+        repo.upsert_bulk(1, vec![transaction(10), transaction(11), transaction(12)])
+            .await
+            .unwrap();
+
+        let deleted = repo.delete_after_block(1, 10).await.unwrap();
+
+        let remaining: Vec<i64> =
+            sqlx::query_scalar("SELECT block_number FROM evm_transactions ORDER BY block_number")
+                .fetch_all(&pool)
+                .await
+                .unwrap();
+        assert_eq!(deleted, 2);
+        assert_eq!(remaining, vec![10]);
+    }
+}
@@ -0,0 +1,192 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::services::{
+    dtos::index_logs::IngestRange,
+    repository::{
+        IngestionRepository,
+        evm_blocks::evm_block_repository::upsert_blocks,
+        evm_logs::evm_log_repository::insert_logs,
+        evm_sync_logs::evm_sync_logs::{advance_cursor, record_ingested_ranges},
+        evm_transactions::evm_transaction_repository::upsert_transactions,
+    },
+};
+
+#[derive(Clone)]
+pub struct IngestionRepositoryImpl {
+    pool: PgPool,
+
+    /// Logs inserted per statement
+    insert_batch_size: usize,
+}
+
+impl IngestionRepositoryImpl {
+    pub fn new(pool: PgPool, insert_batch_size: usize) -> Self {
+        Self {
+            pool,
+            insert_batch_size,
+        }
+    }
+}
+
+#[async_trait]
+impl IngestionRepository for IngestionRepositoryImpl {
+    async fn ingest_range(&self, range: IngestRange) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        upsert_blocks(&mut tx, range.chain_id, &range.blocks).await?;
+        upsert_transactions(&mut tx, range.chain_id, &range.transactions).await?;
+        let inserted = insert_logs(
+            &mut tx,
+            range.chain_id,
+            &range.logs,
+            range.confirmed_block,
+            self.insert_batch_size,
+        )
+        .await?;
+        for cursor in &range.cursors {
+            advance_cursor(&mut tx, range.chain_id, cursor).await?;
+        }
+        record_ingested_ranges(&mut tx, range.chain_id, &range.ranges).await?;
+
+        tx.commit().await?;
+
+        Ok(inserted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::{
+        primitives::{Address, B256, Bytes},
+        rpc::types::Log,
+    };
+    use sqlx::types::chrono::NaiveDateTime;
+
+    use super::*;
+    use crate::services::{
+        dtos::index_logs::{CursorAdvance, IngestedRange},
+        entities::evm_blocks::EVMBlocks,
+    };
+
+    const CHAIN_ID: u64 = 1;
+    const ADDRESS: [u8; 20] = [0x47; 20];
+
+    async fn track(pool: &PgPool) {
+        sqlx::query("INSERT INTO evm_chains (id, name, block_time) VALUES ($1, 'ethereum', 12)")
+            .bind(CHAIN_ID as i64)
+            .execute(pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO evm_sync_logs (address, chain_id, last_synced_block_number) VALUES ($1, $2, 10)",
+        )
+        .bind(ADDRESS)
+        .bind(CHAIN_ID as i64)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    fn block(number: u64) -> EVMBlocks {
+        EVMBlocks {
+            chain_id: CHAIN_ID as i64,
+            number: number as i64,
+            hash: B256::left_padding_from(&number.to_be_bytes()).0,
+            parent_hash: B256::left_padding_from(&(number - 1).to_be_bytes()).0,
+            timestamp: 1_700_000_000 + number as i64 * 12,
+            gas_used: 21_000,
+            base_fee_per_gas: None,
+            miner: [0; 20],
+            created_at: NaiveDateTime::default(),
+        }
+    }
+
+    fn log(block_number: u64, log_index: u64) -> Log {
+        Log {
+            inner: alloy::primitives::Log::new_unchecked(
+                Address::from(ADDRESS),
+                vec![B256::repeat_byte(0xee)],
+                Bytes::new(),
+            ),
+            block_hash: Some(B256::from(block(block_number).hash)),
+            block_number: Some(block_number),
+            block_timestamp: Some(block(block_number).timestamp as u64),
+            transaction_hash: Some(B256::repeat_byte(block_number as u8)),
+            transaction_index: Some(0),
+            log_index: Some(log_index),
+            removed: false,
+        }
+    }
+
+    /// Blocks 11 and 12 with a log each, advancing the cursor from `previous_block`.
+    fn range(previous_block: u64) -> IngestRange {
+        IngestRange {
+            chain_id: CHAIN_ID,
+            blocks: vec![block(11), block(12)],
+            logs: vec![log(11, 0), log(12, 0)],
+            confirmed_block: 12,
+            transactions: Vec::new(),
+            cursors: vec![CursorAdvance {
+                address: ADDRESS,
+                block_number: 12,
+                previous_block: Some(previous_block),
+            }],
+            ranges: vec![IngestedRange {
+                address: ADDRESS,
+                from_block: 11,
+                to_block: 12,
+            }],
+        }
+    }
+
+    async fn count(pool: &PgPool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT count(*) FROM {table}"))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    async fn cursor(pool: &PgPool) -> Option<i64> {
+        sqlx::query_scalar("SELECT last_synced_block_number FROM evm_sync_logs WHERE address = $1")
+            .bind(ADDRESS)
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn treats_logs_already_stored_as_ingested(pool: PgPool) {
+        track(&pool).await;
+        let repo = IngestionRepositoryImpl::new(pool.clone(), 1);
+
+        assert_eq!(repo.ingest_range(range(10)).await.unwrap(), 2);
+
+        // Ingested again without moving the cursor, as a backfill over it does
+        let again = IngestRange {
+            cursors: Vec::new(),
+            ..range(10)
+        };
+        assert_eq!(repo.ingest_range(again).await.unwrap(), 0);
+
+        assert_eq!(count(&pool, "evm_logs").await, 2);
+        assert_eq!(count(&pool, "evm_blocks").await, 2);
+        assert_eq!(cursor(&pool).await, Some(12));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn stores_nothing_once_the_cursor_moved(pool: PgPool) {
+        track(&pool).await;
+        let repo = IngestionRepositoryImpl::new(pool.clone(), 1);
+
+        assert!(matches!(
+            repo.ingest_range(range(9)).await,
+            Err(sqlx::Error::RowNotFound)
+        ));
+
+        assert_eq!(count(&pool, "evm_logs").await, 0);
+        assert_eq!(count(&pool, "evm_blocks").await, 0);
+        assert_eq!(count(&pool, "evm_ingested_ranges").await, 0);
+        assert_eq!(cursor(&pool).await, Some(10));
+    }
+}
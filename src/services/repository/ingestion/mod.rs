@@ -0,0 +1 @@
+pub mod ingestion_repository;
@@ -1,17 +1,39 @@
+pub mod erc20;
 pub mod errors;
+pub mod evm_backfill_chunks;
+pub mod evm_blocks;
 pub mod evm_chains;
 pub mod evm_logs;
 pub mod evm_sync_logs;
+pub mod evm_transactions;
+pub mod ingestion;
+pub mod nft;
+pub mod tracked_contracts;
+pub mod uniswap_v2;
+pub mod uniswap_v3;
 
+use crate::services::dtos::index_engine::{
+    Erc20ApprovalRequest, Erc20TransferRequest, Erc721TransferRequest, Erc1155TransferRequest,
+    Erc1155UriRequest, FeeAmountEnabledRequest, FlashRequest, LiquidityRequest,
+    OwnerChangedRequest, PairCreatedRequest, PairLiquidityRequest, PairSwapRequest,
+    PairSyncRequest, PoolCreatedRequest, PoolInitializedRequest, SwapRequest,
+};
+use crate::services::dtos::index_logs::IngestRange;
+use crate::services::entities::evm_backfill_chunks::EVMBackfillChunks;
+use crate::services::entities::evm_blocks::EVMBlocks;
 use crate::services::entities::evm_chains::EvmChains;
+use crate::services::entities::evm_ingested_ranges::EVMIngestedRanges;
 use crate::services::entities::evm_logs::EVMLogs;
 use crate::services::entities::evm_sync_logs::EVMSyncLogs;
+use crate::services::entities::evm_transactions::EVMTransactions;
+use crate::services::entities::tracked_contracts::TrackedContracts;
 use alloy::rpc::types::Log;
 use async_trait::async_trait;
 
 #[async_trait]
 pub trait EVMChainRepository {
     async fn fetch_by_id(&self, id: u64) -> Result<EvmChains, sqlx::Error>;
+    async fn find_enabled(&self) -> Result<Vec<EvmChains>, sqlx::Error>;
     async fn update_last_synced_block_number(
         &self,
         id: u64,
@@ -21,17 +43,51 @@ pub trait EVMChainRepository {
 
 #[async_trait]
 pub trait EVMLogsRepository {
-    async fn create_bulk(&self, logs: Vec<Log>) -> Result<(), sqlx::Error>;
-    async fn create(&self, log: Log) -> Result<EVMLogs, sqlx::Error>;
+    async fn create_bulk(
+        &self,
+        chain_id: u64,
+        logs: Vec<Log>,
+        confirmed_block: u64,
+    ) -> Result<(), sqlx::Error>;
+    async fn create(
+        &self,
+        chain_id: u64,
+        log: Log,
+        unconfirmed: bool,
+    ) -> Result<EVMLogs, sqlx::Error>;
+    /// Confirmed logs in chain order. Logs within the reorg depth of the head stay
+    /// unconfirmed until then, so that a reorg the listener detects deletes them before
+    /// any state is derived from them. Deeper reorgs are not rolled back out of that state.
+    /// Logs of disabled contracts are left for when they are enabled again.
     async fn list(&self, page_size: i64) -> Result<Vec<EVMLogs>, sqlx::Error>;
     async fn delete(&self, id: i32) -> Result<(), sqlx::Error>;
+    async fn delete_by_address_after_block(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        block_number: u64,
+    ) -> Result<u64, sqlx::Error>;
+    async fn confirm_logs(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        block_number: u64,
+    ) -> Result<u64, sqlx::Error>;
+    /// Number of the logs `list` returns, None when there are none.
     async fn count(&self) -> Result<Option<i64>, sqlx::Error>;
 }
 
 #[async_trait]
 pub trait EVMSyncLogsRepository {
     async fn find_all(&self) -> Result<Vec<EVMSyncLogs>, sqlx::Error>;
-    async fn find_by_address(&self, address: &str) -> Result<Option<EVMSyncLogs>, sqlx::Error>;
+    async fn find_by_chain_id(&self, chain_id: u64) -> Result<Vec<EVMSyncLogs>, sqlx::Error>;
+    async fn find_by_address(
+        &self,
+        address: &str,
+        chain_id: u64,
+    ) -> Result<Option<EVMSyncLogs>, sqlx::Error>;
+    /// Creates a cursor starting after `last_synced_block_number`, or at block 0,
+    /// and records that first block as its start block.
     async fn create(
         &self,
         address: &str,
@@ -45,7 +101,181 @@ pub trait EVMSyncLogsRepository {
     ) -> Result<EVMSyncLogs, sqlx::error::Error>;
     async fn update_last_synced_block_number(
         &self,
+        chain_id: u64,
         address: [u8; 20],
         block_number: u64,
     ) -> Result<EVMSyncLogs, sqlx::Error>;
+    async fn find_ingested_ranges(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+    ) -> Result<Vec<EVMIngestedRanges>, sqlx::Error>;
+    /// Drops the coverage above `block_number`, cutting the range spanning it.
+    async fn delete_ingested_ranges_after(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        block_number: u64,
+    ) -> Result<(), sqlx::Error>;
+    /// Replaces the recorded ranges `ids` with `ranges` at once.
+    async fn replace_ingested_ranges(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        ids: &[i64],
+        ranges: &[(u64, u64)],
+    ) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait EVMBlocksRepository {
+    /// Up to `limit` blocks at or below `up_to_block`, newest first.
+    async fn find_recent(
+        &self,
+        chain_id: u64,
+        up_to_block: u64,
+        limit: i64,
+    ) -> Result<Vec<EVMBlocks>, sqlx::Error>;
+    async fn find_by_numbers(
+        &self,
+        chain_id: u64,
+        numbers: &[u64],
+    ) -> Result<Vec<EVMBlocks>, sqlx::Error>;
+    /// Seconds between the `sample_size` newest stored blocks, on average. None until
+    /// two blocks are stored.
+    async fn average_block_time(
+        &self,
+        chain_id: u64,
+        sample_size: i64,
+    ) -> Result<Option<f64>, sqlx::Error>;
+    async fn delete_after(&self, chain_id: u64, block_number: u64) -> Result<u64, sqlx::Error>;
+}
+
+#[async_trait]
+pub trait EVMTransactionsRepository {
+    async fn upsert_bulk(
+        &self,
+        chain_id: u64,
+        transactions: Vec<EVMTransactions>,
+    ) -> Result<(), sqlx::Error>;
+
+    /// Deletes the transactions mined above `block_number`, whichever contract's logs
+    /// they were stored for.
+    async fn delete_after_block(
+        &self,
+        chain_id: u64,
+        block_number: u64,
+    ) -> Result<u64, sqlx::Error>;
+}
+
+#[async_trait]
+pub trait IngestionRepository {
+    /// Stores the blocks, transactions and logs of a block range and advances its
+    /// cursors in one database transaction. Logs already stored are skipped, so a range can be
+    /// ingested again after a failure. Returns the number of logs inserted, or
+    /// `RowNotFound` without storing anything when a cursor moved since the range was fetched.
+    async fn ingest_range(&self, range: IngestRange) -> Result<u64, sqlx::Error>;
+}
+
+#[async_trait]
+pub trait EVMBackfillChunksRepository {
+    /// Records the chunks, leaving those already recorded as they are.
+    async fn create_bulk(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        chunks: Vec<(u64, u64)>,
+    ) -> Result<(), sqlx::Error>;
+    async fn find_pending(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+    ) -> Result<Vec<EVMBackfillChunks>, sqlx::Error>;
+    async fn complete(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        from_block: u64,
+    ) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait TrackedContractsRepository {
+    /// Enabled contracts of every chain, or of `chain_id` only.
+    async fn find_enabled(
+        &self,
+        chain_id: Option<u64>,
+    ) -> Result<Vec<TrackedContracts>, sqlx::Error>;
+    /// Tracks a contract discovered through `parent_address`, unless it is already
+    /// tracked, indexing it unconfirmed when its parent is.
+    async fn create_discovered(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        contract_name: &str,
+        deployment: Option<&str>,
+        parent_address: [u8; 20],
+        start_block: u64,
+    ) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait UniswapV3Repository {
+    /// Each record is stored once, so reprocessing a log leaves it unchanged.
+    async fn create_pool(&self, pool: &PoolCreatedRequest) -> Result<(), sqlx::Error>;
+    async fn create_factory_owner(&self, owner: &OwnerChangedRequest) -> Result<(), sqlx::Error>;
+    async fn create_fee_tier(&self, fee_tier: &FeeAmountEnabledRequest) -> Result<(), sqlx::Error>;
+
+    /// Pool events are applied once each, keyed by their log. Volumes and fees add up
+    /// whatever order the events arrive in, while the price, tick and liquidity only move
+    /// for events newer than the last one applied to them, as a swap reports the
+    /// liquidity every older mint and burn left.
+    async fn initialize_pool(&self, initialize: &PoolInitializedRequest)
+    -> Result<(), sqlx::Error>;
+    /// Stores the swap and applies it to the pool state at once, the in-range liquidity
+    /// it reports being set like the price.
+    async fn record_swap(&self, swap: &SwapRequest) -> Result<(), sqlx::Error>;
+    /// Mints and burns older than the last swap are stored without moving the liquidity
+    /// it already reports.
+    async fn add_liquidity(&self, mint: &LiquidityRequest) -> Result<(), sqlx::Error>;
+    async fn remove_liquidity(&self, burn: &LiquidityRequest) -> Result<(), sqlx::Error>;
+    async fn record_flash(&self, flash: &FlashRequest) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait UniswapV2Repository {
+    /// Each record is stored once, so reprocessing a log leaves it unchanged.
+    async fn create_pair(&self, pair: &PairCreatedRequest) -> Result<(), sqlx::Error>;
+    /// Sets the reserves, unless a later Sync already did.
+    async fn sync_reserves(&self, sync: &PairSyncRequest) -> Result<(), sqlx::Error>;
+    async fn record_swap(&self, swap: &PairSwapRequest) -> Result<(), sqlx::Error>;
+    async fn record_mint(&self, mint: &PairLiquidityRequest) -> Result<(), sqlx::Error>;
+    async fn record_burn(&self, burn: &PairLiquidityRequest) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait Erc20Repository {
+    /// Stores the transfer and applies it to the balances and total supply at once.
+    /// A transfer already stored is not applied again.
+    async fn record_transfer(&self, transfer: &Erc20TransferRequest) -> Result<(), sqlx::Error>;
+    /// Sets the allowance, unless a later approval already did.
+    async fn record_approval(&self, approval: &Erc20ApprovalRequest) -> Result<(), sqlx::Error>;
+}
+
+#[async_trait]
+pub trait NftRepository {
+    /// Stores the transfer and makes `to` the owner of the token, unless a later
+    /// transfer already did.
+    async fn record_erc721_transfer(
+        &self,
+        transfer: &Erc721TransferRequest,
+    ) -> Result<(), sqlx::Error>;
+    /// Stores the transfer and applies it to the balances at once.
+    /// A transfer already stored is not applied again.
+    async fn record_erc1155_transfer(
+        &self,
+        transfer: &Erc1155TransferRequest,
+    ) -> Result<(), sqlx::Error>;
+    /// Sets the URI of the token id, unless a later URI event already did.
+    async fn set_erc1155_uri(&self, uri: &Erc1155UriRequest) -> Result<(), sqlx::Error>;
 }
@@ -0,0 +1 @@
+pub mod nft_repository;
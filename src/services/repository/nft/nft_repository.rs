@@ -0,0 +1,211 @@
+use async_trait::async_trait;
+use sqlx::{PgConnection, PgPool, types::BigDecimal};
+
+use crate::services::{
+    dtos::index_engine::{
+        Erc721TransferRequest, Erc1155TransferRequest, Erc1155UriRequest, EventSource,
+    },
+    repository::NftRepository,
+};
+
+/// Tokens are minted from and burned to the zero address.
+const ZERO_ADDRESS: [u8; 20] = [0; 20];
+
+/// A row of `nft_transfers`, `amount` of `token_id` moving from `from` to `to`.
+struct Transfer<'a> {
+    source: &'a EventSource,
+    batch_index: u32,
+    operator: Option<[u8; 20]>,
+    from: [u8; 20],
+    to: [u8; 20],
+    token_id: &'a BigDecimal,
+    amount: &'a BigDecimal,
+}
+
+/// Stores `transfer` in the history; `false` when it was stored before.
+async fn insert_transfer(
+    conn: &mut PgConnection,
+    transfer: Transfer<'_>,
+) -> Result<bool, sqlx::Error> {
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO nft_transfers (
+            chain_id, token_address, block_number, log_index, batch_index, transaction_hash,
+            block_timestamp, operator, from_address, to_address, token_id, amount
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+        ON CONFLICT (chain_id, token_address, block_number, log_index, batch_index) DO NOTHING
+        "#,
+    )
+    .bind(transfer.source.chain_id as i64)
+    .bind(transfer.source.address)
+    .bind(transfer.source.block_number as i64)
+    .bind(transfer.source.log_index as i64)
+    .bind(transfer.batch_index as i32)
+    .bind(transfer.source.transaction_hash)
+    .bind(
+        transfer
+            .source
+            .block_timestamp
+            .map(|timestamp| timestamp as i64),
+    )
+    .bind(transfer.operator)
+    .bind(transfer.from)
+    .bind(transfer.to)
+    .bind(transfer.token_id)
+    .bind(transfer.amount)
+    .execute(&mut *conn)
+    .await?
+    .rows_affected();
+
+    Ok(inserted == 1)
+}
+
+/// Adds `delta` to the balance of `holder` in token `transfer.id`.
+async fn change_balance(
+    conn: &mut PgConnection,
+    transfer: &Erc1155TransferRequest,
+    holder: [u8; 20],
+    delta: BigDecimal,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO erc1155_balances (chain_id, token_address, holder, token_id, balance)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (chain_id, token_address, holder, token_id) DO UPDATE SET
+            balance = erc1155_balances.balance + EXCLUDED.balance
+        "#,
+    )
+    .bind(transfer.source.chain_id as i64)
+    .bind(transfer.source.address)
+    .bind(holder)
+    .bind(&transfer.id)
+    .bind(delta)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Clone)]
+pub struct NftRepositoryImpl {
+    pool: PgPool,
+}
+
+impl NftRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl NftRepository for NftRepositoryImpl {
+    async fn record_erc721_transfer(
+        &self,
+        transfer: &Erc721TransferRequest,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        insert_transfer(
+            &mut tx,
+            Transfer {
+                source: &transfer.source,
+                batch_index: 0,
+                operator: None,
+                from: transfer.from,
+                to: transfer.to,
+                token_id: &transfer.token_id,
+                amount: &BigDecimal::from(1),
+            },
+        )
+        .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO erc721_owners (
+                chain_id, token_address, token_id, owner, block_number, log_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (chain_id, token_address, token_id) DO UPDATE SET
+                owner = EXCLUDED.owner,
+                block_number = EXCLUDED.block_number,
+                log_index = EXCLUDED.log_index
+            WHERE (erc721_owners.block_number, erc721_owners.log_index)
+                < (EXCLUDED.block_number, EXCLUDED.log_index)
+            "#,
+        )
+        .bind(transfer.source.chain_id as i64)
+        .bind(transfer.source.address)
+        .bind(&transfer.token_id)
+        .bind(transfer.to)
+        .bind(transfer.source.block_number as i64)
+        .bind(transfer.source.log_index as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    async fn record_erc1155_transfer(
+        &self,
+        transfer: &Erc1155TransferRequest,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = insert_transfer(
+            &mut tx,
+            Transfer {
+                source: &transfer.source,
+                batch_index: transfer.batch_index,
+                operator: Some(transfer.operator),
+                from: transfer.from,
+                to: transfer.to,
+                token_id: &transfer.id,
+                amount: &transfer.value,
+            },
+        )
+        .await?;
+
+        // A transfer stored before was applied already
+        if !inserted {
+            return tx.commit().await;
+        }
+
+        if transfer.from != ZERO_ADDRESS {
+            change_balance(&mut tx, transfer, transfer.from, -transfer.value.clone()).await?;
+        }
+        if transfer.to != ZERO_ADDRESS {
+            change_balance(&mut tx, transfer, transfer.to, transfer.value.clone()).await?;
+        }
+
+        tx.commit().await
+    }
+
+    async fn set_erc1155_uri(&self, uri: &Erc1155UriRequest) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO erc1155_uris (
+                chain_id, token_address, token_id, uri, block_number, log_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (chain_id, token_address, token_id) DO UPDATE SET
+                uri = EXCLUDED.uri,
+                block_number = EXCLUDED.block_number,
+                log_index = EXCLUDED.log_index
+            WHERE (erc1155_uris.block_number, erc1155_uris.log_index)
+                < (EXCLUDED.block_number, EXCLUDED.log_index)
+            "#;
+
+        sqlx::query(query)
+            .bind(uri.source.chain_id as i64)
+            .bind(uri.source.address)
+            .bind(&uri.id)
+            // Postgres text cannot hold NUL, which nothing stops a contract from emitting
+            .bind(uri.uri.replace('\0', ""))
+            .bind(uri.source.block_number as i64)
+            .bind(uri.source.log_index as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
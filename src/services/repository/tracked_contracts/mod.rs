@@ -0,0 +1 @@
+pub mod tracked_contract_repository;
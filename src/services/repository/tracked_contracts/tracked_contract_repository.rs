@@ -0,0 +1,146 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::services::{
+    entities::tracked_contracts::TrackedContracts, repository::TrackedContractsRepository,
+};
+
+#[derive(Clone)]
+pub struct TrackedContractsRepositoryImpl {
+    pool: PgPool,
+}
+
+impl TrackedContractsRepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TrackedContractsRepository for TrackedContractsRepositoryImpl {
+    async fn find_enabled(
+        &self,
+        chain_id: Option<u64>,
+    ) -> Result<Vec<TrackedContracts>, sqlx::Error> {
+        let query = r#"
+            SELECT * FROM tracked_contracts
+            WHERE enabled AND ($1::BIGINT IS NULL OR chain_id = $1)
+            ORDER BY created_at
+            "#;
+
+        sqlx::query_as::<_, TrackedContracts>(query)
+            .bind(chain_id.map(|chain_id| chain_id as i64))
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn create_discovered(
+        &self,
+        chain_id: u64,
+        address: [u8; 20],
+        contract_name: &str,
+        deployment: Option<&str>,
+        parent_address: [u8; 20],
+        start_block: u64,
+    ) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO tracked_contracts (
+                chain_id, address, contract_name, deployment, start_block, parent_address,
+                unconfirmed
+            )
+            SELECT $1, $2, $3, $4, $5, $6, COALESCE(
+                (SELECT unconfirmed FROM tracked_contracts WHERE chain_id = $1 AND address = $6),
+                FALSE
+            )
+            ON CONFLICT (chain_id, address) DO NOTHING
+            "#;
+
+        sqlx::query(query)
+            .bind(chain_id as i64)
+            .bind(address)
+            .bind(contract_name)
+            .bind(deployment)
+            .bind(start_block as i64)
+            .bind(parent_address)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn track(pool: &PgPool, chain_id: i64, address: [u8; 20], enabled: bool) {
+        sqlx::query(
+            r#"
+            INSERT INTO tracked_contracts (chain_id, address, contract_name, enabled)
+            VALUES ($1, $2, 'uniswap_v3_factory', $3)
+            "#,
+        )
+        .bind(chain_id)
+        .bind(address)
+        .bind(enabled)
+        .execute(pool)
+        .await
+        .unwrap();
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn finds_enabled_contracts_of_every_chain_or_one(pool: PgPool) {
+        sqlx::query(
+            "INSERT INTO evm_chains (id, name, block_time) VALUES (1, 'ethereum', 12), (8453, 'base', 2)",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        track(&pool, 1, [0x11; 20], true).await;
+        track(&pool, 1, [0x22; 20], false).await;
+        track(&pool, 8453, [0x11; 20], true).await;
+        let repo = TrackedContractsRepositoryImpl::new(pool);
+
+        let all: Vec<(i64, [u8; 20])> = repo
+            .find_enabled(None)
+            .await
+            .unwrap()
+            .iter()
+            .map(|contract| (contract.chain_id, contract.address))
+            .collect();
+        assert_eq!(all, vec![(1, [0x11; 20]), (8453, [0x11; 20])]);
+
+        let base = repo.find_enabled(Some(8453)).await.unwrap();
+        assert_eq!(base.len(), 1);
+        assert_eq!(base[0].chain_id, 8453);
+
+        assert!(repo.find_enabled(Some(10)).await.unwrap().is_empty());
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn discovered_contracts_inherit_unconfirmed_indexing(pool: PgPool) {
+        sqlx::query("INSERT INTO evm_chains (id, name, block_time) VALUES (1, 'ethereum', 12)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        track(&pool, 1, [0x11; 20], true).await;
+        sqlx::query("UPDATE tracked_contracts SET unconfirmed = TRUE, events = '{PoolCreated}'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let repo = TrackedContractsRepositoryImpl::new(pool);
+
+        repo.create_discovered(1, [0x22; 20], "uniswap_v3_pool", None, [0x11; 20], 100)
+            .await
+            .unwrap();
+
+        let contracts = repo.find_enabled(Some(1)).await.unwrap();
+        let pool = contracts
+            .iter()
+            .find(|contract| contract.address == [0x22; 20])
+            .unwrap();
+        assert!(pool.unconfirmed);
+        assert_eq!(pool.events, None);
+        assert_eq!(contracts[0].events, Some(vec!["PoolCreated".to_string()]));
+    }
+}
@@ -0,0 +1 @@
+pub mod uniswap_v2_repository;
@@ -0,0 +1,284 @@
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::services::{
+    dtos::index_engine::{
+        PairCreatedRequest, PairLiquidityRequest, PairSwapRequest, PairSyncRequest,
+    },
+    repository::UniswapV2Repository,
+};
+
+#[derive(Clone)]
+pub struct UniswapV2RepositoryImpl {
+    pool: PgPool,
+}
+
+impl UniswapV2RepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores a Mint or Burn, `kind` naming which.
+    async fn record_liquidity(
+        &self,
+        kind: &str,
+        liquidity: &PairLiquidityRequest,
+    ) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO uniswap_v2_liquidity_events (
+                chain_id, pair_address, block_number, log_index, transaction_hash,
+                block_timestamp, kind, sender, recipient, amount0, amount1
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (chain_id, pair_address, block_number, log_index) DO NOTHING
+            "#;
+
+        sqlx::query(query)
+            .bind(liquidity.source.chain_id as i64)
+            .bind(liquidity.source.address)
+            .bind(liquidity.source.block_number as i64)
+            .bind(liquidity.source.log_index as i64)
+            .bind(liquidity.source.transaction_hash)
+            .bind(
+                liquidity
+                    .source
+                    .block_timestamp
+                    .map(|timestamp| timestamp as i64),
+            )
+            .bind(kind)
+            .bind(liquidity.sender)
+            .bind(liquidity.recipient)
+            .bind(&liquidity.amount0)
+            .bind(&liquidity.amount1)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UniswapV2Repository for UniswapV2RepositoryImpl {
+    async fn create_pair(&self, pair: &PairCreatedRequest) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO uniswap_v2_pairs (
+                chain_id, factory_address, pair_address, token0, token1,
+                block_number, transaction_hash, log_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (chain_id, factory_address, pair_address) DO NOTHING
+            "#;
+
+        sqlx::query(query)
+            .bind(pair.source.chain_id as i64)
+            .bind(pair.source.address)
+            .bind(pair.pair)
+            .bind(pair.token0)
+            .bind(pair.token1)
+            .bind(pair.source.block_number as i64)
+            .bind(pair.source.transaction_hash)
+            .bind(pair.source.log_index as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn sync_reserves(&self, sync: &PairSyncRequest) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO uniswap_v2_pair_states (
+                chain_id, pair_address, reserve0, reserve1, block_number, log_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (chain_id, pair_address) DO UPDATE SET
+                reserve0 = EXCLUDED.reserve0,
+                reserve1 = EXCLUDED.reserve1,
+                block_number = EXCLUDED.block_number,
+                log_index = EXCLUDED.log_index
+            WHERE (uniswap_v2_pair_states.block_number, uniswap_v2_pair_states.log_index)
+                < (EXCLUDED.block_number, EXCLUDED.log_index)
+            "#;
+
+        sqlx::query(query)
+            .bind(sync.source.chain_id as i64)
+            .bind(sync.source.address)
+            .bind(&sync.reserve0)
+            .bind(&sync.reserve1)
+            .bind(sync.source.block_number as i64)
+            .bind(sync.source.log_index as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn record_swap(&self, swap: &PairSwapRequest) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO uniswap_v2_swaps (
+                chain_id, pair_address, block_number, log_index, transaction_hash,
+                block_timestamp, sender, recipient, amount0_in, amount1_in,
+                amount0_out, amount1_out
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (chain_id, pair_address, block_number, log_index) DO NOTHING
+            "#;
+
+        sqlx::query(query)
+            .bind(swap.source.chain_id as i64)
+            .bind(swap.source.address)
+            .bind(swap.source.block_number as i64)
+            .bind(swap.source.log_index as i64)
+            .bind(swap.source.transaction_hash)
+            .bind(
+                swap.source
+                    .block_timestamp
+                    .map(|timestamp| timestamp as i64),
+            )
+            .bind(swap.sender)
+            .bind(swap.recipient)
+            .bind(&swap.amount0_in)
+            .bind(&swap.amount1_in)
+            .bind(&swap.amount0_out)
+            .bind(&swap.amount1_out)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn record_mint(&self, mint: &PairLiquidityRequest) -> Result<(), sqlx::Error> {
+        self.record_liquidity("mint", mint).await
+    }
+
+    async fn record_burn(&self, burn: &PairLiquidityRequest) -> Result<(), sqlx::Error> {
+        self.record_liquidity("burn", burn).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sqlx::types::BigDecimal;
+
+    use crate::services::dtos::index_engine::EventSource;
+
+    use super::*;
+
+    const CHAIN_ID: u64 = 1;
+    const FACTORY: [u8; 20] = [0x44; 20];
+    const PAIR: [u8; 20] = [0x55; 20];
+
+    fn source(address: [u8; 20], block_number: u64, log_index: u64) -> EventSource {
+        EventSource {
+            chain_id: CHAIN_ID,
+            address,
+            block_number,
+            transaction_hash: [block_number as u8; 32],
+            log_index,
+            block_timestamp: Some(1_700_000_000),
+        }
+    }
+
+    fn sync(block_number: u64, reserve0: i64, reserve1: i64) -> PairSyncRequest {
+        PairSyncRequest {
+            source: source(PAIR, block_number, 0),
+            reserve0: BigDecimal::from(reserve0),
+            reserve1: BigDecimal::from(reserve1),
+        }
+    }
+
+    async fn count(pool: &PgPool, table: &str) -> i64 {
+        sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table}"))
+            .fetch_one(pool)
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn stores_each_pair_once(pool: PgPool) {
+        let repo = UniswapV2RepositoryImpl::new(pool.clone());
+        let pair = PairCreatedRequest {
+            source: source(FACTORY, 1, 0),
+            pair: PAIR,
+            token0: [0x0a; 20],
+            token1: [0x0b; 20],
+        };
+
+        repo.create_pair(&pair).await.unwrap();
+        repo.create_pair(&pair).await.unwrap();
+
+        let (token0, token1): (Vec<u8>, Vec<u8>) = sqlx::query_as(
+            "SELECT token0, token1 FROM uniswap_v2_pairs WHERE factory_address = $1 AND pair_address = $2",
+        )
+        .bind(FACTORY)
+        .bind(PAIR)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((token0, token1), (vec![0x0a; 20], vec![0x0b; 20]));
+        assert_eq!(count(&pool, "uniswap_v2_pairs").await, 1);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn keeps_the_reserves_of_the_latest_sync(pool: PgPool) {
+        let repo = UniswapV2RepositoryImpl::new(pool.clone());
+
+        repo.sync_reserves(&sync(10, 1_000, 2_000)).await.unwrap();
+        repo.sync_reserves(&sync(5, 1, 2)).await.unwrap();
+        repo.sync_reserves(&sync(11, 1_100, 1_900)).await.unwrap();
+
+        let (reserve0, reserve1, block_number): (BigDecimal, BigDecimal, i64) = sqlx::query_as(
+            "SELECT reserve0, reserve1, block_number FROM uniswap_v2_pair_states WHERE pair_address = $1",
+        )
+        .bind(PAIR)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(reserve0, BigDecimal::from(1_100));
+        assert_eq!(reserve1, BigDecimal::from(1_900));
+        assert_eq!(block_number, 11);
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn stores_replayed_swaps_mints_and_burns_once(pool: PgPool) {
+        let repo = UniswapV2RepositoryImpl::new(pool.clone());
+        let swap = PairSwapRequest {
+            source: source(PAIR, 10, 0),
+            sender: [0x01; 20],
+            amount0_in: BigDecimal::from(100),
+            amount1_in: BigDecimal::from(0),
+            amount0_out: BigDecimal::from(0),
+            amount1_out: BigDecimal::from(99),
+            recipient: [0x02; 20],
+        };
+        let liquidity = |log_index, recipient| PairLiquidityRequest {
+            source: source(PAIR, 10, log_index),
+            sender: [0x01; 20],
+            recipient,
+            amount0: BigDecimal::from(5),
+            amount1: BigDecimal::from(6),
+        };
+
+        for _ in 0..2 {
+            repo.record_swap(&swap).await.unwrap();
+            repo.record_mint(&liquidity(1, None)).await.unwrap();
+            repo.record_burn(&liquidity(2, Some([0x02; 20])))
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(count(&pool, "uniswap_v2_swaps").await, 1);
+        let kinds: Vec<(String, Option<Vec<u8>>)> = sqlx::query_as(
+            "SELECT kind, recipient FROM uniswap_v2_liquidity_events ORDER BY log_index",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            kinds,
+            vec![
+                ("mint".to_string(), None),
+                ("burn".to_string(), Some(vec![0x02; 20]))
+            ]
+        );
+    }
+}
@@ -0,0 +1 @@
+pub mod uniswap_v3_repository;
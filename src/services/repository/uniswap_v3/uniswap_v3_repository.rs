@@ -0,0 +1,483 @@
+use async_trait::async_trait;
+use sqlx::{PgPool, types::BigDecimal};
+
+use crate::services::{
+    dtos::index_engine::{
+        FeeAmountEnabledRequest, FlashRequest, LiquidityRequest, OwnerChangedRequest,
+        PoolCreatedRequest, PoolInitializedRequest, SwapRequest,
+    },
+    repository::UniswapV3Repository,
+};
+
+#[derive(Clone)]
+pub struct UniswapV3RepositoryImpl {
+    pool: PgPool,
+}
+
+impl UniswapV3RepositoryImpl {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Stores the mint or burn and adds `delta` to the pool's liquidity when the range
+    /// spans its current tick, unless it was stored already.
+    async fn change_liquidity(
+        &self,
+        liquidity: &LiquidityRequest,
+        delta: BigDecimal,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO uniswap_v3_liquidity_changes (
+                chain_id, pool_address, block_number, log_index, transaction_hash, owner,
+                tick_lower, tick_upper, amount, amount0, amount1
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (chain_id, pool_address, block_number, log_index) DO NOTHING
+            "#,
+        )
+        .bind(liquidity.source.chain_id as i64)
+        .bind(liquidity.source.address)
+        .bind(liquidity.source.block_number as i64)
+        .bind(liquidity.source.log_index as i64)
+        .bind(liquidity.source.transaction_hash)
+        .bind(liquidity.owner)
+        .bind(liquidity.tick_lower)
+        .bind(liquidity.tick_upper)
+        .bind(&delta)
+        .bind(&liquidity.amount0)
+        .bind(&liquidity.amount1)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // A change older than the last swap is part of the liquidity it reported
+        if inserted > 0 {
+            sqlx::query(
+                r#"
+                UPDATE uniswap_v3_pool_states SET
+                    liquidity = liquidity + CASE WHEN $3 <= tick AND tick < $4 THEN $5 ELSE 0 END,
+                    block_number = $6,
+                    log_index = $7
+                WHERE chain_id = $1 AND pool_address = $2 AND (block_number, log_index) < ($6, $7)
+                "#,
+            )
+            .bind(liquidity.source.chain_id as i64)
+            .bind(liquidity.source.address)
+            .bind(liquidity.tick_lower)
+            .bind(liquidity.tick_upper)
+            .bind(delta)
+            .bind(liquidity.source.block_number as i64)
+            .bind(liquidity.source.log_index as i64)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+}
+
+#[async_trait]
+impl UniswapV3Repository for UniswapV3RepositoryImpl {
+    async fn create_pool(&self, pool: &PoolCreatedRequest) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO uniswap_v3_pools (
+                chain_id, factory_address, pool_address, token0, token1, fee, tick_spacing,
+                block_number, transaction_hash, log_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (chain_id, factory_address, pool_address) DO NOTHING
+            "#;
+
+        sqlx::query(query)
+            .bind(pool.source.chain_id as i64)
+            .bind(pool.source.address)
+            .bind(pool.pool)
+            .bind(pool.token0)
+            .bind(pool.token1)
+            .bind(pool.fee as i32)
+            .bind(pool.tick_spacing)
+            .bind(pool.source.block_number as i64)
+            .bind(pool.source.transaction_hash)
+            .bind(pool.source.log_index as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn create_factory_owner(&self, owner: &OwnerChangedRequest) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO uniswap_v3_factory_owners (
+                chain_id, factory_address, previous_owner, new_owner, block_number,
+                transaction_hash, log_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (chain_id, factory_address, block_number, log_index) DO NOTHING
+            "#;
+
+        sqlx::query(query)
+            .bind(owner.source.chain_id as i64)
+            .bind(owner.source.address)
+            .bind(owner.previous)
+            .bind(owner.new_owner)
+            .bind(owner.source.block_number as i64)
+            .bind(owner.source.transaction_hash)
+            .bind(owner.source.log_index as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn create_fee_tier(&self, fee_tier: &FeeAmountEnabledRequest) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO uniswap_v3_fee_tiers (
+                chain_id, factory_address, fee, tick_spacing, block_number, transaction_hash,
+                log_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (chain_id, factory_address, fee) DO NOTHING
+            "#;
+
+        sqlx::query(query)
+            .bind(fee_tier.source.chain_id as i64)
+            .bind(fee_tier.source.address)
+            .bind(fee_tier.fee as i32)
+            .bind(fee_tier.tick_spacing)
+            .bind(fee_tier.source.block_number as i64)
+            .bind(fee_tier.source.transaction_hash)
+            .bind(fee_tier.source.log_index as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn initialize_pool(
+        &self,
+        initialize: &PoolInitializedRequest,
+    ) -> Result<(), sqlx::Error> {
+        let query = r#"
+            INSERT INTO uniswap_v3_pool_states (
+                chain_id, pool_address, sqrt_price_x96, tick, block_number, log_index
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (chain_id, pool_address) DO UPDATE SET
+                sqrt_price_x96 = EXCLUDED.sqrt_price_x96,
+                tick = EXCLUDED.tick,
+                block_number = EXCLUDED.block_number,
+                log_index = EXCLUDED.log_index
+            WHERE (uniswap_v3_pool_states.block_number, uniswap_v3_pool_states.log_index)
+                < (EXCLUDED.block_number, EXCLUDED.log_index)
+            "#;
+
+        sqlx::query(query)
+            .bind(initialize.source.chain_id as i64)
+            .bind(initialize.source.address)
+            .bind(&initialize.sqrt_price_x96)
+            .bind(initialize.tick)
+            .bind(initialize.source.block_number as i64)
+            .bind(initialize.source.log_index as i64)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn record_swap(&self, swap: &SwapRequest) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO uniswap_v3_swaps (
+                chain_id, pool_address, block_number, log_index, transaction_hash,
+                block_timestamp, sender, recipient, amount0, amount1, sqrt_price_x96,
+                liquidity, tick
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (chain_id, pool_address, block_number, log_index) DO NOTHING
+            "#,
+        )
+        .bind(swap.source.chain_id as i64)
+        .bind(swap.source.address)
+        .bind(swap.source.block_number as i64)
+        .bind(swap.source.log_index as i64)
+        .bind(swap.source.transaction_hash)
+        .bind(
+            swap.source
+                .block_timestamp
+                .map(|timestamp| timestamp as i64),
+        )
+        .bind(swap.sender)
+        .bind(swap.recipient)
+        .bind(&swap.amount0)
+        .bind(&swap.amount1)
+        .bind(&swap.sqrt_price_x96)
+        .bind(&swap.liquidity)
+        .bind(swap.tick)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        // A swap stored already was applied already
+        if inserted == 0 {
+            return tx.commit().await;
+        }
+
+        // The fee tier is charged on the token paid in, pools created before the
+        // factory was indexed accrue no swap fees
+        sqlx::query(
+            r#"
+            INSERT INTO uniswap_v3_pool_states (
+                chain_id, pool_address, sqrt_price_x96, tick, liquidity, volume_token0,
+                volume_token1, fees_token0, fees_token1, block_number, log_index
+            )
+            SELECT $1, $2, $3, $4, $5, ABS($6), ABS($7),
+                CASE WHEN $6 > 0 THEN TRUNC($6 * pool.fee / 1000000) ELSE 0 END,
+                CASE WHEN $7 > 0 THEN TRUNC($7 * pool.fee / 1000000) ELSE 0 END,
+                $8, $9
+            FROM (
+                SELECT COALESCE(
+                    (SELECT fee FROM uniswap_v3_pools WHERE chain_id = $1 AND pool_address = $2),
+                    0
+                ) AS fee
+            ) pool
+            ON CONFLICT (chain_id, pool_address) DO UPDATE SET
+                volume_token0 = uniswap_v3_pool_states.volume_token0 + EXCLUDED.volume_token0,
+                volume_token1 = uniswap_v3_pool_states.volume_token1 + EXCLUDED.volume_token1,
+                fees_token0 = uniswap_v3_pool_states.fees_token0 + EXCLUDED.fees_token0,
+                fees_token1 = uniswap_v3_pool_states.fees_token1 + EXCLUDED.fees_token1
+            "#,
+        )
+        .bind(swap.source.chain_id as i64)
+        .bind(swap.source.address)
+        .bind(&swap.sqrt_price_x96)
+        .bind(swap.tick)
+        .bind(&swap.liquidity)
+        .bind(&swap.amount0)
+        .bind(&swap.amount1)
+        .bind(swap.source.block_number as i64)
+        .bind(swap.source.log_index as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        // The price only moves forward, an older swap arriving late leaves it as it is
+        sqlx::query(
+            r#"
+            UPDATE uniswap_v3_pool_states SET
+                sqrt_price_x96 = $3,
+                tick = $4,
+                liquidity = $5,
+                block_number = $6,
+                log_index = $7
+            WHERE chain_id = $1 AND pool_address = $2 AND (block_number, log_index) < ($6, $7)
+            "#,
+        )
+        .bind(swap.source.chain_id as i64)
+        .bind(swap.source.address)
+        .bind(&swap.sqrt_price_x96)
+        .bind(swap.tick)
+        .bind(&swap.liquidity)
+        .bind(swap.source.block_number as i64)
+        .bind(swap.source.log_index as i64)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    async fn add_liquidity(&self, mint: &LiquidityRequest) -> Result<(), sqlx::Error> {
+        self.change_liquidity(mint, mint.amount.clone()).await
+    }
+
+    async fn remove_liquidity(&self, burn: &LiquidityRequest) -> Result<(), sqlx::Error> {
+        self.change_liquidity(burn, -burn.amount.clone()).await
+    }
+
+    async fn record_flash(&self, flash: &FlashRequest) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO uniswap_v3_flashes (
+                chain_id, pool_address, block_number, log_index, transaction_hash, sender,
+                recipient, amount0, amount1, paid0, paid1
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (chain_id, pool_address, block_number, log_index) DO NOTHING
+            "#,
+        )
+        .bind(flash.source.chain_id as i64)
+        .bind(flash.source.address)
+        .bind(flash.source.block_number as i64)
+        .bind(flash.source.log_index as i64)
+        .bind(flash.source.transaction_hash)
+        .bind(flash.sender)
+        .bind(flash.recipient)
+        .bind(&flash.amount0)
+        .bind(&flash.amount1)
+        .bind(&flash.paid0)
+        .bind(&flash.paid1)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if inserted > 0 {
+            sqlx::query(
+                r#"
+                UPDATE uniswap_v3_pool_states SET
+                    fees_token0 = fees_token0 + $3,
+                    fees_token1 = fees_token1 + $4
+                WHERE chain_id = $1 AND pool_address = $2
+                "#,
+            )
+            .bind(flash.source.chain_id as i64)
+            .bind(flash.source.address)
+            .bind(&flash.paid0)
+            .bind(&flash.paid1)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::services::dtos::index_engine::EventSource;
+
+    use super::*;
+
+    const CHAIN_ID: u64 = 1;
+    const POOL: [u8; 20] = [0x33; 20];
+
+    fn source(block_number: u64, log_index: u64) -> EventSource {
+        EventSource {
+            chain_id: CHAIN_ID,
+            address: POOL,
+            block_number,
+            transaction_hash: [block_number as u8; 32],
+            log_index,
+            block_timestamp: None,
+        }
+    }
+
+    fn swap(block_number: u64, amount0: i64, amount1: i64, tick: i32) -> SwapRequest {
+        SwapRequest {
+            source: source(block_number, 0),
+            sender: [0x01; 20],
+            recipient: [0x02; 20],
+            amount0: BigDecimal::from(amount0),
+            amount1: BigDecimal::from(amount1),
+            sqrt_price_x96: BigDecimal::from(tick + 1_000),
+            liquidity: BigDecimal::from(100),
+            tick,
+        }
+    }
+
+    fn liquidity(block_number: u64, amount: i64) -> LiquidityRequest {
+        LiquidityRequest {
+            source: source(block_number, 0),
+            owner: [0x01; 20],
+            tick_lower: -10,
+            tick_upper: 10,
+            amount: BigDecimal::from(amount),
+            amount0: BigDecimal::from(1),
+            amount1: BigDecimal::from(1),
+        }
+    }
+
+    /// Volumes, fees, liquidity, tick and the block of the last event moving them.
+    async fn state(
+        pool: &PgPool,
+    ) -> (
+        BigDecimal,
+        BigDecimal,
+        BigDecimal,
+        BigDecimal,
+        BigDecimal,
+        i32,
+        i64,
+    ) {
+        sqlx::query_as(
+            r#"
+            SELECT volume_token0, volume_token1, fees_token0, fees_token1, liquidity, tick,
+                block_number
+            FROM uniswap_v3_pool_states WHERE chain_id = $1 AND pool_address = $2
+            "#,
+        )
+        .bind(CHAIN_ID as i64)
+        .bind(POOL)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn counts_replayed_and_late_swaps_once_without_moving_the_price_back(pool: PgPool) {
+        let repo = UniswapV3RepositoryImpl::new(pool.clone());
+        repo.create_pool(&PoolCreatedRequest {
+            source: EventSource {
+                address: [0x44; 20],
+                ..source(1, 0)
+            },
+            pool: POOL,
+            token0: [0x0a; 20],
+            token1: [0x0b; 20],
+            fee: 3000,
+            tick_spacing: 60,
+        })
+        .await
+        .unwrap();
+
+        repo.record_swap(&swap(10, 1_000, -990, 2)).await.unwrap();
+        repo.record_swap(&swap(10, 1_000, -990, 2)).await.unwrap();
+        repo.record_swap(&swap(5, -500, 505, 1)).await.unwrap();
+
+        let (volume0, volume1, fees0, fees1, _, tick, block_number) = state(&pool).await;
+        assert_eq!(volume0, BigDecimal::from(1_500));
+        assert_eq!(volume1, BigDecimal::from(1_495));
+        assert_eq!(fees0, BigDecimal::from(3));
+        assert_eq!(fees1, BigDecimal::from(1));
+        assert_eq!((tick, block_number), (2, 10));
+    }
+
+    #[sqlx::test(migrations = "./migrations")]
+    async fn applies_liquidity_changes_after_the_last_swap_and_flashes_once(pool: PgPool) {
+        let repo = UniswapV3RepositoryImpl::new(pool.clone());
+        repo.initialize_pool(&PoolInitializedRequest {
+            source: source(1, 0),
+            sqrt_price_x96: BigDecimal::from(1_000),
+            tick: 0,
+        })
+        .await
+        .unwrap();
+        repo.record_swap(&swap(10, 0, 0, 0)).await.unwrap();
+
+        repo.add_liquidity(&liquidity(5, 50)).await.unwrap();
+        repo.add_liquidity(&liquidity(5, 50)).await.unwrap();
+        repo.remove_liquidity(&liquidity(11, 20)).await.unwrap();
+        let flash = FlashRequest {
+            source: source(12, 0),
+            sender: [0x01; 20],
+            recipient: [0x02; 20],
+            amount0: BigDecimal::from(1_000),
+            amount1: BigDecimal::from(2_000),
+            paid0: BigDecimal::from(7),
+            paid1: BigDecimal::from(9),
+        };
+        repo.record_flash(&flash).await.unwrap();
+        repo.record_flash(&flash).await.unwrap();
+
+        // The swap at block 10 reported the liquidity the mint at block 5 left
+        let (_, _, fees0, fees1, liquidity, tick, block_number) = state(&pool).await;
+        assert_eq!(liquidity, BigDecimal::from(80));
+        assert_eq!(fees0, BigDecimal::from(7));
+        assert_eq!(fees1, BigDecimal::from(9));
+        assert_eq!((tick, block_number), (0, 11));
+    }
+}
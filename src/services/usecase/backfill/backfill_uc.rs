@@ -0,0 +1,146 @@
+use alloy::primitives::Address;
+use async_trait::async_trait;
+use futures::{StreamExt, stream};
+
+use crate::services::{
+    dtos::backfill::{BackfillJob, BackfillReport},
+    entities::evm_chains::EvmChains,
+    repository::EVMBackfillChunksRepository,
+    usecase::{BackfillUC, IndexLogUC, errors::AppError},
+};
+
+pub struct BackfillUCImpl<U, CR> {
+    pub index_log_uc: U,
+    pub chunk_repo: CR,
+}
+
+impl<U, CR> BackfillUCImpl<U, CR> {
+    pub fn new(index_log_uc: U, chunk_repo: CR) -> Self {
+        Self {
+            index_log_uc,
+            chunk_repo,
+        }
+    }
+}
+
+/// Splits the inclusive block range into consecutive chunks of at most `chunk_size`
+/// blocks.
+fn split_range(from_block: u64, to_block: u64, chunk_size: u64) -> Vec<(u64, u64)> {
+    let chunk_size = chunk_size.max(1);
+
+    let mut chunks = Vec::new();
+    let mut chunk_start = from_block;
+    while chunk_start <= to_block {
+        let chunk_end = chunk_start.saturating_add(chunk_size - 1).min(to_block);
+        chunks.push((chunk_start, chunk_end));
+        if chunk_end == u64::MAX {
+            break;
+        }
+        chunk_start = chunk_end + 1;
+    }
+
+    chunks
+}
+
+#[async_trait]
+impl<U, CR> BackfillUC for BackfillUCImpl<U, CR>
+where
+    U: IndexLogUC,
+    CR: EVMBackfillChunksRepository + Send + Sync,
+{
+    async fn execute(
+        &self,
+        chain: &EvmChains,
+        job: &BackfillJob,
+    ) -> Result<BackfillReport, AppError> {
+        let contract = &job.contract;
+        let address = contract
+            .address
+            .parse::<Address>()
+            .map_err(|e| AppError::InvalidAddress(e.to_string()))?
+            .0
+            .0;
+
+        // The live cursor follows the head from the end of the range on
+        let range = match job.range {
+            Some(range) => Some(range),
+            None => self.index_log_uc.hand_over_cursor(chain, contract).await?,
+        };
+        if let Some((from_block, to_block)) = range {
+            self.chunk_repo
+                .create_bulk(
+                    chain.id as u64,
+                    address,
+                    split_range(from_block, to_block, job.chunk_size),
+                )
+                .await?;
+        }
+
+        let chunks = self
+            .chunk_repo
+            .find_pending(chain.id as u64, address)
+            .await?;
+        let total = chunks.len();
+        println!(
+            "Backfilling {total} chunks of address {} on chain {}",
+            contract.address, chain.id
+        );
+
+        let results: Vec<Result<u64, AppError>> = stream::iter(chunks)
+            .map(|chunk| async move {
+                let (from_block, to_block) = (chunk.from_block as u64, chunk.to_block as u64);
+                let logs = self
+                    .index_log_uc
+                    .ingest_range(chain, contract, from_block, to_block)
+                    .await
+                    .inspect_err(|err| {
+                        eprintln!(
+                            "Failed to backfill blocks {from_block}-{to_block} of {}: {err}",
+                            contract.address
+                        )
+                    })?;
+
+                self.chunk_repo
+                    .complete(chain.id as u64, address, from_block)
+                    .await?;
+                println!(
+                    "Backfilled blocks {from_block}-{to_block} of {}: {logs} logs",
+                    contract.address
+                );
+
+                Ok(logs)
+            })
+            .buffer_unordered(job.workers.max(1))
+            .collect()
+            .await;
+
+        // Failed chunks stay pending for the next run
+        let mut report = BackfillReport::default();
+        for result in results {
+            match result {
+                Ok(logs) => {
+                    report.completed_chunks += 1;
+                    report.logs += logs;
+                }
+                Err(_) => report.failed_chunks += 1,
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_range_into_inclusive_chunks() {
+        assert_eq!(
+            split_range(100, 349, 100),
+            vec![(100, 199), (200, 299), (300, 349)]
+        );
+        assert_eq!(split_range(5, 5, 10), vec![(5, 5)]);
+        assert_eq!(split_range(10, 5, 10), vec![]);
+    }
+}
@@ -0,0 +1,198 @@
+use async_trait::async_trait;
+
+use crate::services::{
+    dtos::{coverage::CoverageReport, index_logs::TrackedContract},
+    entities::evm_chains::EvmChains,
+    repository::EVMSyncLogsRepository,
+    usecase::{CoverageUC, IndexLogUC, errors::AppError},
+};
+
+pub struct CoverageUCImpl<U, SR> {
+    pub index_log_uc: U,
+    pub sync_repo: SR,
+}
+
+impl<U, SR> CoverageUCImpl<U, SR> {
+    pub fn new(index_log_uc: U, sync_repo: SR) -> Self {
+        Self {
+            index_log_uc,
+            sync_repo,
+        }
+    }
+}
+
+/// Compares the inclusive `ranges`, sorted by start, with the blocks from
+/// `start_block` to `cursor`.
+fn coverage(start_block: u64, cursor: u64, ranges: &[(u64, u64)]) -> CoverageReport {
+    let mut gaps = Vec::new();
+    let mut overlaps = Vec::new();
+
+    // First block no range covered so far, and the end of the ranges seen
+    let mut next_block = start_block;
+    let mut covered_to: Option<u64> = None;
+    for &(from_block, to_block) in ranges {
+        if let Some(covered_to) = covered_to
+            && from_block <= covered_to
+        {
+            overlaps.push((from_block, to_block.min(covered_to)));
+        }
+
+        if from_block > next_block && next_block <= cursor {
+            gaps.push((next_block, (from_block - 1).min(cursor)));
+        }
+
+        next_block = next_block.max(to_block.saturating_add(1));
+        covered_to = Some(covered_to.map_or(to_block, |covered_to| covered_to.max(to_block)));
+    }
+
+    if next_block <= cursor {
+        gaps.push((next_block, cursor));
+    }
+
+    let mut report = CoverageReport {
+        start_block,
+        cursor,
+        covered_blocks: 0,
+        gaps,
+        overlaps,
+    };
+    let missing: u64 = report.gaps.iter().map(|(from, to)| to - from + 1).sum();
+    report.covered_blocks = report.expected_blocks() - missing;
+
+    report
+}
+
+/// Merges the inclusive `ranges`, sorted by start, where they overlap or touch.
+fn merge_ranges(ranges: &[(u64, u64)]) -> Vec<(u64, u64)> {
+    let mut merged: Vec<(u64, u64)> = Vec::with_capacity(ranges.len());
+    for &(from_block, to_block) in ranges {
+        match merged.last_mut() {
+            Some((_, last_to)) if from_block <= last_to.saturating_add(1) => {
+                *last_to = (*last_to).max(to_block);
+            }
+            _ => merged.push((from_block, to_block)),
+        }
+    }
+
+    merged
+}
+
+#[async_trait]
+impl<U, SR> CoverageUC for CoverageUCImpl<U, SR>
+where
+    U: IndexLogUC,
+    SR: EVMSyncLogsRepository + Send + Sync,
+{
+    async fn report(
+        &self,
+        chain: &EvmChains,
+        contract: &TrackedContract,
+    ) -> Result<CoverageReport, AppError> {
+        let sync_log = self
+            .sync_repo
+            .find_by_address(&contract.address, chain.id as u64)
+            .await?
+            .ok_or_else(|| AppError::UnsupportedAddress(contract.address.clone()))?;
+
+        let ranges: Vec<(u64, u64)> = self
+            .sync_repo
+            .find_ingested_ranges(chain.id as u64, sync_log.address)
+            .await?
+            .iter()
+            .map(|range| (range.from_block as u64, range.to_block as u64))
+            .collect();
+
+        // A cursor that indexed nothing yet has no coverage to report
+        let cursor = sync_log
+            .last_synced_block_number
+            .ok_or_else(|| AppError::UnsupportedAddress(contract.address.clone()))?
+            as u64;
+
+        // Without a configured start block, coverage starts at the block the cursor
+        // started at, the deployment block found for it, or for cursors older than
+        // that record, with the earliest range
+        let start_block = contract
+            .start_block
+            .or(sync_log.start_block.map(|block| block as u64))
+            .or(ranges.first().map(|(from_block, _)| *from_block))
+            .unwrap_or(cursor + 1);
+
+        Ok(coverage(start_block, cursor, &ranges))
+    }
+
+    async fn repair(
+        &self,
+        chain: &EvmChains,
+        contract: &TrackedContract,
+    ) -> Result<CoverageReport, AppError> {
+        let report = self.report(chain, contract).await?;
+
+        for (from_block, to_block) in report.gaps {
+            match self
+                .index_log_uc
+                .ingest_range(chain, contract, from_block, to_block)
+                .await
+            {
+                Ok(logs) => println!(
+                    "Filled gap {from_block}-{to_block} of {} with {logs} logs",
+                    contract.address
+                ),
+                Err(err) => eprintln!(
+                    "Failed to fill gap {from_block}-{to_block} of {}: {err}",
+                    contract.address
+                ),
+            }
+        }
+
+        // Overlapping ranges are merged so the next report only shows new ones
+        let sync_log = self
+            .sync_repo
+            .find_by_address(&contract.address, chain.id as u64)
+            .await?
+            .ok_or_else(|| AppError::UnsupportedAddress(contract.address.clone()))?;
+        let ranges = self
+            .sync_repo
+            .find_ingested_ranges(chain.id as u64, sync_log.address)
+            .await?;
+        let merged = merge_ranges(
+            &ranges
+                .iter()
+                .map(|range| (range.from_block as u64, range.to_block as u64))
+                .collect::<Vec<_>>(),
+        );
+        if merged.len() < ranges.len() {
+            let ids: Vec<i64> = ranges.iter().map(|range| range.id).collect();
+            self.sync_repo
+                .replace_ingested_ranges(chain.id as u64, sync_log.address, &ids, &merged)
+                .await?;
+        }
+
+        self.report(chain, contract).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_gaps_and_overlaps_up_to_the_cursor() {
+        let report = coverage(100, 500, &[(100, 199), (150, 249), (300, 349), (350, 400)]);
+
+        assert_eq!(report.gaps, vec![(250, 299), (401, 500)]);
+        assert_eq!(report.overlaps, vec![(150, 199)]);
+        assert_eq!(report.covered_blocks, 401 - 50 - 100);
+
+        let report = coverage(100, 500, &[(50, 500)]);
+        assert!(report.gaps.is_empty());
+        assert_eq!(report.covered_blocks, 401);
+    }
+
+    #[test]
+    fn merges_touching_and_overlapping_ranges() {
+        assert_eq!(
+            merge_ranges(&[(1, 10), (5, 20), (21, 30), (40, 50)]),
+            vec![(1, 30), (40, 50)]
+        );
+    }
+}
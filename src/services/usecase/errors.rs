@@ -22,9 +22,30 @@ pub enum AppError {
     #[error("RPC error: {0}")]
     RpcError(String),
 
+    #[error("Rate limited: {0}")]
+    RateLimited(String),
+
+    #[error("Transient RPC error: {0}")]
+    TransientRpcError(String),
+
+    #[error("Circuit open for RPC endpoint `{0}`")]
+    CircuitOpen(String),
+
+    #[error("Unsupported RPC method: {0}")]
+    UnsupportedMethod(String),
+
+    #[error("Log range too large: {0}")]
+    LogRangeTooLarge(String),
+
+    #[error("Log subscription closed")]
+    SubscriptionClosed,
+
     #[error("Invalid address: {0}")]
     InvalidAddress(String),
 
+    #[error("No contract code found at `{0}`")]
+    MissingContractCode(String),
+
     #[error("Invalid ChainID: `{0}`")]
     InvalidChainID(String),
 
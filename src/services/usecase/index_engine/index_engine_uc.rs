@@ -1,31 +1,73 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 use futures::stream::{self, StreamExt};
 
 use crate::services::{
-    dtos::index_engine::{FeeAmountEnabledRequest, OwnerChangedRequest, PoolCreatedRequest},
+    dtos::index_engine::{
+        Erc20ApprovalRequest, Erc20TransferRequest, Erc721TransferRequest, Erc1155TransferRequest,
+        Erc1155UriRequest, FeeAmountEnabledRequest, FlashRequest, IndexedEvent, LiquidityRequest,
+        OwnerChangedRequest, PairCreatedRequest, PairLiquidityRequest, PairSwapRequest,
+        PairSyncRequest, PoolCreatedRequest, PoolInitializedRequest, SwapRequest,
+    },
     entities::evm_logs::EVMLogs,
-    repository::EVMLogsRepository,
+    repository::{
+        EVMLogsRepository, Erc20Repository, NftRepository, TrackedContractsRepository,
+        UniswapV2Repository, UniswapV3Repository,
+    },
     usecase::{IndexEngineUC, errors::AppError},
 };
 
-use crate::infrastructure::contracts::{ContractHandler, contract_registry::ContractRegistry};
+use crate::infrastructure::contracts::contract_registry::ContractRegistry;
 
-pub struct IndexEngineUCImpl<RL: EVMLogsRepository> {
+pub struct IndexEngineUCImpl<RL, TC, UR, VR, ER, NR> {
     evm_log_repo: RL,
+    tracked_contract_repo: TC,
+    uniswap_v3_repo: UR,
+    uniswap_v2_repo: VR,
+    erc20_repo: ER,
+    nft_repo: NR,
     contract_registry: ContractRegistry,
     batch_size: u64,
 }
 
-impl<RL: EVMLogsRepository> IndexEngineUCImpl<RL> {
-    pub fn new(evm_log_repo: RL, contract_registry: ContractRegistry, batch_size: u64) -> Self {
+impl<RL, TC, UR, VR, ER, NR> IndexEngineUCImpl<RL, TC, UR, VR, ER, NR>
+where
+    RL: EVMLogsRepository + Send + Sync,
+    TC: TrackedContractsRepository + Send + Sync,
+    UR: UniswapV3Repository + Send + Sync,
+    VR: UniswapV2Repository + Send + Sync,
+    ER: Erc20Repository + Send + Sync,
+    NR: NftRepository + Send + Sync,
+{
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        evm_log_repo: RL,
+        tracked_contract_repo: TC,
+        uniswap_v3_repo: UR,
+        uniswap_v2_repo: VR,
+        erc20_repo: ER,
+        nft_repo: NR,
+        contract_registry: ContractRegistry,
+        batch_size: u64,
+    ) -> Self {
         Self {
             evm_log_repo,
+            tracked_contract_repo,
+            uniswap_v3_repo,
+            uniswap_v2_repo,
+            erc20_repo,
+            nft_repo,
             contract_registry,
             batch_size,
         }
     }
 
     pub async fn process_logs(&self) -> Result<(), AppError> {
+        // Picks up contracts tracked or disabled since the previous batch
+        let contracts = self.tracked_contract_repo.find_enabled(None).await?;
+        self.contract_registry.reload(&contracts);
+
         let logs: Vec<EVMLogs> = self.evm_log_repo.list(self.batch_size as i64).await?;
 
         if logs.is_empty() {
@@ -34,19 +76,37 @@ impl<RL: EVMLogsRepository> IndexEngineUCImpl<RL> {
 
         println!("Processing batch of {} logs in parallel", logs.len());
 
-        // Process up to 10 logs concurrently
-        let results: Vec<_> = stream::iter(logs)
-            .map(|log| async move {
-                let log_id = log.id;
-                match self.process_and_delete_log(log).await {
-                    Ok(_) => (log_id, true),
-                    Err(e) => {
-                        eprintln!(" [{}] Error: {}", log_id, e);
-                        (log_id, false)
+        // Each log may build on the state the previous one of its contract left, so
+        // logs run in order per contract, and up to 10 contracts concurrently
+        let mut contract_logs: Vec<Vec<EVMLogs>> = Vec::new();
+        let mut positions: HashMap<(i64, [u8; 20]), usize> = HashMap::new();
+        for log in logs {
+            let position = *positions
+                .entry((log.chain_id, log.address))
+                .or_insert_with(|| {
+                    contract_logs.push(Vec::new());
+                    contract_logs.len() - 1
+                });
+            contract_logs[position].push(log);
+        }
+
+        let results: Vec<(i32, bool)> = stream::iter(contract_logs)
+            .map(|logs| async move {
+                let mut results = Vec::with_capacity(logs.len());
+                for log in logs {
+                    let log_id = log.id;
+                    match self.process_and_delete_log(log).await {
+                        Ok(_) => results.push((log_id, true)),
+                        Err(e) => {
+                            eprintln!(" [{}] Error: {}", log_id, e);
+                            results.push((log_id, false));
+                        }
                     }
                 }
+                results
             })
             .buffer_unordered(10)
+            .flat_map(stream::iter)
             .collect()
             .await;
 
@@ -58,10 +118,47 @@ impl<RL: EVMLogsRepository> IndexEngineUCImpl<RL> {
     }
 
     async fn process_and_delete_log(&self, log: EVMLogs) -> Result<(), AppError> {
-        let log_id = log.id;
-        let processor = self.contract_registry.get_processor(log.address)?;
+        let (log_id, chain_id, parent_address) = (log.id, log.chain_id as u64, log.address);
+        let processor = self
+            .contract_registry
+            .get_processor(chain_id, log.address)?;
 
-        processor.process(log).await?;
+        let events = processor.process(log).await?;
+        for event in events {
+            match event {
+                // Both the listener and the next batch pick up the new contract
+                IndexedEvent::ContractDiscovered(contract) => {
+                    self.tracked_contract_repo
+                        .create_discovered(
+                            chain_id,
+                            contract.address,
+                            &contract.contract_name,
+                            contract.deployment.as_deref(),
+                            parent_address,
+                            contract.start_block,
+                        )
+                        .await?
+                }
+                IndexedEvent::PoolCreated(data) => self.on_pool_created(data).await?,
+                IndexedEvent::OwnerChanged(data) => self.on_owner_changed(data).await?,
+                IndexedEvent::FeeAmountEnabled(data) => self.on_fee_amount_enabled(data).await?,
+                IndexedEvent::PoolInitialized(data) => self.on_pool_initialized(data).await?,
+                IndexedEvent::Swap(data) => self.on_swap(data).await?,
+                IndexedEvent::Mint(data) => self.on_mint(data).await?,
+                IndexedEvent::Burn(data) => self.on_burn(data).await?,
+                IndexedEvent::Flash(data) => self.on_flash(data).await?,
+                IndexedEvent::Erc20Transfer(data) => self.on_erc20_transfer(data).await?,
+                IndexedEvent::Erc20Approval(data) => self.on_erc20_approval(data).await?,
+                IndexedEvent::Erc721Transfer(data) => self.on_erc721_transfer(data).await?,
+                IndexedEvent::Erc1155Transfer(data) => self.on_erc1155_transfer(data).await?,
+                IndexedEvent::Erc1155Uri(data) => self.on_erc1155_uri(data).await?,
+                IndexedEvent::PairCreated(data) => self.on_pair_created(data).await?,
+                IndexedEvent::PairSync(data) => self.on_pair_sync(data).await?,
+                IndexedEvent::PairSwap(data) => self.on_pair_swap(data).await?,
+                IndexedEvent::PairMint(data) => self.on_pair_mint(data).await?,
+                IndexedEvent::PairBurn(data) => self.on_pair_burn(data).await?,
+            }
+        }
         self.evm_log_repo.delete(log_id).await?;
 
         Ok(())
@@ -69,17 +166,85 @@ impl<RL: EVMLogsRepository> IndexEngineUCImpl<RL> {
 }
 
 #[async_trait]
-impl<RL> IndexEngineUC for IndexEngineUCImpl<RL>
+impl<RL, TC, UR, VR, ER, NR> IndexEngineUC for IndexEngineUCImpl<RL, TC, UR, VR, ER, NR>
 where
     RL: EVMLogsRepository + Send + Sync,
+    TC: TrackedContractsRepository + Send + Sync,
+    UR: UniswapV3Repository + Send + Sync,
+    VR: UniswapV2Repository + Send + Sync,
+    ER: Erc20Repository + Send + Sync,
+    NR: NftRepository + Send + Sync,
 {
     async fn on_pool_created(&self, data: PoolCreatedRequest) -> Result<(), AppError> {
-        todo!()
+        self.uniswap_v3_repo.create_pool(&data).await?;
+        Ok(())
     }
     async fn on_owner_changed(&self, data: OwnerChangedRequest) -> Result<(), AppError> {
-        todo!()
+        self.uniswap_v3_repo.create_factory_owner(&data).await?;
+        Ok(())
     }
     async fn on_fee_amount_enabled(&self, data: FeeAmountEnabledRequest) -> Result<(), AppError> {
-        todo!()
+        self.uniswap_v3_repo.create_fee_tier(&data).await?;
+        Ok(())
+    }
+    async fn on_pool_initialized(&self, data: PoolInitializedRequest) -> Result<(), AppError> {
+        self.uniswap_v3_repo.initialize_pool(&data).await?;
+        Ok(())
+    }
+    async fn on_swap(&self, data: SwapRequest) -> Result<(), AppError> {
+        self.uniswap_v3_repo.record_swap(&data).await?;
+        Ok(())
+    }
+    async fn on_mint(&self, data: LiquidityRequest) -> Result<(), AppError> {
+        self.uniswap_v3_repo.add_liquidity(&data).await?;
+        Ok(())
+    }
+    async fn on_burn(&self, data: LiquidityRequest) -> Result<(), AppError> {
+        self.uniswap_v3_repo.remove_liquidity(&data).await?;
+        Ok(())
+    }
+    async fn on_flash(&self, data: FlashRequest) -> Result<(), AppError> {
+        self.uniswap_v3_repo.record_flash(&data).await?;
+        Ok(())
+    }
+    async fn on_erc20_transfer(&self, data: Erc20TransferRequest) -> Result<(), AppError> {
+        self.erc20_repo.record_transfer(&data).await?;
+        Ok(())
+    }
+    async fn on_erc20_approval(&self, data: Erc20ApprovalRequest) -> Result<(), AppError> {
+        self.erc20_repo.record_approval(&data).await?;
+        Ok(())
+    }
+    async fn on_erc721_transfer(&self, data: Erc721TransferRequest) -> Result<(), AppError> {
+        self.nft_repo.record_erc721_transfer(&data).await?;
+        Ok(())
+    }
+    async fn on_erc1155_transfer(&self, data: Erc1155TransferRequest) -> Result<(), AppError> {
+        self.nft_repo.record_erc1155_transfer(&data).await?;
+        Ok(())
+    }
+    async fn on_erc1155_uri(&self, data: Erc1155UriRequest) -> Result<(), AppError> {
+        self.nft_repo.set_erc1155_uri(&data).await?;
+        Ok(())
+    }
+    async fn on_pair_created(&self, data: PairCreatedRequest) -> Result<(), AppError> {
+        self.uniswap_v2_repo.create_pair(&data).await?;
+        Ok(())
+    }
+    async fn on_pair_sync(&self, data: PairSyncRequest) -> Result<(), AppError> {
+        self.uniswap_v2_repo.sync_reserves(&data).await?;
+        Ok(())
+    }
+    async fn on_pair_swap(&self, data: PairSwapRequest) -> Result<(), AppError> {
+        self.uniswap_v2_repo.record_swap(&data).await?;
+        Ok(())
+    }
+    async fn on_pair_mint(&self, data: PairLiquidityRequest) -> Result<(), AppError> {
+        self.uniswap_v2_repo.record_mint(&data).await?;
+        Ok(())
+    }
+    async fn on_pair_burn(&self, data: PairLiquidityRequest) -> Result<(), AppError> {
+        self.uniswap_v2_repo.record_burn(&data).await?;
+        Ok(())
     }
 }
@@ -0,0 +1,63 @@
+use std::collections::{HashMap, VecDeque};
+
+use alloy::primitives::B256;
+
+/// Timestamps of recently seen blocks, keyed by hash so a reorged block never
+/// matches. The oldest entries are evicted first once `capacity` is reached.
+#[derive(Debug)]
+pub struct BlockTimestampCache {
+    capacity: usize,
+    timestamps: HashMap<B256, u64>,
+    order: VecDeque<B256>,
+}
+
+impl BlockTimestampCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            timestamps: HashMap::new(),
+            order: VecDeque::new(),
+        }
+    }
+
+    pub fn get(&self, hash: &B256) -> Option<u64> {
+        self.timestamps.get(hash).copied()
+    }
+
+    pub fn insert(&mut self, hash: B256, timestamp: u64) {
+        if self.timestamps.insert(hash, timestamp).is_some() {
+            return;
+        }
+
+        self.order.push_back(hash);
+        if self.order.len() > self.capacity
+            && let Some(oldest) = self.order.pop_front()
+        {
+            self.timestamps.remove(&oldest);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evicts_the_oldest_blocks_first() {
+        let mut cache = BlockTimestampCache::new(2);
+        let (a, b, c) = (
+            B256::repeat_byte(1),
+            B256::repeat_byte(2),
+            B256::repeat_byte(3),
+        );
+
+        cache.insert(a, 10);
+        cache.insert(b, 12);
+        cache.insert(a, 10);
+        cache.insert(c, 14);
+
+        assert_eq!(cache.get(&a), None);
+        assert_eq!(cache.get(&b), Some(12));
+        assert_eq!(cache.get(&c), Some(14));
+    }
+}
@@ -1,73 +1,1757 @@
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::Mutex,
+    time::Duration,
+};
+
+use alloy::{
+    primitives::{Address, B256},
+    rpc::types::{BlockNumberOrTag, Filter, Header, Log},
+};
+use futures::{StreamExt, stream};
+use tokio::{sync::RwLock, time::timeout};
+
 use crate::{
-    infrastructure::blockchain::provider::{BlockchainProvider, create_log_filter},
+    infrastructure::blockchain::provider::{
+        BlockchainProvider, create_log_filter, create_multi_address_log_filter,
+        create_subscription_log_filter, find_deployment_block,
+    },
     services::{
-        repository::{EVMLogsRepository, EVMSyncLogsRepository},
-        usecase::{IndexLogUC, errors::AppError},
+        dtos::index_logs::{CursorAdvance, IngestRange, IngestedRange, TrackedContract},
+        entities::evm_transactions::EVMTransactions,
+        entities::{evm_blocks::EVMBlocks, evm_chains::EvmChains, evm_sync_logs::EVMSyncLogs},
+        repository::{
+            EVMBlocksRepository, EVMLogsRepository, EVMSyncLogsRepository,
+            EVMTransactionsRepository, IngestionRepository,
+        },
+        usecase::{
+            IndexLogUC,
+            errors::AppError,
+            index_logs::{block_timestamps::BlockTimestampCache, transactions::TransactionFetcher},
+        },
     },
+    utils::vec_to_hex,
 };
 use async_trait::async_trait;
 
-pub struct IndexLogUCImpl<P, LR, SR> {
+pub struct IndexLogUCImpl<P, LR, SR, TR, IR, BR> {
     pub provider: P,
     pub log_repo: LR,
     pub sync_repo: SR,
+
+    /// Stores the transactions behind every batch of logs when set
+    pub transaction_repo: Option<TR>,
+
+    /// Commits each block range with its cursors
+    pub ingestion_repo: IR,
+
+    /// Headers of every block in the synced ranges, checked for reorgs
+    pub block_repo: BR,
     pub batch_size: u64,
+
+    /// How many stored blocks behind the cursor are checked for reorgs
+    pub reorg_depth: u64,
+
+    /// Held shared by the tasks syncing the chain from reorg check to commit, and
+    /// exclusively by a rollback, so none commits a range fetched before the reorg
+    /// on top of it
+    chain_lock: RwLock<()>,
+
+    /// Timestamps of the block headers fetched so far
+    block_timestamps: Mutex<BlockTimestampCache>,
+
+    transactions: TransactionFetcher,
 }
 
-impl<P, LR, SR> IndexLogUCImpl<P, LR, SR> {
-    pub fn new(provider: P, log_repo: LR, sync_repo: SR, batch_size: u64) -> Self {
+/// Block timestamps kept in memory, enough for several full log windows of blocks.
+const BLOCK_TIMESTAMP_CACHE_SIZE: usize = 50_000;
+
+/// Block headers fetched concurrently for a block range.
+const HEADER_CONCURRENCY: usize = 8;
+
+/// How long the subscription waits for more logs of a block before committing it.
+const STREAM_FLUSH_DELAY: Duration = Duration::from_millis(500);
+
+/// Logs of a single block received through the subscription.
+struct StreamedBlock {
+    number: u64,
+    hash: B256,
+    logs: Vec<Log>,
+
+    /// Contracts whose cursors were advanced to the block once committed
+    addresses: Vec<[u8; 20]>,
+}
+
+impl StreamedBlock {
+    fn new(number: u64, hash: B256, log: Log) -> Self {
+        Self {
+            number,
+            hash,
+            logs: vec![log],
+            addresses: Vec::new(),
+        }
+    }
+}
+
+/// Narrows `filter` to the events the contracts asked for. A single contract wanting
+/// every event leaves topic0 open for all of them.
+fn with_event_signatures(filter: Filter, contracts: &[TrackedContract]) -> Filter {
+    if contracts
+        .iter()
+        .any(|contract| contract.event_signatures.is_empty())
+    {
+        return filter;
+    }
+
+    let event_signatures: HashSet<B256> = contracts
+        .iter()
+        .flat_map(|contract| contract.event_signatures.iter().copied())
+        .collect();
+
+    filter.event_signature(event_signatures.into_iter().collect::<Vec<_>>())
+}
+
+/// A tracked contract and its cursor at the start of a sync tick.
+struct SyncTarget<'a> {
+    contract: &'a TrackedContract,
+    sync_log: EVMSyncLogs,
+}
+
+impl<P, LR, SR, TR, IR, BR> IndexLogUCImpl<P, LR, SR, TR, IR, BR> {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        provider: P,
+        log_repo: LR,
+        sync_repo: SR,
+        transaction_repo: Option<TR>,
+        ingestion_repo: IR,
+        block_repo: BR,
+        batch_size: u64,
+        reorg_depth: u64,
+    ) -> Self {
         Self {
             provider,
             log_repo,
             sync_repo,
+            transaction_repo,
+            ingestion_repo,
+            block_repo,
             batch_size,
+            reorg_depth,
+            chain_lock: RwLock::new(()),
+            block_timestamps: Mutex::new(BlockTimestampCache::new(BLOCK_TIMESTAMP_CACHE_SIZE)),
+            transactions: TransactionFetcher::default(),
         }
     }
 }
 
-#[async_trait]
-impl<P, LR, SR> IndexLogUC for IndexLogUCImpl<P, LR, SR>
+impl<P, LR, SR, TR, IR, BR> IndexLogUCImpl<P, LR, SR, TR, IR, BR>
 where
     P: BlockchainProvider + Send + Sync,
     LR: EVMLogsRepository + Send + Sync,
     SR: EVMSyncLogsRepository + Send + Sync,
+    TR: EVMTransactionsRepository + Send + Sync,
+    IR: IngestionRepository + Send + Sync,
+    BR: EVMBlocksRepository + Send + Sync,
 {
-    async fn execute(&self, chain_id: u64, address: String) -> Result<(), AppError> {
+    /// Highest block the chain considers final, taken from its finality tag when set,
+    /// otherwise `confirmation_depth` blocks behind the head.
+    async fn confirmed_block(&self, chain: &EvmChains, latest_block: u64) -> Result<u64, AppError> {
+        match chain.finality_tag.as_deref() {
+            Some(tag) => {
+                let tag = tag.parse::<BlockNumberOrTag>().map_err(|_| {
+                    AppError::ConfigError(format!(
+                        "Invalid finality tag `{tag}` for chain {}",
+                        chain.id
+                    ))
+                })?;
+
+                self.provider.get_block_number_by_tag(tag).await
+            }
+            None => Ok(latest_block.saturating_sub(chain.confirmation_depth as u64)),
+        }
+    }
+
+    /// Highest block whose logs the processor may build state from: confirmed, and
+    /// deeper than the reorgs the listener detects, so that a rollback deletes any log
+    /// above it before it is processed.
+    fn settled_block(&self, confirmed_block: u64, latest_block: u64) -> u64 {
+        confirmed_block.min(latest_block.saturating_sub(self.reorg_depth))
+    }
+
+    async fn find_or_create_cursor(
+        &self,
+        chain: &EvmChains,
+        contract: &TrackedContract,
+    ) -> Result<EVMSyncLogs, AppError> {
+        let address = &contract.address;
+        if let Some(sync_log) = self
+            .sync_repo
+            .find_by_address(address, chain.id as u64)
+            .await?
+        {
+            return Ok(sync_log);
+        }
+
+        let start_block = self.start_block(contract).await?;
+        println!("Indexing address {address} from block {start_block}");
+
         let sync_log = self
             .sync_repo
-            .find_or_create_by_address(&address, chain_id)
+            .create(
+                address,
+                chain.id as u64,
+                start_block
+                    .checked_sub(1)
+                    .map(|block_number| block_number as i64),
+            )
             .await?;
 
+        Ok(sync_log)
+    }
+
+    /// The cursor of `contract`, or None when it has no code to find a start block
+    /// from yet, skipping it so it does not hold back the other contracts.
+    async fn find_cursor_to_sync(
+        &self,
+        chain: &EvmChains,
+        contract: &TrackedContract,
+    ) -> Result<Option<EVMSyncLogs>, AppError> {
+        match self.find_or_create_cursor(chain, contract).await {
+            Ok(sync_log) => Ok(Some(sync_log)),
+            Err(AppError::MissingContractCode(address)) => {
+                eprintln!("No contract code found at address {address}, skipping it");
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// First block a new cursor indexes: the configured start block, or the block the
+    /// contract was deployed at.
+    async fn start_block(&self, contract: &TrackedContract) -> Result<u64, AppError> {
+        if let Some(start_block) = contract.start_block {
+            return Ok(start_block);
+        }
+
+        let address = contract
+            .address
+            .parse::<Address>()
+            .map_err(|e| AppError::InvalidAddress(e.to_string()))?;
         let latest_block = self.provider.get_block_number().await?;
-        if latest_block == sync_log.last_synced_block_number as u64 {
-            println!("Fully indexed address: {address}");
-            return Ok(());
+
+        find_deployment_block(&self.provider, address, latest_block)
+            .await?
+            .ok_or_else(|| AppError::MissingContractCode(contract.address.clone()))
+    }
+
+    /// Fetches a block header, remembering its timestamp for the logs of the block.
+    async fn get_block_header(&self, block_number: u64) -> Result<Option<Header>, AppError> {
+        let header = self.provider.get_block_header(block_number).await?;
+        if let Some(header) = &header {
+            self.block_timestamps
+                .lock()
+                .unwrap()
+                .insert(header.hash, header.timestamp);
+        }
+
+        Ok(header)
+    }
+
+    /// Sets the block timestamp of every log, reading each block missing from the cache
+    /// from `evm_blocks`, or fetching its header once when it is not stored either.
+    async fn fill_block_timestamps(&self, chain_id: u64, logs: &mut [Log]) -> Result<(), AppError> {
+        let mut missing: BTreeMap<u64, B256> = {
+            let block_timestamps = self.block_timestamps.lock().unwrap();
+            logs.iter()
+                .filter(|log| log.block_timestamp.is_none())
+                .filter_map(|log| Some((log.block_number?, log.block_hash?)))
+                .filter(|(_, hash)| block_timestamps.get(hash).is_none())
+                .collect()
+        };
+
+        if !missing.is_empty() {
+            let numbers: Vec<u64> = missing.keys().copied().collect();
+            let stored = self.block_repo.find_by_numbers(chain_id, &numbers).await?;
+
+            let mut block_timestamps = self.block_timestamps.lock().unwrap();
+            for block in stored {
+                let hash = B256::from(block.hash);
+                if missing.get(&(block.number as u64)) == Some(&hash) {
+                    block_timestamps.insert(hash, block.timestamp as u64);
+                    missing.remove(&(block.number as u64));
+                }
+            }
+        }
+
+        let headers: Vec<Result<Option<Header>, AppError>> = stream::iter(missing.into_keys())
+            .map(|block_number| self.get_block_header(block_number))
+            .buffer_unordered(HEADER_CONCURRENCY)
+            .collect()
+            .await;
+        for header in headers {
+            header?;
+        }
+
+        // Logs of a block reorged out since keep no timestamp until re-ingested
+        let block_timestamps = self.block_timestamps.lock().unwrap();
+        for log in logs.iter_mut() {
+            if log.block_timestamp.is_none() {
+                log.block_timestamp = log.block_hash.and_then(|hash| block_timestamps.get(&hash));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Transactions behind `logs`, when transactions are stored.
+    async fn fetch_transactions(&self, logs: &[Log]) -> Result<Vec<EVMTransactions>, AppError> {
+        match self.transaction_repo {
+            Some(_) => self.transactions.fetch(&self.provider, logs).await,
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// Fetches the header of every block of `from_block_number..=to_block_number`,
+    /// `HEADER_CONCURRENCY` at a time. None when the chain moved while fetching them:
+    /// a log no longer matches its block, or the headers no longer link to each other
+    /// or to the stored block below them.
+    async fn fetch_headers(
+        &self,
+        chain_id: u64,
+        from_block_number: u64,
+        to_block_number: u64,
+        logs: &[Log],
+    ) -> Result<Option<Vec<Header>>, AppError> {
+        let fetched: Vec<Result<Option<Header>, AppError>> =
+            stream::iter(from_block_number..=to_block_number)
+                .map(|block_number| self.get_block_header(block_number))
+                .buffered(HEADER_CONCURRENCY)
+                .collect()
+                .await;
+
+        let mut headers = Vec::with_capacity(fetched.len());
+        for header in fetched {
+            match header? {
+                Some(header) => headers.push(header),
+                None => return Ok(None),
+            }
+        }
+
+        let block_hashes: HashMap<u64, B256> = headers
+            .iter()
+            .map(|header| (header.number, header.hash))
+            .collect();
+        if logs.iter().any(|log| {
+            log.block_number
+                .and_then(|number| block_hashes.get(&number))
+                != log.block_hash.as_ref()
+        }) {
+            return Ok(None);
+        }
+
+        if headers
+            .windows(2)
+            .any(|pair| pair[1].parent_hash != pair[0].hash)
+        {
+            return Ok(None);
+        }
+
+        let parent = self
+            .block_repo
+            .find_by_numbers(chain_id, &[from_block_number.saturating_sub(1)])
+            .await?;
+        if let (Some(parent), Some(first)) = (parent.first(), headers.first())
+            && parent.number as u64 + 1 == first.number
+            && parent.hash != first.parent_hash.0
+        {
+            return Ok(None);
         }
 
-        let from_block_number = match sync_log.last_synced_block_number as u64 {
-            0 => latest_block,
-            block_number => block_number + 1_u64,
+        Ok(Some(headers))
+    }
+
+    /// Compares the blocks stored up to the cursor against the canonical chain, newest
+    /// first, and returns the highest block that is still canonical when the cursor
+    /// tip has been reorged out.
+    async fn find_fork_point(
+        &self,
+        sync_log: &EVMSyncLogs,
+        canonical_hashes: &mut HashMap<u64, B256>,
+    ) -> Result<Option<u64>, AppError> {
+        let Some(last_synced_block) = sync_log.last_synced_block_number else {
+            return Ok(None);
         };
 
-        let to_block_number = match sync_log.last_synced_block_number as u64 {
-            0 => 0,
-            block_number => std::cmp::min(block_number + 10_000_u64, latest_block),
+        let recent = self
+            .block_repo
+            .find_recent(
+                sync_log.chain_id as u64,
+                last_synced_block as u64,
+                self.reorg_depth as i64,
+            )
+            .await?;
+
+        let Some(oldest) = recent.last() else {
+            return Ok(None);
         };
 
-        let filter = create_log_filter(&address, from_block_number, to_block_number)?;
+        for (i, block) in recent.iter().enumerate() {
+            let block_number = block.number as u64;
+            let canonical = match canonical_hashes.get(&block_number) {
+                Some(hash) => *hash,
+                None => {
+                    // A lagging endpoint is no evidence of a reorg, so retry on the next tick
+                    let hash = self
+                        .get_block_header(block_number)
+                        .await?
+                        .ok_or_else(|| {
+                            AppError::RpcError(format!("Block {block_number} not found"))
+                        })?
+                        .hash;
+                    *canonical_hashes.entry(block_number).or_insert(hash)
+                }
+            };
+
+            if canonical.0 == block.hash {
+                return Ok((i > 0).then_some(block.number as u64));
+            }
+        }
+
+        eprintln!(
+            "Reorg deeper than {} stored blocks, rewinding before block {}",
+            recent.len(),
+            oldest.number
+        );
+
+        Ok(Some((oldest.number as u64).saturating_sub(1)))
+    }
+
+    /// Fetches the logs of every contract in `group` with a single filter and the
+    /// headers of the range, and advances their cursors to `to_block_number`.
+    async fn sync_range(
+        &self,
+        chain: &EvmChains,
+        group: &[SyncTarget<'_>],
+        from_block_number: u64,
+        to_block_number: u64,
+        settled_block: u64,
+    ) -> Result<(), AppError> {
+        let addresses: Vec<&str> = group
+            .iter()
+            .map(|target| target.contract.address.as_str())
+            .collect();
+
+        let contracts: Vec<TrackedContract> =
+            group.iter().map(|target| target.contract.clone()).collect();
+        let filter = with_event_signatures(
+            create_multi_address_log_filter(&addresses, from_block_number, to_block_number)?,
+            &contracts,
+        );
 
         let logs = self.provider.get_logs(&filter).await?;
 
-        if !logs.is_empty() {
-            self.log_repo.create_bulk(logs).await?;
+        let Some(headers) = self
+            .fetch_headers(chain.id as u64, from_block_number, to_block_number, &logs)
+            .await?
+        else {
+            println!(
+                "Blocks {from_block_number} to {to_block_number} were reorged while indexing {}, retrying",
+                addresses.join(",")
+            );
+            return Ok(());
+        };
+
+        self.commit_range(chain, group, to_block_number, &headers, logs, settled_block)
+            .await
+    }
+
+    /// Stores the headers of the range and the logs `group` wants, those above
+    /// `settled_block` as unconfirmed, and advances their cursors to `to_block_number`.
+    async fn commit_range(
+        &self,
+        chain: &EvmChains,
+        group: &[SyncTarget<'_>],
+        to_block_number: u64,
+        headers: &[Header],
+        mut logs: Vec<Log>,
+        settled_block: u64,
+    ) -> Result<(), AppError> {
+        // The shared topic0 filter also matches events other contracts in the group asked for
+        let contracts: HashMap<[u8; 20], &TrackedContract> = group
+            .iter()
+            .map(|target| (target.sync_log.address, target.contract))
+            .collect();
+        logs.retain(|log| {
+            contracts
+                .get(&log.address().0.0)
+                .is_some_and(|contract| contract.wants_event(log.topics().first()))
+        });
+
+        self.fill_block_timestamps(chain.id as u64, &mut logs)
+            .await?;
+        let transactions = self.fetch_transactions(&logs).await?;
+
+        let ranges = group
+            .iter()
+            .map(|target| IngestedRange {
+                address: target.sync_log.address,
+                from_block: target.sync_log.next_block(),
+                to_block: to_block_number,
+            })
+            .collect();
+        let cursors = group
+            .iter()
+            .map(|target| CursorAdvance {
+                address: target.sync_log.address,
+                block_number: to_block_number,
+                previous_block: target
+                    .sync_log
+                    .last_synced_block_number
+                    .map(|block_number| block_number as u64),
+            })
+            .collect();
+
+        let ingested = self
+            .ingestion_repo
+            .ingest_range(IngestRange {
+                chain_id: chain.id as u64,
+                blocks: headers.iter().map(EVMBlocks::from).collect(),
+                logs,
+                confirmed_block: settled_block,
+                transactions,
+                cursors,
+                ranges,
+            })
+            .await;
+
+        match ingested {
+            Ok(_) => Ok(()),
+            Err(sqlx::Error::RowNotFound) => {
+                println!("Cursors moved while indexing blocks up to {to_block_number}, retrying");
+                Ok(())
+            }
+            Err(e) => Err(e.into()),
         }
+    }
 
-        let _ = self
-            .sync_repo
-            .update_last_synced_block_number(sync_log.address, to_block_number)
+    /// Runs one sync tick over `contracts`, optionally stopping at `max_block`, and
+    /// returns whether every cursor had already reached its head.
+    async fn sync_contracts(
+        &self,
+        chain: &EvmChains,
+        contracts: &[TrackedContract],
+        max_block: Option<u64>,
+    ) -> Result<bool, AppError> {
+        let tick = self.chain_lock.read().await;
+        let mut canonical_hashes = HashMap::new();
+        let mut caught_up = true;
+        let mut targets = Vec::with_capacity(contracts.len());
+        for contract in contracts {
+            let Some(sync_log) = self.find_cursor_to_sync(chain, contract).await? else {
+                continue;
+            };
+
+            if self
+                .find_fork_point(&sync_log, &mut canonical_hashes)
+                .await?
+                .is_some()
+            {
+                drop(tick);
+                let _rollback = self.chain_lock.write().await;
+
+                // Another task may have rolled the reorg back while the lock was awaited
+                let sync_log = self.find_or_create_cursor(chain, contract).await?;
+                if let Some(fork_block) =
+                    self.find_fork_point(&sync_log, &mut HashMap::new()).await?
+                {
+                    // Every cursor of the chain was rewound, so the orphaned ranges are
+                    // re-ingested on the next tick
+                    self.rollback(chain, fork_block).await?;
+                }
+                return Ok(false);
+            }
+
+            targets.push(SyncTarget { contract, sync_log });
+        }
+
+        let latest_block = self.provider.get_block_number().await?;
+        let confirmed_block = self.confirmed_block(chain, latest_block).await?;
+        let settled_block = self.settled_block(confirmed_block, latest_block);
+        let max_block = max_block.unwrap_or(u64::MAX);
+
+        // Contracts at the same height share one filter per block range
+        let mut groups: BTreeMap<(u64, bool), Vec<SyncTarget>> = BTreeMap::new();
+        for target in targets {
+            // Logs ingested past the settled bound are confirmed once it passes them
+            self.log_repo
+                .confirm_logs(chain.id as u64, target.sync_log.address, settled_block)
+                .await?;
+
+            let head_block = if target.contract.unconfirmed {
+                latest_block
+            } else {
+                confirmed_block
+            };
+
+            let next_block = target.sync_log.next_block();
+            if head_block.min(max_block) < next_block {
+                if max_block == u64::MAX {
+                    println!("Fully indexed address: {}", target.contract.address);
+                }
+                continue;
+            }
+
+            caught_up = false;
+
+            groups
+                .entry((next_block, target.contract.unconfirmed))
+                .or_default()
+                .push(target);
+        }
+
+        let group_heights: Vec<u64> = groups.keys().map(|(height, _)| *height).collect();
+        for ((from_block_number, unconfirmed), group) in groups {
+            let head_block = if unconfirmed {
+                latest_block
+            } else {
+                confirmed_block
+            }
+            .min(max_block);
+
+            // Lagging groups stop below the next group's height so they merge into it
+            let next_height = group_heights
+                .iter()
+                .find(|height| **height > from_block_number)
+                .map_or(u64::MAX, |height| height - 1);
+
+            let to_block_number = (from_block_number + self.provider.log_window() - 1)
+                .min(head_block)
+                .min(next_height);
+
+            self.sync_range(
+                chain,
+                &group,
+                from_block_number,
+                to_block_number,
+                settled_block,
+            )
+            .await?;
+        }
+
+        Ok(caught_up)
+    }
+
+    /// Backfills every cursor up to the block before `block` and commits its logs on top.
+    async fn commit_streamed_block(
+        &self,
+        chain: &EvmChains,
+        contracts: &[TrackedContract],
+        block: StreamedBlock,
+    ) -> Result<StreamedBlock, AppError> {
+        let previous_block = block.number.saturating_sub(1);
+        while !self
+            .sync_contracts(chain, contracts, Some(previous_block))
+            .await?
+        {}
+
+        let _tick = self.chain_lock.read().await;
+
+        // Cursors already past the block fetched it through polling
+        let mut targets = Vec::with_capacity(contracts.len());
+        for contract in contracts {
+            if let Some(sync_log) = self.find_cursor_to_sync(chain, contract).await?
+                && sync_log.next_block() == block.number
+            {
+                targets.push(SyncTarget { contract, sync_log });
+            }
+        }
+
+        // The header also confirms the block is still the one the logs were streamed from
+        let Some(headers) = self
+            .fetch_headers(chain.id as u64, block.number, block.number, &block.logs)
+            .await?
+            .filter(|headers| headers.iter().all(|header| header.hash == block.hash))
+        else {
+            println!(
+                "Streamed block {} was reorged, leaving it to polling",
+                block.number
+            );
+            return Ok(StreamedBlock {
+                logs: Vec::new(),
+                ..block
+            });
+        };
+
+        let confirmed_block = self.confirmed_block(chain, block.number).await?;
+        self.commit_range(
+            chain,
+            &targets,
+            block.number,
+            &headers,
+            block.logs,
+            self.settled_block(confirmed_block, block.number),
+        )
+        .await?;
+
+        Ok(StreamedBlock {
+            addresses: targets
+                .iter()
+                .map(|target| target.sync_log.address)
+                .collect(),
+            logs: Vec::new(),
+            ..block
+        })
+    }
+
+    /// Stores logs that arrived after their block was committed, for the contracts
+    /// whose cursors the stream advanced to it.
+    async fn commit_late_logs(
+        &self,
+        chain: &EvmChains,
+        block: &StreamedBlock,
+        mut logs: Vec<Log>,
+    ) -> Result<(), AppError> {
+        logs.retain(|log| block.addresses.contains(&log.address().0.0));
+
+        if logs.is_empty() {
+            return Ok(());
+        }
+
+        let _tick = self.chain_lock.read().await;
+        self.fill_block_timestamps(chain.id as u64, &mut logs)
+            .await?;
+        let transactions = self.fetch_transactions(&logs).await?;
+        let confirmed_block = self.confirmed_block(chain, block.number).await?;
+
+        self.ingestion_repo
+            .ingest_range(IngestRange {
+                chain_id: chain.id as u64,
+                blocks: Vec::new(),
+                logs,
+                confirmed_block: self.settled_block(confirmed_block, block.number),
+                transactions,
+                cursors: Vec::new(),
+                ranges: Vec::new(),
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Removes the blocks and transactions above the fork point along with the logs every contract of
+    /// the chain indexed past it, and rewinds their cursors to it. Runs while the chain
+    /// lock is held exclusively.
+    async fn rollback(&self, chain: &EvmChains, fork_block: u64) -> Result<(), AppError> {
+        let chain_id = chain.id as u64;
+
+        for sync_log in self.sync_repo.find_by_chain_id(chain_id).await? {
+            let Some(last_synced_block) = sync_log
+                .last_synced_block_number
+                .filter(|block_number| *block_number as u64 > fork_block)
+            else {
+                continue;
+            };
+
+            let removed = self
+                .log_repo
+                .delete_by_address_after_block(chain_id, sync_log.address, fork_block)
+                .await?;
+
+            self.sync_repo
+                .delete_ingested_ranges_after(chain_id, sync_log.address, fork_block)
+                .await?;
+
+            self.sync_repo
+                .update_last_synced_block_number(chain_id, sync_log.address, fork_block)
+                .await?;
+
+            println!(
+                "Reorg detected for address {}: rewound from block {} to {fork_block}, removed {removed} orphaned logs",
+                vec_to_hex(sync_log.address.to_vec()),
+                last_synced_block
+            );
+        }
+
+        // The processor may have deleted the logs of the transactions already
+        if let Some(transaction_repo) = &self.transaction_repo {
+            transaction_repo
+                .delete_after_block(chain_id, fork_block)
+                .await?;
+        }
+
+        // Removed last, so an interrupted rollback is found again on the next tick
+        self.block_repo.delete_after(chain_id, fork_block).await?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<P, LR, SR, TR, IR, BR> IndexLogUC for IndexLogUCImpl<P, LR, SR, TR, IR, BR>
+where
+    P: BlockchainProvider + Send + Sync,
+    LR: EVMLogsRepository + Send + Sync,
+    SR: EVMSyncLogsRepository + Send + Sync,
+    TR: EVMTransactionsRepository + Send + Sync,
+    IR: IngestionRepository + Send + Sync,
+    BR: EVMBlocksRepository + Send + Sync,
+{
+    async fn execute(&self, chain: &EvmChains, contract: &TrackedContract) -> Result<(), AppError> {
+        self.execute_many(chain, std::slice::from_ref(contract))
             .await
-            .inspect_err(|error| eprintln!("Error updating last_synced_block_number {error}"));
+    }
 
+    async fn execute_many(
+        &self,
+        chain: &EvmChains,
+        contracts: &[TrackedContract],
+    ) -> Result<(), AppError> {
+        self.sync_contracts(chain, contracts, None).await?;
         Ok(())
     }
+
+    async fn stream(
+        &self,
+        chain: &EvmChains,
+        contracts: &[TrackedContract],
+    ) -> Result<(), AppError> {
+        // Streamed logs arrive at the head, ahead of any confirmations
+        let contracts: Vec<TrackedContract> = contracts
+            .iter()
+            .map(|contract| TrackedContract {
+                unconfirmed: true,
+                ..contract.clone()
+            })
+            .collect();
+
+        let addresses: Vec<&str> = contracts
+            .iter()
+            .map(|contract| contract.address.as_str())
+            .collect();
+        let filter = with_event_signatures(create_subscription_log_filter(&addresses)?, &contracts);
+
+        let mut logs = self.provider.subscribe_logs(&filter).await?;
+        let mut pending: Option<StreamedBlock> = None;
+        let mut committed: Option<StreamedBlock> = None;
+
+        loop {
+            let next = match pending {
+                Some(_) => timeout(STREAM_FLUSH_DELAY, logs.next()).await,
+                None => Ok(logs.next().await),
+            };
+
+            let log = match next {
+                Ok(Some(log)) => log,
+                Ok(None) => return Err(AppError::SubscriptionClosed),
+                Err(_) => {
+                    if let Some(block) = pending.take() {
+                        committed =
+                            Some(self.commit_streamed_block(chain, &contracts, block).await?);
+                    }
+                    continue;
+                }
+            };
+
+            let (Some(number), Some(hash)) = (log.block_number, log.block_hash) else {
+                continue;
+            };
+
+            // Removed logs of committed blocks are rolled back by the reorg check
+            if log.removed {
+                if let Some(block) = pending.as_mut() {
+                    block.logs.retain(|pending_log| {
+                        (pending_log.transaction_hash, pending_log.log_index)
+                            != (log.transaction_hash, log.log_index)
+                    });
+                }
+                continue;
+            }
+
+            match (pending.as_mut(), committed.as_ref()) {
+                (Some(block), _) if block.number == number && block.hash == hash => {
+                    block.logs.push(log)
+                }
+                // Logs of a block delivered after it was flushed
+                (_, Some(block)) if block.number == number && block.hash == hash => {
+                    self.commit_late_logs(chain, block, vec![log]).await?
+                }
+                _ => {
+                    let block = StreamedBlock::new(number, hash, log);
+                    if let Some(previous) = pending.replace(block)
+                        && previous.number < number
+                    {
+                        committed = Some(
+                            self.commit_streamed_block(chain, &contracts, previous)
+                                .await?,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    async fn ingest_range(
+        &self,
+        chain: &EvmChains,
+        contract: &TrackedContract,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<u64, AppError> {
+        let latest_block = self.provider.get_block_number().await?;
+        let confirmed_block = self.confirmed_block(chain, latest_block).await?;
+        if to_block > confirmed_block {
+            return Err(AppError::ConfigError(format!(
+                "Block {to_block} is past the confirmed block {confirmed_block}"
+            )));
+        }
+
+        let address = contract
+            .address
+            .parse::<Address>()
+            .map_err(|e| AppError::InvalidAddress(e.to_string()))?
+            .0
+            .0;
+
+        let mut inserted = 0;
+        let mut from_block_number = from_block;
+        while from_block_number <= to_block {
+            let to_block_number =
+                (from_block_number + self.provider.log_window() - 1).min(to_block);
+            let filter = with_event_signatures(
+                create_log_filter(&contract.address, from_block_number, to_block_number)?,
+                std::slice::from_ref(contract),
+            );
+
+            let mut logs = self.provider.get_logs(&filter).await?;
+            let headers = self
+                .fetch_headers(chain.id as u64, from_block_number, to_block_number, &logs)
+                .await?
+                .ok_or_else(|| {
+                    AppError::RpcError(format!(
+                        "Blocks {from_block_number} to {to_block_number} changed while fetching their headers"
+                    ))
+                })?;
+
+            logs.retain(|log| contract.wants_event(log.topics().first()));
+            self.fill_block_timestamps(chain.id as u64, &mut logs)
+                .await?;
+            let transactions = self.fetch_transactions(&logs).await?;
+
+            inserted += self
+                .ingestion_repo
+                .ingest_range(IngestRange {
+                    chain_id: chain.id as u64,
+                    blocks: headers.iter().map(EVMBlocks::from).collect(),
+                    logs,
+                    confirmed_block: self.settled_block(confirmed_block, latest_block),
+                    transactions,
+                    cursors: Vec::new(),
+                    ranges: vec![IngestedRange {
+                        address,
+                        from_block: from_block_number,
+                        to_block: to_block_number,
+                    }],
+                })
+                .await?;
+
+            from_block_number = to_block_number + 1;
+        }
+
+        Ok(inserted)
+    }
+
+    async fn hand_over_cursor(
+        &self,
+        chain: &EvmChains,
+        contract: &TrackedContract,
+    ) -> Result<Option<(u64, u64)>, AppError> {
+        let sync_log = self.find_or_create_cursor(chain, contract).await?;
+        let latest_block = self.provider.get_block_number().await?;
+        let confirmed_block = self.confirmed_block(chain, latest_block).await?;
+
+        let from_block = sync_log.next_block();
+        if from_block > confirmed_block {
+            return Ok(None);
+        }
+
+        let tip = self
+            .get_block_header(confirmed_block)
+            .await?
+            .ok_or_else(|| AppError::RpcError(format!("Block {confirmed_block} not found")))?;
+
+        // Storing the new tip lets the reorg check start from it
+        self.ingestion_repo
+            .ingest_range(IngestRange {
+                chain_id: chain.id as u64,
+                blocks: vec![EVMBlocks::from(&tip)],
+                logs: Vec::new(),
+                confirmed_block,
+                transactions: Vec::new(),
+                cursors: vec![CursorAdvance {
+                    address: sync_log.address,
+                    block_number: confirmed_block,
+                    previous_block: sync_log
+                        .last_synced_block_number
+                        .map(|block_number| block_number as u64),
+                }],
+                // The skipped blocks are covered once backfilled
+                ranges: Vec::new(),
+            })
+            .await?;
+
+        Ok(Some((from_block, confirmed_block)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, sync::Arc};
+
+    use alloy::{
+        primitives::Bytes,
+        rpc::types::{Transaction, TransactionReceipt},
+    };
+    use sqlx::types::chrono::NaiveDateTime;
+
+    use super::*;
+    use crate::{
+        infrastructure::blockchain::provider::LogStream,
+        services::entities::{evm_ingested_ranges::EVMIngestedRanges, evm_logs::EVMLogs},
+    };
+
+    const CHAIN_ID: u64 = 1;
+
+    /// Header of block `number` on branch `fork`, whose parent is on the same branch
+    /// unless it sits at or below `forked_after`.
+    fn header(number: u64, fork: u8, forked_after: u64) -> Header {
+        let block_hash = |number: u64, fork: u8| {
+            let mut hash = B256::left_padding_from(&number.to_be_bytes());
+            hash.0[0] = fork;
+            hash
+        };
+        let parent = number.saturating_sub(1);
+        let parent_fork = if parent > forked_after { fork } else { 0 };
+
+        Header {
+            hash: block_hash(number, fork),
+            inner: alloy::consensus::Header {
+                number,
+                parent_hash: block_hash(parent, parent_fork),
+                timestamp: 1_700_000_000 + number * 12,
+                ..Default::default()
+            },
+            total_difficulty: None,
+            size: None,
+        }
+    }
+
+    /// Chain whose blocks above `reorged_after` were replaced by a competing branch, with
+    /// no contract deployed on it.
+    struct Chain {
+        head: u64,
+        finalized: u64,
+        reorged_after: Option<u64>,
+        header_requests: Mutex<Vec<u64>>,
+
+        /// Logs polling finds, whatever the filter's addresses
+        logs: Vec<Log>,
+
+        /// Logs the next subscription delivers before disconnecting
+        subscription: Mutex<Vec<Log>>,
+    }
+
+    impl Chain {
+        fn new(head: u64, reorged_after: Option<u64>) -> Self {
+            Self {
+                head,
+                finalized: head,
+                reorged_after,
+                header_requests: Mutex::new(Vec::new()),
+                logs: Vec::new(),
+                subscription: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl BlockchainProvider for Chain {
+        async fn get_block_number(&self) -> Result<u64, AppError> {
+            Ok(self.head)
+        }
+
+        async fn get_block_number_by_tag(&self, tag: BlockNumberOrTag) -> Result<u64, AppError> {
+            match tag {
+                BlockNumberOrTag::Finalized => Ok(self.finalized),
+                _ => Ok(self.head),
+            }
+        }
+
+        async fn get_logs(&self, filter: &Filter) -> Result<Vec<Log>, AppError> {
+            let range =
+                filter.get_from_block().unwrap_or(0)..=filter.get_to_block().unwrap_or(self.head);
+            Ok(self
+                .logs
+                .iter()
+                .filter(|log| {
+                    log.block_number
+                        .is_some_and(|number| range.contains(&number))
+                })
+                .cloned()
+                .collect())
+        }
+
+        async fn get_block_header(&self, block_number: u64) -> Result<Option<Header>, AppError> {
+            self.header_requests.lock().unwrap().push(block_number);
+            if block_number > self.head {
+                return Ok(None);
+            }
+
+            Ok(Some(match self.reorged_after {
+                Some(fork) if block_number > fork => header(block_number, 1, fork),
+                _ => header(block_number, 0, 0),
+            }))
+        }
+
+        async fn get_code(&self, _: Address, _: u64) -> Result<Bytes, AppError> {
+            Ok(Bytes::new())
+        }
+
+        async fn get_block_receipts(
+            &self,
+            _: u64,
+        ) -> Result<Option<Vec<TransactionReceipt>>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            _: B256,
+        ) -> Result<Option<TransactionReceipt>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_transactions(
+            &self,
+            _: u64,
+        ) -> Result<Option<Vec<Transaction>>, AppError> {
+            unimplemented!()
+        }
+
+        async fn subscribe_logs(&self, _: &Filter) -> Result<LogStream, AppError> {
+            let logs: Vec<Log> = self.subscription.lock().unwrap().drain(..).collect();
+            Ok(stream::iter(logs).boxed())
+        }
+
+        fn log_window(&self) -> u64 {
+            100
+        }
+    }
+
+    /// Blocks, cursors and the block numbers of the logs stored per contract.
+    #[derive(Default)]
+    struct State {
+        blocks: BTreeMap<u64, EVMBlocks>,
+        cursors: BTreeMap<[u8; 20], Option<u64>>,
+        logs: BTreeMap<[u8; 20], BTreeSet<u64>>,
+        ingested: Vec<IngestRange>,
+    }
+
+    /// In-memory stand-in for every repository the use case writes to.
+    #[derive(Clone, Default)]
+    struct Store(Arc<Mutex<State>>);
+
+    impl Store {
+        fn with_blocks(self, blocks: std::ops::RangeInclusive<u64>) -> Self {
+            for number in blocks {
+                let mut block = EVMBlocks::from(&header(number, 0, 0));
+                block.chain_id = CHAIN_ID as i64;
+                self.0.lock().unwrap().blocks.insert(number, block);
+            }
+            self
+        }
+
+        fn with_cursor(self, address: [u8; 20], block_number: u64, logs: &[u64]) -> Self {
+            {
+                let mut state = self.0.lock().unwrap();
+                state.cursors.insert(address, Some(block_number));
+                state.logs.insert(address, logs.iter().copied().collect());
+            }
+            self
+        }
+
+        fn sync_log(&self, address: [u8; 20]) -> EVMSyncLogs {
+            sync_log(address, self.0.lock().unwrap().cursors[&address])
+        }
+    }
+
+    fn sync_log(address: [u8; 20], block_number: Option<u64>) -> EVMSyncLogs {
+        EVMSyncLogs {
+            address,
+            chain_id: CHAIN_ID as i64,
+            last_synced_block_number: block_number.map(|block_number| block_number as i64),
+            start_block: None,
+            created_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+        }
+    }
+
+    #[async_trait]
+    impl EVMLogsRepository for Store {
+        async fn create_bulk(&self, _: u64, _: Vec<Log>, _: u64) -> Result<(), sqlx::Error> {
+            unimplemented!()
+        }
+
+        async fn create(&self, _: u64, _: Log, _: bool) -> Result<EVMLogs, sqlx::Error> {
+            unimplemented!()
+        }
+
+        async fn list(&self, _: i64) -> Result<Vec<EVMLogs>, sqlx::Error> {
+            unimplemented!()
+        }
+
+        async fn delete(&self, _: i32) -> Result<(), sqlx::Error> {
+            unimplemented!()
+        }
+
+        async fn delete_by_address_after_block(
+            &self,
+            _: u64,
+            address: [u8; 20],
+            block_number: u64,
+        ) -> Result<u64, sqlx::Error> {
+            let mut state = self.0.lock().unwrap();
+            let logs = state.logs.entry(address).or_default();
+            let removed = logs.split_off(&(block_number + 1));
+            Ok(removed.len() as u64)
+        }
+
+        async fn confirm_logs(&self, _: u64, _: [u8; 20], _: u64) -> Result<u64, sqlx::Error> {
+            Ok(0)
+        }
+
+        async fn count(&self) -> Result<Option<i64>, sqlx::Error> {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait]
+    impl EVMSyncLogsRepository for Store {
+        async fn find_all(&self) -> Result<Vec<EVMSyncLogs>, sqlx::Error> {
+            unimplemented!()
+        }
+
+        async fn find_by_chain_id(&self, _: u64) -> Result<Vec<EVMSyncLogs>, sqlx::Error> {
+            let state = self.0.lock().unwrap();
+            Ok(state
+                .cursors
+                .iter()
+                .map(|(address, block_number)| sync_log(*address, *block_number))
+                .collect())
+        }
+
+        async fn find_by_address(
+            &self,
+            address: &str,
+            _: u64,
+        ) -> Result<Option<EVMSyncLogs>, sqlx::Error> {
+            let address = address.parse::<Address>().unwrap().0.0;
+            let state = self.0.lock().unwrap();
+            Ok(state
+                .cursors
+                .get(&address)
+                .map(|block_number| sync_log(address, *block_number)))
+        }
+
+        async fn create(
+            &self,
+            address: &str,
+            _: u64,
+            last_synced_block_number: Option<i64>,
+        ) -> Result<EVMSyncLogs, sqlx::Error> {
+            let address = address.parse::<Address>().unwrap().0.0;
+            let block_number = last_synced_block_number.map(|block_number| block_number as u64);
+            self.0.lock().unwrap().cursors.insert(address, block_number);
+            Ok(sync_log(address, block_number))
+        }
+
+        async fn find_or_create_by_address(
+            &self,
+            _: &str,
+            _: u64,
+        ) -> Result<EVMSyncLogs, sqlx::error::Error> {
+            unimplemented!()
+        }
+
+        async fn update_last_synced_block_number(
+            &self,
+            _: u64,
+            address: [u8; 20],
+            block_number: u64,
+        ) -> Result<EVMSyncLogs, sqlx::Error> {
+            self.0
+                .lock()
+                .unwrap()
+                .cursors
+                .insert(address, Some(block_number));
+            Ok(sync_log(address, Some(block_number)))
+        }
+
+        async fn find_ingested_ranges(
+            &self,
+            _: u64,
+            _: [u8; 20],
+        ) -> Result<Vec<EVMIngestedRanges>, sqlx::Error> {
+            unimplemented!()
+        }
+
+        async fn delete_ingested_ranges_after(
+            &self,
+            _: u64,
+            _: [u8; 20],
+            _: u64,
+        ) -> Result<(), sqlx::Error> {
+            Ok(())
+        }
+
+        async fn replace_ingested_ranges(
+            &self,
+            _: u64,
+            _: [u8; 20],
+            _: &[i64],
+            _: &[(u64, u64)],
+        ) -> Result<(), sqlx::Error> {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait]
+    impl EVMBlocksRepository for Store {
+        async fn find_recent(
+            &self,
+            _: u64,
+            up_to_block: u64,
+            limit: i64,
+        ) -> Result<Vec<EVMBlocks>, sqlx::Error> {
+            let state = self.0.lock().unwrap();
+            Ok(state
+                .blocks
+                .range(..=up_to_block)
+                .rev()
+                .take(limit as usize)
+                .map(|(_, block)| block.clone())
+                .collect())
+        }
+
+        async fn find_by_numbers(
+            &self,
+            _: u64,
+            numbers: &[u64],
+        ) -> Result<Vec<EVMBlocks>, sqlx::Error> {
+            let state = self.0.lock().unwrap();
+            Ok(numbers
+                .iter()
+                .filter_map(|number| state.blocks.get(number).cloned())
+                .collect())
+        }
+
+        async fn average_block_time(&self, _: u64, _: i64) -> Result<Option<f64>, sqlx::Error> {
+            unimplemented!()
+        }
+
+        async fn delete_after(&self, _: u64, block_number: u64) -> Result<u64, sqlx::Error> {
+            let mut state = self.0.lock().unwrap();
+            let removed = state.blocks.split_off(&(block_number + 1));
+            Ok(removed.len() as u64)
+        }
+    }
+
+    #[async_trait]
+    impl EVMTransactionsRepository for Store {
+        async fn upsert_bulk(&self, _: u64, _: Vec<EVMTransactions>) -> Result<(), sqlx::Error> {
+            unimplemented!()
+        }
+
+        async fn delete_after_block(&self, _: u64, _: u64) -> Result<u64, sqlx::Error> {
+            unimplemented!()
+        }
+    }
+
+    #[async_trait]
+    impl IngestionRepository for Store {
+        async fn ingest_range(&self, range: IngestRange) -> Result<u64, sqlx::Error> {
+            let mut state = self.0.lock().unwrap();
+            if range.cursors.iter().any(|cursor| {
+                state.cursors.get(&cursor.address).copied().flatten() != cursor.previous_block
+            }) {
+                return Err(sqlx::Error::RowNotFound);
+            }
+
+            for block in &range.blocks {
+                let block = EVMBlocks {
+                    chain_id: range.chain_id as i64,
+                    ..block.clone()
+                };
+                state.blocks.insert(block.number as u64, block);
+            }
+            for cursor in &range.cursors {
+                state
+                    .cursors
+                    .insert(cursor.address, Some(cursor.block_number));
+            }
+            state.ingested.push(range.clone());
+
+            Ok(range.logs.len() as u64)
+        }
+    }
+
+    type TestIndexLogUC = IndexLogUCImpl<Chain, Store, Store, Store, Store, Store>;
+
+    fn index_log_uc(chain: Chain, store: &Store, reorg_depth: u64) -> TestIndexLogUC {
+        IndexLogUCImpl::new(
+            chain,
+            store.clone(),
+            store.clone(),
+            None,
+            store.clone(),
+            store.clone(),
+            100,
+            reorg_depth,
+        )
+    }
+
+    fn evm_chain() -> EvmChains {
+        EvmChains {
+            id: CHAIN_ID as i64,
+            name: "testnet".into(),
+            last_synced_block_number: 0,
+            block_time: 12,
+            confirmation_depth: 0,
+            finality_tag: None,
+            enabled: true,
+            rpc_urls: Vec::new(),
+            ws_url: None,
+            created_at: NaiveDateTime::default(),
+            updated_at: NaiveDateTime::default(),
+        }
+    }
+
+    fn tracked(address: [u8; 20]) -> TrackedContract {
+        TrackedContract {
+            address: Address::from(address).to_string(),
+            unconfirmed: false,
+            start_block: None,
+            event_signatures: Vec::new(),
+        }
+    }
+
+    const CONTRACT: [u8; 20] = [0x11; 20];
+
+    #[tokio::test]
+    async fn finds_no_fork_while_the_stored_blocks_are_canonical() {
+        let store = Store::default()
+            .with_blocks(1..=10)
+            .with_cursor(CONTRACT, 10, &[]);
+        let uc = index_log_uc(Chain::new(12, None), &store, 5);
+
+        let fork = uc
+            .find_fork_point(&store.sync_log(CONTRACT), &mut HashMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(fork, None);
+        assert_eq!(*uc.provider.header_requests.lock().unwrap(), vec![10]);
+    }
+
+    #[tokio::test]
+    async fn finds_no_fork_without_stored_blocks() {
+        let store = Store::default().with_cursor(CONTRACT, 10, &[]);
+        let uc = index_log_uc(Chain::new(12, Some(3)), &store, 5);
+
+        let fork = uc
+            .find_fork_point(&store.sync_log(CONTRACT), &mut HashMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(fork, None);
+        assert!(uc.provider.header_requests.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn finds_a_fork_at_the_edge_of_the_window() {
+        let store = Store::default()
+            .with_blocks(1..=10)
+            .with_cursor(CONTRACT, 10, &[]);
+        let uc = index_log_uc(Chain::new(12, Some(6)), &store, 5);
+
+        let fork = uc
+            .find_fork_point(&store.sync_log(CONTRACT), &mut HashMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(fork, Some(6));
+        assert_eq!(
+            *uc.provider.header_requests.lock().unwrap(),
+            vec![10, 9, 8, 7, 6]
+        );
+    }
+
+    #[tokio::test]
+    async fn fails_without_rolling_back_when_a_stored_block_is_not_found() {
+        let store = Store::default()
+            .with_blocks(1..=10)
+            .with_cursor(CONTRACT, 10, &[4, 10]);
+        let uc = index_log_uc(Chain::new(8, None), &store, 5);
+
+        let synced = uc.execute_many(&evm_chain(), &[tracked(CONTRACT)]).await;
+
+        assert!(matches!(synced, Err(AppError::RpcError(_))));
+        let state = store.0.lock().unwrap();
+        assert_eq!(state.cursors[&CONTRACT], Some(10));
+        assert_eq!(state.logs[&CONTRACT], BTreeSet::from([4, 10]));
+        assert_eq!(state.blocks.keys().last(), Some(&10));
+    }
+
+    #[tokio::test]
+    async fn rewinds_below_the_window_when_the_reorg_is_deeper() {
+        let store = Store::default()
+            .with_blocks(1..=10)
+            .with_cursor(CONTRACT, 10, &[]);
+        let uc = index_log_uc(Chain::new(12, Some(3)), &store, 5);
+
+        let fork = uc
+            .find_fork_point(&store.sync_log(CONTRACT), &mut HashMap::new())
+            .await
+            .unwrap();
+
+        assert_eq!(fork, Some(5));
+    }
+
+    #[tokio::test]
+    async fn rolls_back_every_cursor_past_the_fork_and_reingests_it() {
+        let (lagging, behind) = ([0x22; 20], [0x33; 20]);
+        let store = Store::default()
+            .with_blocks(1..=10)
+            .with_cursor(CONTRACT, 10, &[4, 8, 10])
+            .with_cursor(lagging, 9, &[9])
+            .with_cursor(behind, 5, &[5]);
+        let uc = index_log_uc(Chain::new(12, Some(7)), &store, 5);
+        let contracts = [tracked(CONTRACT), tracked(lagging)];
+
+        uc.execute_many(&evm_chain(), &contracts).await.unwrap();
+
+        {
+            let state = store.0.lock().unwrap();
+            assert_eq!(state.cursors[&CONTRACT], Some(7));
+            assert_eq!(state.cursors[&lagging], Some(7));
+            assert_eq!(state.cursors[&behind], Some(5));
+            assert_eq!(state.logs[&CONTRACT], BTreeSet::from([4]));
+            assert!(state.logs[&lagging].is_empty());
+            assert_eq!(state.logs[&behind], BTreeSet::from([5]));
+            assert_eq!(state.blocks.keys().last(), Some(&7));
+            assert!(state.ingested.is_empty());
+        }
+
+        uc.execute_many(&evm_chain(), &contracts).await.unwrap();
+
+        let state = store.0.lock().unwrap();
+        assert_eq!(state.cursors[&CONTRACT], Some(12));
+        assert_eq!(state.cursors[&lagging], Some(12));
+        assert_eq!(B256::from(state.blocks[&10].hash), header(10, 1, 7).hash);
+    }
+
+    #[tokio::test]
+    async fn confirms_blocks_behind_the_head_without_a_finality_tag() {
+        let uc = index_log_uc(Chain::new(20, None), &Store::default(), 5);
+        let chain = EvmChains {
+            confirmation_depth: 3,
+            ..evm_chain()
+        };
+
+        assert_eq!(uc.confirmed_block(&chain, 20).await.unwrap(), 17);
+        assert_eq!(uc.confirmed_block(&chain, 2).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn confirms_up_to_the_finality_tag() {
+        let uc = index_log_uc(
+            Chain {
+                finalized: 15,
+                ..Chain::new(20, None)
+            },
+            &Store::default(),
+            5,
+        );
+        let chain = EvmChains {
+            confirmation_depth: 3,
+            finality_tag: Some("finalized".into()),
+            ..evm_chain()
+        };
+        let invalid = EvmChains {
+            finality_tag: Some("soon".into()),
+            ..evm_chain()
+        };
+
+        assert_eq!(uc.confirmed_block(&chain, 20).await.unwrap(), 15);
+        assert!(matches!(
+            uc.confirmed_block(&invalid, 20).await,
+            Err(AppError::ConfigError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn syncs_confirmed_contracts_up_to_the_confirmed_block_only() {
+        let pending = [0x22; 20];
+        let store = Store::default()
+            .with_cursor(CONTRACT, 10, &[])
+            .with_cursor(pending, 10, &[]);
+        let uc = index_log_uc(Chain::new(20, None), &store, 5);
+        let chain = EvmChains {
+            confirmation_depth: 3,
+            ..evm_chain()
+        };
+        let contracts = [
+            tracked(CONTRACT),
+            TrackedContract {
+                unconfirmed: true,
+                ..tracked(pending)
+            },
+        ];
+
+        uc.execute_many(&chain, &contracts).await.unwrap();
+
+        let state = store.0.lock().unwrap();
+        assert_eq!(state.cursors[&CONTRACT], Some(17));
+        assert_eq!(state.cursors[&pending], Some(20));
+
+        // Logs within the reorg depth wait for it to pass, even when confirmed
+        assert!(
+            state
+                .ingested
+                .iter()
+                .all(|range| range.confirmed_block == 15)
+        );
+    }
+
+    #[tokio::test]
+    async fn indexes_from_block_zero() {
+        let store = Store::default();
+        let uc = index_log_uc(Chain::new(5, None), &store, 5);
+        let contract = TrackedContract {
+            start_block: Some(0),
+            ..tracked(CONTRACT)
+        };
+
+        uc.execute(&evm_chain(), &contract).await.unwrap();
+
+        let state = store.0.lock().unwrap();
+        assert_eq!(state.cursors[&CONTRACT], Some(5));
+        assert_eq!(state.ingested[0].ranges[0].from_block, 0);
+    }
+
+    #[tokio::test]
+    async fn skips_contracts_without_code() {
+        let undeployed = [0x22; 20];
+        let store = Store::default();
+        let uc = index_log_uc(Chain::new(5, None), &store, 5);
+        let contracts = [
+            tracked(undeployed),
+            TrackedContract {
+                start_block: Some(1),
+                ..tracked(CONTRACT)
+            },
+        ];
+
+        uc.execute_many(&evm_chain(), &contracts).await.unwrap();
+
+        let state = store.0.lock().unwrap();
+        assert_eq!(state.cursors[&CONTRACT], Some(5));
+        assert!(!state.cursors.contains_key(&undeployed));
+    }
+
+    #[tokio::test]
+    async fn drops_a_range_whose_cursor_was_rewound_since_it_was_fetched() {
+        let store = Store::default().with_cursor(CONTRACT, 7, &[]);
+        let uc = index_log_uc(Chain::new(12, None), &store, 5);
+        let contract = tracked(CONTRACT);
+        let stale = [SyncTarget {
+            contract: &contract,
+            sync_log: sync_log(CONTRACT, Some(10)),
+        }];
+        let headers: Vec<Header> = (11..=12).map(|number| header(number, 0, 0)).collect();
+
+        uc.commit_range(&evm_chain(), &stale, 12, &headers, Vec::new(), 12)
+            .await
+            .unwrap();
+
+        let state = store.0.lock().unwrap();
+        assert_eq!(state.cursors[&CONTRACT], Some(7));
+        assert!(state.blocks.is_empty());
+        assert!(state.ingested.is_empty());
+    }
+
+    fn streamed_log(address: [u8; 20], block_number: u64) -> Log {
+        Log {
+            inner: alloy::primitives::Log::new_unchecked(
+                Address::from(address),
+                vec![B256::repeat_byte(0xee)],
+                Bytes::new(),
+            ),
+            block_hash: Some(header(block_number, 0, 0).hash),
+            block_number: Some(block_number),
+            block_timestamp: Some(header(block_number, 0, 0).timestamp),
+            transaction_hash: Some(B256::repeat_byte(block_number as u8)),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn backfills_from_the_cursor_before_committing_a_streamed_block() {
+        let store = Store::default().with_cursor(CONTRACT, 10, &[]);
+        let chain = Chain::new(16, None);
+        *chain.subscription.lock().unwrap() =
+            vec![streamed_log(CONTRACT, 15), streamed_log(CONTRACT, 16)];
+        let uc = index_log_uc(chain, &store, 5);
+
+        let disconnected = uc.stream(&evm_chain(), &[tracked(CONTRACT)]).await;
+
+        assert!(matches!(disconnected, Err(AppError::SubscriptionClosed)));
+        let state = store.0.lock().unwrap();
+        let ranges: Vec<(u64, u64)> = state
+            .ingested
+            .iter()
+            .flat_map(|range| &range.ranges)
+            .map(|range| (range.from_block, range.to_block))
+            .collect();
+        assert_eq!(ranges, vec![(11, 14), (15, 15)]);
+        assert_eq!(state.ingested[1].logs, vec![streamed_log(CONTRACT, 15)]);
+
+        // Block 16 was still pending, so polling picks it up from the cursor
+        assert_eq!(state.cursors[&CONTRACT], Some(15));
+    }
+
+    #[tokio::test]
+    async fn stores_the_header_of_every_block_of_the_range() {
+        let store = Store::default().with_cursor(CONTRACT, 0, &[]);
+        let chain = Chain {
+            logs: vec![streamed_log(CONTRACT, 20), streamed_log(CONTRACT, 50)],
+            ..Chain::new(100, None)
+        };
+        let uc = index_log_uc(chain, &store, 5);
+
+        uc.execute_many(&evm_chain(), &[tracked(CONTRACT)])
+            .await
+            .unwrap();
+
+        let mut requests = uc.provider.header_requests.lock().unwrap().clone();
+        requests.sort();
+        assert_eq!(requests, (1..=100).collect::<Vec<_>>());
+        let state = store.0.lock().unwrap();
+        assert_eq!(state.cursors[&CONTRACT], Some(100));
+        assert_eq!(
+            state.blocks.keys().copied().collect::<Vec<_>>(),
+            (1..=100).collect::<Vec<_>>()
+        );
+        assert_eq!(state.ingested[0].logs.len(), 2);
+    }
 }
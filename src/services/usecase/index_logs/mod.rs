@@ -1 +1,3 @@
+pub mod block_timestamps;
 pub mod index_log_uc;
+pub mod transactions;
@@ -0,0 +1,309 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use alloy::{
+    primitives::B256,
+    rpc::types::{Log, Transaction, TransactionReceipt},
+};
+use futures::{StreamExt, TryStreamExt, stream};
+
+use crate::{
+    infrastructure::blockchain::provider::BlockchainProvider,
+    services::{entities::evm_transactions::EVMTransactions, usecase::errors::AppError},
+};
+
+/// Blocks or transactions requested concurrently while enriching a batch.
+const ENRICH_CONCURRENCY: usize = 8;
+
+/// Fetches the transactions and receipts behind a batch of logs: one
+/// `eth_getBlockByNumber` call per block for the transactions, and one
+/// `eth_getBlockReceipts` call per block for their receipts until the node turns out
+/// not to serve it, then one `eth_getTransactionReceipt` call per transaction.
+pub struct TransactionFetcher {
+    block_receipts: AtomicBool,
+}
+
+impl Default for TransactionFetcher {
+    fn default() -> Self {
+        Self {
+            block_receipts: AtomicBool::new(true),
+        }
+    }
+}
+
+impl TransactionFetcher {
+    /// Transactions the logs were emitted by. Transactions no longer found, reorged
+    /// out since their logs were fetched, are left out.
+    pub async fn fetch<P>(
+        &self,
+        provider: &P,
+        logs: &[Log],
+    ) -> Result<Vec<EVMTransactions>, AppError>
+    where
+        P: BlockchainProvider + ?Sized,
+    {
+        let mut hashes_by_block: BTreeMap<u64, BTreeSet<B256>> = BTreeMap::new();
+        for log in logs {
+            if let (Some(block_number), Some(hash)) = (log.block_number, log.transaction_hash) {
+                hashes_by_block
+                    .entry(block_number)
+                    .or_default()
+                    .insert(hash);
+            }
+        }
+
+        let transactions: Vec<Vec<EVMTransactions>> = stream::iter(hashes_by_block)
+            .map(|(block_number, hashes)| self.block_transactions(provider, block_number, hashes))
+            .buffered(ENRICH_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        Ok(transactions.into_iter().flatten().collect())
+    }
+
+    /// Transactions among `hashes` mined in the block, along with their receipts.
+    async fn block_transactions<P>(
+        &self,
+        provider: &P,
+        block_number: u64,
+        hashes: BTreeSet<B256>,
+    ) -> Result<Vec<EVMTransactions>, AppError>
+    where
+        P: BlockchainProvider + ?Sized,
+    {
+        let (transactions, receipts) = futures::try_join!(
+            provider.get_block_transactions(block_number),
+            self.receipts(provider, block_number, hashes)
+        )?;
+
+        let transactions: HashMap<B256, Transaction> = transactions
+            .unwrap_or_default()
+            .into_iter()
+            .map(|transaction| (*transaction.inner.tx_hash(), transaction))
+            .collect();
+
+        receipts
+            .iter()
+            .filter_map(|receipt| {
+                let transaction = transactions.get(&receipt.transaction_hash)?;
+                Some(
+                    EVMTransactions::try_from((transaction, receipt))
+                        .map_err(|e| AppError::RpcError(e.to_string())),
+                )
+            })
+            .collect()
+    }
+
+    /// Receipts of the `hashes` mined in the block.
+    async fn receipts<P>(
+        &self,
+        provider: &P,
+        block_number: u64,
+        hashes: BTreeSet<B256>,
+    ) -> Result<Vec<TransactionReceipt>, AppError>
+    where
+        P: BlockchainProvider + ?Sized,
+    {
+        if self.block_receipts.load(Ordering::Relaxed) {
+            match provider.get_block_receipts(block_number).await {
+                Ok(receipts) => {
+                    return Ok(receipts
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter(|receipt| hashes.contains(&receipt.transaction_hash))
+                        .collect());
+                }
+                Err(AppError::UnsupportedMethod(message)) => {
+                    if self.block_receipts.swap(false, Ordering::Relaxed) {
+                        println!(
+                            "eth_getBlockReceipts unsupported ({message}), fetching receipts per transaction"
+                        );
+                    }
+                }
+                Err(err) => return Err(err),
+            }
+        }
+
+        let mut receipts = Vec::with_capacity(hashes.len());
+        for hash in hashes {
+            receipts.extend(provider.get_transaction_receipt(hash).await?);
+        }
+
+        Ok(receipts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU32;
+
+    use alloy::{
+        primitives::{Address, Bytes},
+        rpc::types::{BlockNumberOrTag, Filter, Header, Transaction},
+    };
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    use super::*;
+    use crate::infrastructure::blockchain::provider::LogStream;
+
+    fn hash_of(n: u64) -> B256 {
+        B256::left_padding_from(&n.to_be_bytes())
+    }
+
+    /// Node serving one transaction per block, with `eth_getBlockReceipts` only when
+    /// `block_receipts` is set.
+    struct Node {
+        block_receipts: bool,
+        receipt_calls: AtomicU32,
+        block_calls: AtomicU32,
+    }
+
+    fn receipt(block_number: u64) -> TransactionReceipt {
+        serde_json::from_value(json!({
+            "transactionHash": hash_of(block_number),
+            "transactionIndex": "0x0",
+            "blockHash": hash_of(block_number + 1_000),
+            "blockNumber": format!("{block_number:#x}"),
+            "from": Address::repeat_byte(0xaa),
+            "to": Address::repeat_byte(0xbb),
+            "cumulativeGasUsed": "0xc350",
+            "gasUsed": "0xc350",
+            "effectiveGasPrice": "0x77359400",
+            "contractAddress": null,
+            "logs": [],
+            "logsBloom": format!("0x{}", "00".repeat(256)),
+            "type": "0x0",
+            "status": "0x1",
+        }))
+        .unwrap()
+    }
+
+    #[async_trait]
+    impl BlockchainProvider for Node {
+        async fn get_block_number(&self) -> Result<u64, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_number_by_tag(&self, _: BlockNumberOrTag) -> Result<u64, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_logs(&self, _: &Filter) -> Result<Vec<Log>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_header(&self, _: u64) -> Result<Option<Header>, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_code(&self, _: Address, _: u64) -> Result<Bytes, AppError> {
+            unimplemented!()
+        }
+
+        async fn get_block_receipts(
+            &self,
+            block_number: u64,
+        ) -> Result<Option<Vec<TransactionReceipt>>, AppError> {
+            self.receipt_calls.fetch_add(1, Ordering::Relaxed);
+            match self.block_receipts {
+                true => Ok(Some(vec![receipt(block_number)])),
+                false => Err(AppError::UnsupportedMethod("method not found".into())),
+            }
+        }
+
+        async fn get_transaction_receipt(
+            &self,
+            hash: B256,
+        ) -> Result<Option<TransactionReceipt>, AppError> {
+            self.receipt_calls.fetch_add(1, Ordering::Relaxed);
+            let block_number = u64::from_be_bytes(hash[24..].try_into().unwrap());
+            Ok(Some(receipt(block_number)))
+        }
+
+        async fn get_block_transactions(
+            &self,
+            block_number: u64,
+        ) -> Result<Option<Vec<Transaction>>, AppError> {
+            self.block_calls.fetch_add(1, Ordering::Relaxed);
+            let hash = hash_of(block_number);
+            let transaction = serde_json::from_value(json!({
+                "hash": hash,
+                "nonce": "0x0",
+                "blockHash": hash_of(block_number + 1_000),
+                "blockNumber": format!("{block_number:#x}"),
+                "transactionIndex": "0x0",
+                "from": Address::repeat_byte(0xaa),
+                "to": Address::repeat_byte(0xbb),
+                "value": format!("{:#x}", block_number * 10),
+                "gasPrice": "0x77359400",
+                "gas": "0x186a0",
+                "input": "0x",
+                "type": "0x0",
+                "v": "0x1b",
+                "r": format!("0x{}", "01".repeat(32)),
+                "s": format!("0x{}", "02".repeat(32)),
+            }))
+            .unwrap();
+
+            Ok(Some(vec![transaction]))
+        }
+
+        async fn subscribe_logs(&self, _: &Filter) -> Result<LogStream, AppError> {
+            unimplemented!()
+        }
+
+        fn log_window(&self) -> u64 {
+            unimplemented!()
+        }
+    }
+
+    fn logs() -> Vec<Log> {
+        // Two logs share the transaction of block 7
+        [7, 7, 9]
+            .into_iter()
+            .map(|block_number| Log {
+                block_number: Some(block_number),
+                transaction_hash: Some(hash_of(block_number)),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn fetches_one_block_and_receipt_list_per_block() {
+        let node = Node {
+            block_receipts: true,
+            receipt_calls: AtomicU32::new(0),
+            block_calls: AtomicU32::new(0),
+        };
+
+        let transactions = TransactionFetcher::default()
+            .fetch(&node, &logs())
+            .await
+            .unwrap();
+
+        assert_eq!(transactions.len(), 2);
+        assert_eq!(transactions[1].block_number, 9);
+        assert_eq!(transactions[1].value.to_string(), "90");
+        assert_eq!(node.receipt_calls.load(Ordering::Relaxed), 2);
+        assert_eq!(node.block_calls.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_transaction_receipts() {
+        let node = Node {
+            block_receipts: false,
+            receipt_calls: AtomicU32::new(0),
+            block_calls: AtomicU32::new(0),
+        };
+        let fetcher = TransactionFetcher::default();
+
+        let transactions = fetcher.fetch(&node, &logs()).await.unwrap();
+
+        assert_eq!(transactions.len(), 2);
+        assert!(!fetcher.block_receipts.load(Ordering::Relaxed));
+    }
+}
@@ -1,15 +1,60 @@
 use crate::services::{
-    dtos::index_engine::{FeeAmountEnabledRequest, OwnerChangedRequest, PoolCreatedRequest},
+    dtos::{
+        backfill::{BackfillJob, BackfillReport},
+        coverage::CoverageReport,
+        index_engine::{
+            Erc20ApprovalRequest, Erc20TransferRequest, Erc721TransferRequest,
+            Erc1155TransferRequest, Erc1155UriRequest, FeeAmountEnabledRequest, FlashRequest,
+            LiquidityRequest, OwnerChangedRequest, PairCreatedRequest, PairLiquidityRequest,
+            PairSwapRequest, PairSyncRequest, PoolCreatedRequest, PoolInitializedRequest,
+            SwapRequest,
+        },
+        index_logs::TrackedContract,
+    },
+    entities::evm_chains::EvmChains,
     usecase::errors::AppError,
 };
 
+pub mod backfill;
+pub mod coverage;
 pub mod errors;
 pub mod index_engine;
 pub mod index_logs;
 
 #[async_trait::async_trait]
 pub trait IndexLogUC: Send + Sync {
-    async fn execute(&self, chain_id: u64, address: String) -> Result<(), AppError>;
+    async fn execute(&self, chain: &EvmChains, contract: &TrackedContract) -> Result<(), AppError>;
+    async fn execute_many(
+        &self,
+        chain: &EvmChains,
+        contracts: &[TrackedContract],
+    ) -> Result<(), AppError>;
+
+    /// Indexes logs pushed by a log subscription until it disconnects, backfilling
+    /// from the cursors before each streamed block.
+    async fn stream(
+        &self,
+        chain: &EvmChains,
+        contracts: &[TrackedContract],
+    ) -> Result<(), AppError>;
+
+    /// Indexes the logs of a confirmed block range without moving the cursor, and
+    /// returns the number of logs stored.
+    async fn ingest_range(
+        &self,
+        chain: &EvmChains,
+        contract: &TrackedContract,
+        from_block: u64,
+        to_block: u64,
+    ) -> Result<u64, AppError>;
+
+    /// Moves the cursor, created when missing, to the confirmed head and returns the
+    /// block range it skipped, left for a backfill to index.
+    async fn hand_over_cursor(
+        &self,
+        chain: &EvmChains,
+        contract: &TrackedContract,
+    ) -> Result<Option<(u64, u64)>, AppError>;
 }
 
 #[async_trait::async_trait]
@@ -17,4 +62,47 @@ pub trait IndexEngineUC: Send + Sync {
     async fn on_pool_created(&self, data: PoolCreatedRequest) -> Result<(), AppError>;
     async fn on_owner_changed(&self, data: OwnerChangedRequest) -> Result<(), AppError>;
     async fn on_fee_amount_enabled(&self, data: FeeAmountEnabledRequest) -> Result<(), AppError>;
+    async fn on_pool_initialized(&self, data: PoolInitializedRequest) -> Result<(), AppError>;
+    async fn on_swap(&self, data: SwapRequest) -> Result<(), AppError>;
+    async fn on_mint(&self, data: LiquidityRequest) -> Result<(), AppError>;
+    async fn on_burn(&self, data: LiquidityRequest) -> Result<(), AppError>;
+    async fn on_flash(&self, data: FlashRequest) -> Result<(), AppError>;
+    async fn on_erc20_transfer(&self, data: Erc20TransferRequest) -> Result<(), AppError>;
+    async fn on_erc20_approval(&self, data: Erc20ApprovalRequest) -> Result<(), AppError>;
+    async fn on_erc721_transfer(&self, data: Erc721TransferRequest) -> Result<(), AppError>;
+    async fn on_erc1155_transfer(&self, data: Erc1155TransferRequest) -> Result<(), AppError>;
+    async fn on_erc1155_uri(&self, data: Erc1155UriRequest) -> Result<(), AppError>;
+    async fn on_pair_created(&self, data: PairCreatedRequest) -> Result<(), AppError>;
+    async fn on_pair_sync(&self, data: PairSyncRequest) -> Result<(), AppError>;
+    async fn on_pair_swap(&self, data: PairSwapRequest) -> Result<(), AppError>;
+    async fn on_pair_mint(&self, data: PairLiquidityRequest) -> Result<(), AppError>;
+    async fn on_pair_burn(&self, data: PairLiquidityRequest) -> Result<(), AppError>;
+}
+
+#[async_trait::async_trait]
+pub trait BackfillUC: Send + Sync {
+    /// Indexes the job's range in chunks, resuming the chunks earlier runs left pending.
+    async fn execute(
+        &self,
+        chain: &EvmChains,
+        job: &BackfillJob,
+    ) -> Result<BackfillReport, AppError>;
+}
+
+#[async_trait::async_trait]
+pub trait CoverageUC: Send + Sync {
+    /// Compares the block ranges ingested for the contract with its cursor.
+    async fn report(
+        &self,
+        chain: &EvmChains,
+        contract: &TrackedContract,
+    ) -> Result<CoverageReport, AppError>;
+
+    /// Re-fetches the gaps of the contract's coverage, merges its overlapping ranges,
+    /// and returns the coverage left afterwards.
+    async fn repair(
+        &self,
+        chain: &EvmChains,
+        contract: &TrackedContract,
+    ) -> Result<CoverageReport, AppError>;
 }